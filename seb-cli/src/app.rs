@@ -5,6 +5,7 @@ use seb::ast::{Biblio, BiblioResolver, Entry, Resolver as EntryResolver};
 use crate::interact::{user_resolve_entry, user_select, user_select_entry};
 
 #[inline]
+#[allow(clippy::result_large_err)] // the resolver is returned so it can be resolved by the user
 pub fn take_first_resolvable(bib: Result<Biblio, BiblioResolver>) -> Result<Entry, EntryResolver> {
     bib.map(|bib| bib.into_entries().remove(0))
         .or_else(|mut b| b.checked_remove(0).expect("BiblioResolver was empty!"))
@@ -64,6 +65,22 @@ pub fn check_entry_field_duplication(bib: &Biblio, name: &str, value: &str) -> e
     }
 }
 
+/// Checks that no entry uses the cite key, ignoring case, so that cite keys are unique across
+/// every bibliography file.
+pub fn check_cite_is_unique(bib: &Biblio, cite: &str) -> eyre::Result<()> {
+    trace!("Checking current bibliography for an entry with the cite key '{cite}'");
+    if bib
+        .entries()
+        .any(|entry| entry.cite().eq_ignore_ascii_case(cite))
+    {
+        Err(eyre!(
+            "An entry already exists with the cite key of '{cite}'"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 #[test]
 fn field_dup_macro() {
     use seb::ast::Entry;
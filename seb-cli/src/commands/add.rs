@@ -1,9 +1,19 @@
+use std::path::Path;
+
+use super::list::YearRange;
 use crate::{
     app::{self, resolve_entry_resolver, take_first_resolvable, user_select_resolvable},
-    interact::{user_resolve_entry, user_select_map},
+    config::Config,
+    interact::{user_resolve_entry, user_select},
+    template::Template,
 };
 
-use seb::ast::{Biblio, BiblioResolver, Entry};
+use seb::{
+    ast::{Biblio, BiblioResolver, Entry, FieldQuery},
+    format::{CitationCff, CodeMeta, Format},
+    provider::{IdKind, Registry},
+    EntryStub, TitleQuery,
+};
 
 use clap::Subcommand;
 use eyre::{eyre, Context};
@@ -13,6 +23,9 @@ use log::{debug, info, trace};
 #[allow(clippy::module_name_repetitions)]
 pub enum AddCommands {
     /// Search for reference by doi
+    ///
+    /// DOIs registered with `DataCite`, such as the DOIs of Zenodo and Figshare, are looked up using
+    /// the `DataCite` API and datasets and software are added as `dataset` and `software` entries.
     #[clap(arg_required_else_help = true)]
     Doi {
         /// The doi to search for
@@ -48,12 +61,54 @@ pub enum AddCommands {
         #[clap(long)]
         cite: Option<String>,
     },
+    /// Add a web page using the metadata embedded in the page
+    ///
+    /// The metadata is read from Highwire Press `citation_*` meta tags, JSON-LD, `COinS`, Dublin
+    /// Core or `OpenGraph` meta tags. Pages of a journal are added as an article and other pages as
+    /// an online entry, and the `urldate` field is set to today.
+    #[clap(arg_required_else_help = true)]
+    Url {
+        /// The url of the page
+        url: String,
+
+        /// The cite key of the new entry
+        ///
+        /// This will override any citation key either present or generated by seb.
+        #[clap(long)]
+        cite: Option<String>,
+    },
+    /// Add software from its `CITATION.cff` or `codemeta.json` file
+    ///
+    /// The file is read from a path or downloaded from a url, where the url of a file in a GitHub
+    /// or GitLab repository is changed to the url of the raw file. The `preferred-citation` of a
+    /// `CITATION.cff` file is added when there is one, and otherwise the software is added as a
+    /// `software` entry with the authors, ORCID iDs, version, release date, DOI and repository.
+    #[clap(arg_required_else_help = true)]
+    Cff {
+        /// The path or url of the `CITATION.cff` or `codemeta.json` file
+        source: String,
+
+        /// The cite key of the new entry
+        ///
+        /// This will override any citation key either present or generated by seb.
+        #[clap(long)]
+        cite: Option<String>,
+    },
     /// Search for entry using the title
     #[clap(arg_required_else_help = true)]
     Title {
         /// The title to search for
         title: String,
 
+        /// Only search for entries with an author matching this name
+        #[clap(long)]
+        author: Option<String>,
+
+        /// Only search for entries published in the year or range of years, such as `2019`,
+        /// `2010..2020`, `2010..` or `..2020`
+        #[clap(long)]
+        year: Option<YearRange>,
+
         /// The cite key of the new entry
         ///
         /// This will override any citation key either present or generated by seb.
@@ -68,12 +123,13 @@ impl AddCommands {
     pub(super) fn execute(
         self,
         biblio: &mut Biblio,
+        config: &Config,
         mut interact: bool,
     ) -> Result<String, DynError> {
         // take a copy of interact bool and use it to determine if the first resolvable item should
         // be resolved
         let resolve = interact;
-        let search_results = self.search_entries(biblio, &mut interact)?;
+        let search_results = self.search_entries(biblio, &config.registry()?, &mut interact)?;
 
         let mut entry = if interact {
             user_select_resolvable(search_results)?.or_else(resolve_entry_resolver)?
@@ -87,9 +143,10 @@ impl AddCommands {
             }
         };
 
-        self.set_cite(&mut entry);
+        self.set_cite(&mut entry, config.cite_template().as_ref());
 
         let cite = entry.cite().to_owned();
+        app::check_cite_is_unique(biblio, &cite)?;
         biblio.insert(entry);
         info!("Entry with cite '{cite}' added to bibliography");
         Ok(cite)
@@ -98,13 +155,20 @@ impl AddCommands {
     fn search_entries(
         &self,
         biblio: &Biblio,
+        providers: &Registry,
         interact: &mut bool,
     ) -> eyre::Result<Result<Biblio, BiblioResolver>> {
+        let lookup = |kind, id: &str| {
+            providers
+                .lookup(kind, id)
+                .wrap_err_with(|| eyre!("Cannot find the entry"))
+        };
+
         match self {
             AddCommands::Doi { doi, .. } => {
                 debug!("doi subcommand called with a value of '{doi}'");
                 app::check_entry_field_duplication(biblio, "doi", doi)?;
-                seb::entries_by_doi(doi).wrap_err_with(|| eyre!("Cannot find the entry"))
+                lookup(IdKind::Doi, doi)
             }
             AddCommands::Ietf { rfc_number, .. } => {
                 debug!("ietf subcommand called with value of '{}'", &rfc_number);
@@ -113,39 +177,69 @@ impl AddCommands {
                         && contains_field(e, "series", "Request for Comment")
                         && contains_field(e, "number", rfc_number.to_string().as_str())
                 });
-                seb::entries_by_rfc(*rfc_number).wrap_err_with(|| eyre!("Cannot find the entry"))
+                lookup(IdKind::Rfc, &rfc_number.to_string())
             }
             AddCommands::Isbn { isbn, .. } => {
                 debug!("isbn subcommand called with value of '{isbn}'");
                 app::check_entry_field_duplication(biblio, "isbn", isbn)?;
-                seb::entries_by_isbn(isbn).wrap_err_with(|| eyre!("Cannot find the entry"))
+                lookup(IdKind::Isbn, isbn)
             }
-            AddCommands::Title { title, .. } => {
+            AddCommands::Url { url, .. } => {
+                debug!("url subcommand called with value of '{url}'");
+                app::check_entry_field_duplication(biblio, "url", url)?;
+                lookup(IdKind::Url, url)
+            }
+            AddCommands::Cff { source, .. } => {
+                debug!("cff subcommand called with value of '{source}'");
+                let found = read_citation_file(source)
+                    .wrap_err_with(|| eyre!("Cannot read the citation file '{source}'"))?;
+                let doi = match &found {
+                    Ok(biblio) => biblio.entries().find_map(|entry| entry.get_field("doi")),
+                    Err(resolver) => resolver.iter().find_map(|entry| entry.get_field("doi")),
+                };
+                if let Some(doi) = doi {
+                    app::check_entry_field_duplication(biblio, "doi", doi)?;
+                }
+                Ok(found)
+            }
+            AddCommands::Title {
+                title,
+                author,
+                year,
+                ..
+            } => {
                 debug!("title subcommand called with value of '{title}'");
                 // We don't check duplication with title because it is unlikely to be a complete
                 // title or to match fully with an existing entry.
-                // Instead we will perform the search for entry stubs (doi, title) and once
-                // one is selected then perform the duplication check on the doi.
-                trace!("Searching parts of an entry by title..");
-                let mut entry_stubs = seb::entry_stubs_by_title(title)?;
+                // Instead we will perform the search for entry stubs and once one is selected
+                // then perform the duplication check on the doi.
+                let mut query = TitleQuery::new(title);
+                if let Some(author) = author {
+                    query = query.author(author);
+                }
+                if let Some(year) = year {
+                    query = query.years(year.start, year.end);
+                }
 
-                let index = if *interact {
-                    user_select_map("Confirm entry", &entry_stubs, |(_, title)| title.clone())?
+                trace!("Searching parts of an entry by title..");
+                let stub = if *interact {
+                    user_select_stub(query)?
                 } else {
                     trace!("Interact mode not enabled - taking first entry part");
-                    0
+                    seb::entry_stubs_by_query(&query)?.swap_remove(0)
                 };
 
                 *interact = false;
 
-                let doi = entry_stubs.remove(index).0;
-                app::check_entry_field_duplication(biblio, "doi", &doi)?;
-                seb::entries_by_doi(&doi).wrap_err_with(|| eyre!("Cannot find the entry"))
+                app::check_entry_field_duplication(biblio, "doi", &stub.doi)?;
+                lookup(IdKind::Doi, &stub.doi)
             }
         }
     }
 
-    fn set_cite(self, entry: &mut Entry) {
+    /// Sets the cite key given with `--cite`, or the cite key from the cite key template of the
+    /// settings when there is one.
+    fn set_cite(self, entry: &mut Entry, cite_template: Option<&Template>) {
         match self {
             AddCommands::Doi {
                 cite: Some(cite), ..
@@ -155,17 +249,111 @@ impl AddCommands {
             }
             | AddCommands::Isbn {
                 cite: Some(cite), ..
+            }
+            | AddCommands::Url {
+                cite: Some(cite), ..
+            }
+            | AddCommands::Cff {
+                cite: Some(cite), ..
             } => entry.set_cite(cite),
-            _ => {}
+            _ => {
+                if let Some(cite) = cite_template.and_then(|template| template.render_cite(entry)) {
+                    entry.set_cite(cite);
+                }
+            }
         }
     }
 }
 
-fn contains_field(entry: &Entry, field_name: &str, value: &str) -> bool {
-    use seb::ast::FieldQuery;
+/// Asks the user to pick one of the entry stubs found by the `query`, offering to load the next
+/// page of stubs when the last page was full.
+fn user_select_stub(mut query: TitleQuery) -> eyre::Result<EntryStub> {
+    const MORE: &str = "More results...";
+
+    let mut stubs = seb::entry_stubs_by_query(&query)?;
+    let mut more = stubs.len() >= query.rows;
+    loop {
+        let mut items = stubs.iter().map(ToString::to_string).collect::<Vec<_>>();
+        if more {
+            items.push(MORE.to_owned());
+        }
+
+        let index = user_select("Confirm entry", &items)?;
+        if index < stubs.len() {
+            return Ok(stubs.swap_remove(index));
+        }
+
+        query = query.next_page();
+        trace!(
+            "Searching the next page of entry parts from {}..",
+            query.offset
+        );
+        match seb::entry_stubs_by_query(&query) {
+            Ok(page) => {
+                more = page.len() >= query.rows;
+                stubs.extend(page);
+            }
+            Err(e) if e.kind() == seb::ErrorKind::NoValue => more = false,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
 
+/// Reads the entries of the `CITATION.cff` or `codemeta.json` file at the `source` path or url,
+/// where a file with the `json` extension is read as a `codemeta.json` file.
+fn read_citation_file(source: &str) -> Result<Result<Biblio, BiblioResolver>, seb::Error> {
+    let is_codemeta = Path::new(source)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(CodeMeta::ext()));
+
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let url = raw_file_url(source);
+        trace!("Downloading the citation file from '{url}'");
+        if is_codemeta {
+            seb::entries_by_url::<CodeMeta>(&url)
+        } else {
+            seb::entries_by_url::<CitationCff>(&url)
+        }
+    } else {
+        let text =
+            std::fs::read_to_string(source).map_err(|e| seb::Error::wrap(seb::ErrorKind::IO, e))?;
+        if is_codemeta {
+            CodeMeta::new(text).parse()
+        } else {
+            CitationCff::new(text).parse()
+        }
+    }
+}
+
+/// The url of the raw file of a url of a file shown in a GitHub or GitLab repository, such as
+/// `https://github.com/owner/repo/blob/main/CITATION.cff`, or the `url` for any other url.
+fn raw_file_url(url: &str) -> String {
+    if let Some(path) = url.strip_prefix("https://github.com/") {
+        if let Some((repo, file)) = path.split_once("/blob/") {
+            return format!("https://raw.githubusercontent.com/{repo}/{file}");
+        }
+    }
+    url.replacen("/-/blob/", "/-/raw/", 1)
+}
+
+fn contains_field(entry: &Entry, field_name: &str, value: &str) -> bool {
     entry
         .get_field(field_name)
-        .map(|v| v.as_ref() == value)
-        .unwrap_or_default()
+        .is_some_and(|v| v.as_ref() == value)
+}
+
+#[test]
+fn repository_file_urls_are_changed_to_raw_file_urls() {
+    assert_eq!(
+        "https://raw.githubusercontent.com/mc1098/seb/main/CITATION.cff",
+        raw_file_url("https://github.com/mc1098/seb/blob/main/CITATION.cff")
+    );
+    assert_eq!(
+        "https://gitlab.com/group/project/-/raw/main/codemeta.json",
+        raw_file_url("https://gitlab.com/group/project/-/blob/main/codemeta.json")
+    );
+    assert_eq!(
+        "https://example.org/CITATION.cff",
+        raw_file_url("https://example.org/CITATION.cff")
+    );
 }
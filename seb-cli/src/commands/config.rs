@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use crate::config::{self, Config, Key, Layers};
+
+use clap::{ArgEnum, Subcommand};
+use eyre::eyre;
+
+type DynError = Box<dyn std::error::Error>;
+
+#[derive(Subcommand)]
+#[allow(clippy::module_name_repetitions)]
+pub enum ConfigCommands {
+    /// Show the value of a setting
+    #[clap(arg_required_else_help = true)]
+    Get {
+        /// The key of the setting
+        #[clap(arg_enum)]
+        key: Key,
+    },
+    /// Change a setting in the project `.seb.toml` file, or the user configuration file with
+    /// `--global`
+    ///
+    /// The project file is the nearest `.seb.toml` file in the current directory or its parents,
+    /// which is created in the current directory when there is none.
+    #[clap(arg_required_else_help = true)]
+    Set {
+        /// The key of the setting
+        #[clap(arg_enum)]
+        key: Key,
+
+        /// The value of the setting
+        value: String,
+
+        /// Change the user configuration file at `$XDG_CONFIG_HOME/seb/config.toml`
+        #[clap(long)]
+        global: bool,
+    },
+    /// List the settings that are set and where each setting comes from
+    List,
+}
+
+impl ConfigCommands {
+    pub(super) fn execute(self) -> Result<String, DynError> {
+        match self {
+            Self::Get { key } => {
+                let (value, _) = Layers::load()?.get(key).ok_or_else(|| {
+                    seb::Error::new(
+                        seb::ErrorKind::NoValue,
+                        format!("The '{key}' setting is not set"),
+                    )
+                })?;
+                Ok(value)
+            }
+            Self::Set { key, value, global } => {
+                let path = if global {
+                    config::user_path()
+                        .ok_or_else(|| eyre!("Cannot find the user configuration directory"))?
+                } else {
+                    config::project_path().unwrap_or_else(|| PathBuf::from(config::PROJECT_FILE))
+                };
+
+                let mut config = Config::read_verbatim(&path)?;
+                config.set(key, &value)?;
+                config.write(&path)?;
+
+                log::info!("Setting '{key}' changed in '{}'", path.display());
+                Ok(format!("{key} = {}", config.get(key).unwrap_or_default()))
+            }
+            Self::List => {
+                let layers = Layers::load()?;
+                Ok(Key::value_variants()
+                    .iter()
+                    .filter_map(|&key| {
+                        let (value, source) = layers.get(key)?;
+                        Some(format!("{key} = {value}\t({source})"))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+        }
+    }
+}
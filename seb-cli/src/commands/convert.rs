@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use crate::{
+    file::{read_biblio, FileFormat},
+    interact::user_resolve_biblio_resolver,
+};
+
+use seb::{ast::Biblio, file::replace_file};
+
+use eyre::{eyre, Context};
+use log::{info, warn};
+
+type DynError = Box<dyn std::error::Error>;
+
+pub(super) fn execute(
+    input: &Path,
+    output: &Path,
+    from: Option<FileFormat>,
+    to: Option<FileFormat>,
+    force: bool,
+    interact: bool,
+) -> Result<String, DynError> {
+    let from = from.map_or_else(|| FileFormat::from_path(input), Ok)?;
+    let to = to.map_or_else(|| FileFormat::from_path(output), Ok)?;
+
+    if output.exists() && !force {
+        return Err(eyre!(
+            "The '{}' file already exists - use `--force` to overwrite it",
+            output.display()
+        )
+        .into());
+    }
+
+    let biblio = match read_biblio(input, from)? {
+        Ok(biblio) => biblio,
+        Err(resolver) if interact => user_resolve_biblio_resolver(resolver)?,
+        Err(resolver) => return Err(resolver.into()),
+    };
+
+    let dropped = dropped_fields(&biblio, to);
+    for line in &dropped {
+        warn!("{line}");
+    }
+
+    replace_file(output, to.compose(&biblio).as_bytes())
+        .wrap_err_with(|| eyre!("Cannot write the '{}' file", output.display()))?;
+
+    let count = biblio.entries().count();
+    info!("Converted {} to {}", input.display(), output.display());
+
+    let message = format!(
+        "Converted {count} entries from {} to {}",
+        from.name(),
+        to.name()
+    );
+    if dropped.is_empty() {
+        return Ok(message);
+    }
+
+    Ok(format!(
+        "{message}\nThe following fields cannot be represented in {} and were dropped:\n{}",
+        to.name(),
+        dropped.join("\n")
+    ))
+}
+
+/// A line for each entry with fields that cannot be represented in the format, ordered by cite.
+fn dropped_fields(biblio: &Biblio, format: FileFormat) -> Vec<String> {
+    let mut entries = biblio.entries().collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.cite());
+
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let fields = format.unsupported_fields(entry);
+            (!fields.is_empty()).then(|| format!("{}: {}", entry.cite(), fields.join(", ")))
+        })
+        .collect()
+}
+
+#[test]
+fn dropped_fields_are_reported_per_entry() {
+    use seb::ast::{Entry, EntryKind};
+
+    let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+    resolver.title("Title");
+    resolver.set_field("howpublished", "Online");
+    let biblio = Biblio::new(vec![resolver.resolve().expect("Title is set")]);
+
+    assert!(dropped_fields(&biblio, FileFormat::Bibtex).is_empty());
+    assert_eq!(
+        vec!["cite: howpublished"],
+        dropped_fields(&biblio, FileFormat::Ris)
+    );
+}
@@ -0,0 +1,156 @@
+use crate::{app::resolve_entry_resolver, interact::user_select};
+
+use seb::ast::{Biblio, Entry, FieldQuery};
+
+use log::info;
+
+type DynError = Box<dyn std::error::Error>;
+
+/// The cite keys of a group of duplicate entries, owned so that the bibliography can be changed
+/// while each group is merged.
+struct Duplicates {
+    cites: Vec<String>,
+    reason: String,
+}
+
+pub(super) fn execute(biblio: &mut Biblio, interact: bool) -> Result<String, DynError> {
+    let duplicates = biblio
+        .duplicates()
+        .iter()
+        .map(|cluster| Duplicates {
+            cites: cluster.cites().map(str::to_owned).collect(),
+            reason: cluster.reason().to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    if duplicates.is_empty() {
+        return Ok("No duplicate entries found".to_owned());
+    }
+
+    if !interact {
+        return Ok(duplicates
+            .iter()
+            .map(|dup| format!("{} ({})", dup.cites.join(", "), dup.reason))
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+
+    let mut merged = 0;
+    for dup in &duplicates {
+        let mut options = dup
+            .cites
+            .iter()
+            .map(|cite| format!("Keep '{cite}'"))
+            .collect::<Vec<_>>();
+        options.push("Skip".to_owned());
+
+        let prompt = format!("Duplicates {} ({})", dup.cites.join(", "), dup.reason);
+        let index = user_select(&prompt, &options)?;
+        let Some(kept) = dup.cites.get(index) else {
+            continue;
+        };
+
+        let entry = merge_entries(biblio, kept, &dup.cites, |name, values| {
+            user_select(&format!("Choose the value of '{name}'"), values)
+        })?;
+        let cites = dup.cites.iter().map(String::as_str).collect::<Vec<_>>();
+        biblio.merge_duplicates(&cites, entry);
+
+        info!("Merged {} into '{kept}'", dup.cites.join(", "));
+        merged += 1;
+    }
+
+    Ok(format!("Merged {merged} group(s) of duplicate entries"))
+}
+
+/// Merges the fields of the duplicate entries into a single entry with the kind and cite key of
+/// the `kept` entry.
+///
+/// Fields found in only one entry, or with the same value in every entry, are kept as they are and
+/// `choose` is called with the distinct values of any other field to pick the value to keep, the
+/// value of the `kept` entry is always first.
+fn merge_entries<C>(
+    biblio: &Biblio,
+    kept: &str,
+    cites: &[String],
+    mut choose: C,
+) -> eyre::Result<Entry>
+where
+    C: FnMut(&str, &[String]) -> eyre::Result<usize>,
+{
+    let kept_entry = biblio.get(kept).ok_or_else(|| super::no_entry(kept))?;
+
+    // the kept entry is first so its values are the first choice
+    let entries = std::iter::once(kept_entry)
+        .chain(
+            cites
+                .iter()
+                .filter(|cite| cite.as_str() != kept)
+                .filter_map(|cite| biblio.get(cite)),
+        )
+        .collect::<Vec<_>>();
+
+    let mut names = Vec::new();
+    for entry in &entries {
+        for field in entry.fields() {
+            let name = field.name.to_lowercase();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    let mut resolver = Entry::resolver_with_cite(kept_entry.kind(), kept);
+    for name in names {
+        let mut values: Vec<String> = Vec::new();
+        for value in entries.iter().filter_map(|entry| entry.get_field(&name)) {
+            if !values.iter().any(|v| v == &**value) {
+                values.push(value.to_string());
+            }
+        }
+
+        let index = if values.len() > 1 {
+            choose(&name, &values)?
+        } else {
+            0
+        };
+        resolver.set_field(&name, values.swap_remove(index));
+    }
+
+    match resolver.resolve() {
+        Ok(entry) => Ok(entry),
+        Err(resolver) => resolve_entry_resolver(resolver),
+    }
+}
+
+#[test]
+fn merge_takes_union_of_fields_and_chosen_conflicts() {
+    use seb::ast::manual_entry;
+
+    let biblio = Biblio::new(vec![
+        manual_entry("a", &[("title", "Title"), ("year", "2020")]),
+        manual_entry(
+            "b",
+            &[("title", "Title"), ("year", "2021"), ("doi", "10.1/b")],
+        ),
+    ]);
+    let cites = vec!["a".to_owned(), "b".to_owned()];
+
+    let mut conflicts = Vec::new();
+    let merged = merge_entries(&biblio, "a", &cites, |name, values| {
+        conflicts.push((name.to_owned(), values.to_vec()));
+        Ok(1)
+    })
+    .expect("Merged entry is valid");
+
+    assert_eq!(
+        vec![(
+            "year".to_owned(),
+            vec!["2020".to_owned(), "2021".to_owned()]
+        )],
+        conflicts
+    );
+    assert_eq!("a", merged.cite());
+    assert_eq!(Some("2021"), merged.get_field("year").map(|v| &**v));
+    assert_eq!(Some("10.1/b"), merged.get_field("doi").map(|v| &**v));
+}
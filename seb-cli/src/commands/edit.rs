@@ -0,0 +1,223 @@
+use std::{env, fs, io::Write, process::Command};
+
+use crate::{
+    app::{self, resolve_entry_resolver},
+    interact::user_resolve_biblio_resolver,
+};
+
+use seb::{
+    ast::{Biblio, Entry, EntryKind, Resolver},
+    format::Format,
+};
+
+use eyre::{eyre, Context};
+use log::{info, trace};
+
+type DynError = Box<dyn std::error::Error>;
+
+/// Field changes to make to an existing entry.
+pub(super) struct FieldEdits {
+    pub(super) set: Vec<(String, String)>,
+    pub(super) unset: Vec<String>,
+    pub(super) rename: Vec<(String, String)>,
+    pub(super) kind: Option<EntryKind<'static>>,
+}
+
+/// Parse a `key=value` pair used for the `--set` and `--rename` options.
+pub(super) fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .filter(|(key, _)| !key.trim().is_empty())
+        .map(|(key, value)| (key.trim().to_owned(), value.to_owned()))
+        .ok_or_else(|| format!("invalid key=value pair: no `=` found in '{s}'"))
+}
+
+pub(super) fn execute(
+    biblio: &mut Biblio,
+    cite: &str,
+    edits: FieldEdits,
+    interact: bool,
+) -> Result<String, DynError> {
+    let existing = biblio.get(cite).ok_or_else(|| super::no_entry(cite))?;
+
+    let resolver = edit_resolver(existing, edits)?;
+
+    let entry = match resolver.resolve() {
+        Ok(entry) => entry,
+        Err(resolver) if interact => resolve_entry_resolver(resolver)?,
+        Err(resolver) => return Err(resolver.into()),
+    };
+
+    biblio.insert(entry);
+    info!("Entry with cite '{cite}' edited");
+    Ok(format!("Entry '{cite}' edited"))
+}
+
+/// Creates a [`Resolver`] for the existing entry with the field edits applied.
+///
+/// The resolver is for the new kind when the kind is changed so the entry is validated against
+/// the required fields of that kind.
+fn edit_resolver(existing: &Entry, edits: FieldEdits) -> eyre::Result<Resolver> {
+    let FieldEdits {
+        set,
+        unset,
+        rename,
+        kind,
+    } = edits;
+
+    let mut resolver = match kind {
+        Some(kind) => {
+            trace!("Changing kind of '{}' to {kind}", existing.cite());
+            Entry::resolver_with_cite(kind, existing.cite())
+        }
+        None => Entry::resolver_with_cite(existing.kind(), existing.cite()),
+    };
+    resolver.set_fields_from_entry(existing);
+
+    for (from, to) in rename {
+        let value = resolver
+            .remove_field(&from)
+            .ok_or_else(|| eyre!("No field named '{from}' to rename"))?;
+        resolver.set_field(&to, value);
+    }
+
+    for name in unset {
+        if resolver.remove_field(&name).is_none() {
+            log::warn!("No field named '{name}' to unset");
+        }
+    }
+
+    for (name, value) in set {
+        resolver.set_field(&name, value);
+    }
+
+    Ok(resolver)
+}
+
+/// Opens the entry in the users `$EDITOR` using the format `F` and replaces the entry with the
+/// result.
+pub(super) fn execute_in_editor<F: Format>(
+    biblio: &mut Biblio,
+    cite: &str,
+    interact: bool,
+) -> Result<String, DynError> {
+    let existing = biblio.get(cite).ok_or_else(|| super::no_entry(cite))?;
+    let old_cite = existing.cite().to_owned();
+
+    let text = edit_text(&F::compose_entry(existing), F::ext())?;
+
+    let edited = match F::new(text).parse()? {
+        Ok(edited) => edited,
+        Err(resolver) if interact => user_resolve_biblio_resolver(resolver)?,
+        Err(resolver) => return Err(resolver.into()),
+    };
+
+    let mut entries = edited.into_entries();
+    if entries.len() != 1 {
+        return Err(eyre!(
+            "Expected a single entry after editing but found {}",
+            entries.len()
+        )
+        .into());
+    }
+    let entry = entries.remove(0);
+    let new_cite = entry.cite().to_owned();
+
+    if new_cite != old_cite {
+        if !new_cite.eq_ignore_ascii_case(&old_cite) {
+            app::check_cite_is_unique(biblio, &new_cite)?;
+        }
+        biblio.take(&old_cite);
+    }
+
+    biblio.insert(entry);
+    info!("Entry with cite '{new_cite}' edited");
+    Ok(format!("Entry '{new_cite}' edited"))
+}
+
+/// Writes the text to a temporary file, opens it with the users `$EDITOR` (or `$VISUAL`) and
+/// returns the text of the file once the editor exits.
+fn edit_text(text: &str, ext: &str) -> eyre::Result<String> {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_owned());
+    let mut command = editor_command(&editor)?;
+
+    // the file is created with a random name that no other user can have created beforehand
+    let mut file = tempfile::Builder::new()
+        .prefix("seb-edit-")
+        .suffix(&format!(".{ext}"))
+        .tempfile()
+        .wrap_err("Cannot create a temporary file to edit")?;
+    file.write_all(text.as_bytes())
+        .and_then(|()| file.flush())
+        .wrap_err("Cannot write the entry to the temporary file")?;
+    let path = file.path();
+
+    trace!("Opening {} with '{editor}'", path.display());
+    let status = command
+        .arg(path)
+        .status()
+        .wrap_err_with(|| eyre!("Cannot open the editor '{editor}'"));
+
+    // the temporary file is removed when it is dropped
+    let text = fs::read_to_string(path).wrap_err("Cannot read the edited temporary file");
+
+    if !status?.success() {
+        return Err(eyre!(
+            "Editor exited with an error - the entry is unchanged"
+        ));
+    }
+    text
+}
+
+/// The command of the editor, which is split like a shell would split it so that the editor can
+/// have arguments, such as `code --wait`.
+fn editor_command(editor: &str) -> eyre::Result<Command> {
+    let words = shell_words::split(editor)
+        .wrap_err_with(|| eyre!("Cannot split the editor '{editor}' into its arguments"))?;
+    let (program, args) = words
+        .split_first()
+        .ok_or_else(|| eyre!("The editor '{editor}' has no program"))?;
+
+    let mut command = Command::new(program);
+    command.args(args);
+    Ok(command)
+}
+
+#[test]
+fn editor_is_split_into_the_program_and_arguments() {
+    let command = editor_command("code --wait").unwrap();
+    assert_eq!("code", command.get_program());
+    assert_eq!(vec!["--wait"], command.get_args().collect::<Vec<_>>());
+
+    let command = editor_command("'/opt/my editor/bin/edit' -t").unwrap();
+    assert_eq!("/opt/my editor/bin/edit", command.get_program());
+    assert_eq!(vec!["-t"], command.get_args().collect::<Vec<_>>());
+
+    assert!(editor_command("vim '").is_err());
+}
+
+#[test]
+fn kind_change_requires_fields_of_new_kind() {
+    let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+    resolver.title("Title");
+    resolver.set_field("note", "a note");
+    let manual = resolver.resolve().expect("Manual only requires title");
+
+    let edits = FieldEdits {
+        set: vec![("author".to_owned(), "Me".to_owned())],
+        unset: vec!["note".to_owned()],
+        rename: vec![],
+        kind: Some(EntryKind::Book),
+    };
+
+    let resolver = edit_resolver(&manual, edits).expect("No renames to fail");
+
+    assert_eq!(&EntryKind::Book, resolver.kind());
+    assert_eq!(
+        vec!["publisher", "year"],
+        resolver.required_fields().collect::<Vec<_>>()
+    );
+}
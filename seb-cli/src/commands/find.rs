@@ -0,0 +1,193 @@
+use super::list::YearRange;
+use crate::{
+    app::{self, resolve_entry_resolver},
+    config::Config,
+    interact::user_multi_select,
+};
+
+use seb::{
+    ast::{Biblio, FieldQuery},
+    dedupe::{normalize_doi, normalize_isbn},
+    provider::Found,
+    TitleQuery,
+};
+
+use clap::Args;
+use eyre::eyre;
+use log::{info, trace, warn};
+
+type DynError = Box<dyn std::error::Error>;
+
+/// The fields that hold the venue of an entry, in the order they are shown.
+const VENUE_FIELDS: [&str; 3] = ["journal", "booktitle", "publisher"];
+
+/// The query and options of the `find` command.
+#[derive(Args)]
+pub struct FindArgs {
+    /// The words to search for
+    #[clap(required = true)]
+    query: Vec<String>,
+
+    /// Only find entries with an author matching this name
+    #[clap(long)]
+    author: Option<String>,
+
+    /// Only find entries published in this journal, proceedings or by this publisher
+    #[clap(long)]
+    venue: Option<String>,
+
+    /// Only find entries published in the year or range of years, such as `2019`, `2010..2020`,
+    /// `2010..` or `..2020`
+    #[clap(long)]
+    year: Option<YearRange>,
+
+    /// The maximum number of results to show
+    #[clap(long)]
+    limit: Option<usize>,
+}
+
+impl FindArgs {
+    fn search_query(self) -> TitleQuery {
+        let mut query = TitleQuery::new(self.query.join(" "));
+        query.author = self.author;
+        query.venue = self.venue;
+        if let Some(year) = self.year {
+            query = query.years(year.start, year.end);
+        }
+        query
+    }
+}
+
+pub(super) fn execute(
+    biblio: &mut Biblio,
+    config: &Config,
+    args: FindArgs,
+    interact: bool,
+) -> Result<String, DynError> {
+    let limit = args.limit;
+    let query = args.search_query();
+    if query.is_empty() {
+        return Err(eyre!("The search query is empty").into());
+    }
+
+    trace!("Searching the providers for '{query}'..");
+    let mut found = config.registry()?.search(&query)?;
+    if let Some(limit) = limit {
+        found.truncate(limit);
+    }
+
+    let items = found.iter().map(describe).collect::<Vec<_>>();
+    if !interact {
+        return Ok(items.join("\n"));
+    }
+
+    let chosen = user_multi_select("Choose the entries to add", &items, false)?;
+    let mut added = Vec::new();
+    // remove the chosen results from the end so that the indexes stay valid
+    for index in chosen.into_iter().rev() {
+        let result = found.remove(index);
+        if let Some((name, value)) = duplicate_id(biblio, result.fields()) {
+            warn!(
+                "Skipping '{}' as an entry already has the {name} '{value}'",
+                items[index]
+            );
+            continue;
+        }
+
+        let mut entry = result.entry.or_else(resolve_entry_resolver)?;
+        if let Some(cite) = config
+            .cite_template()
+            .and_then(|template| template.render_cite(&entry))
+        {
+            entry.set_cite(cite);
+        }
+
+        let cite = entry.cite().to_owned();
+        if let Err(e) = app::check_cite_is_unique(biblio, &cite) {
+            warn!("Skipping '{}': {e}", items[index]);
+            continue;
+        }
+        biblio.insert(entry);
+        info!("Entry with cite '{cite}' added to bibliography");
+        added.push(cite);
+    }
+
+    if added.is_empty() {
+        Ok("No entries added".to_owned())
+    } else {
+        added.reverse();
+        Ok(added.join("\n"))
+    }
+}
+
+/// The DOI or ISBN of the `fields` when an entry of the `biblio` already has it, ignoring case
+/// and formatting.
+fn duplicate_id(biblio: &Biblio, fields: &dyn FieldQuery) -> Option<(&'static str, String)> {
+    let normalize = |name, value: &str| match name {
+        "doi" => Some(normalize_doi(value)),
+        _ => normalize_isbn(value),
+    };
+
+    ["doi", "isbn"].into_iter().find_map(|name| {
+        let value = fields.get_field(name)?;
+        let id = normalize(name, value)?;
+        biblio
+            .contains_field(name, |other| normalize(name, other).as_ref() == Some(&id))
+            .then(|| (name, value.to_string()))
+    })
+}
+
+/// Describes a result as `title - author (year), venue [providers]`, leaving out what is missing.
+fn describe(found: &Found) -> String {
+    let fields = found.fields();
+    let field = |names: &[&str], prefix: &str, suffix: &str| {
+        names
+            .iter()
+            .find_map(|name| fields.get_field(name))
+            .map(|value| format!("{prefix}{}{suffix}", &**value))
+            .unwrap_or_default()
+    };
+
+    format!(
+        "{}{}{}{} [{}]",
+        field(&["title"], "", ""),
+        field(&["author"], " - ", ""),
+        field(&["year"], " (", ")"),
+        field(&VENUE_FIELDS, ", ", ""),
+        found.providers.join(", ")
+    )
+}
+
+#[test]
+fn results_are_described_with_the_fields_they_have() {
+    use seb::ast::{Entry, EntryKind};
+
+    let mut resolver = Entry::resolver_with_cite(EntryKind::Book, "mcconnell2004");
+    resolver.set_field("title", "Code Complete");
+    resolver.set_field("year", "2004");
+    resolver.set_field("publisher", "Microsoft Press");
+    let found = Found {
+        entry: Err(resolver),
+        providers: vec!["google-books".to_owned(), "open-library".to_owned()],
+        score: 1.0,
+    };
+
+    assert_eq!(
+        "Code Complete (2004), Microsoft Press [google-books, open-library]",
+        describe(&found)
+    );
+
+    let mut biblio = Biblio::new(Vec::new());
+    assert_eq!(None, duplicate_id(&biblio, found.fields()));
+    let mut entry = Entry::resolver_with_cite(EntryKind::Manual, "manual");
+    entry.set_field("title", "A manual");
+    entry.set_field("isbn", "9780735619678");
+    biblio.insert(entry.resolve().unwrap());
+
+    let mut resolver = found.entry.unwrap_err();
+    resolver.set_field("isbn", "0-7356-1967-0");
+    assert_eq!(
+        Some(("isbn", "0-7356-1967-0".to_owned())),
+        duplicate_id(&biblio, &resolver)
+    );
+}
@@ -0,0 +1,148 @@
+use super::Outcome;
+use crate::{
+    journal::{self, Journal},
+    output,
+};
+
+use seb::{
+    ast::{Biblio, Entry},
+    Error, ErrorKind,
+};
+
+use log::info;
+
+type DynError = Box<dyn std::error::Error>;
+
+/// Lists the most recent records of the journal, most recent first.
+pub(super) fn history(journal: &Journal, limit: usize) -> Result<String, DynError> {
+    let records = journal.records()?;
+    if records.is_empty() {
+        return Ok("No changes have been made to the bibliography".to_owned());
+    }
+
+    let lines = records
+        .iter()
+        .rev()
+        .take(limit)
+        .map(|record| {
+            let mut summary = record
+                .changes
+                .iter()
+                .map(journal::Change::summary)
+                .collect::<Vec<_>>();
+            if !record.reverts.is_empty() {
+                let ids = record
+                    .reverts
+                    .iter()
+                    .map(u64::to_string)
+                    .collect::<Vec<_>>();
+                summary.insert(0, format!("(undid {})", ids.join(", ")));
+            }
+            if journal::is_undone(&records, record.id) {
+                summary.push("(undone)".to_owned());
+            }
+
+            format!(
+                "{:>4}  {}  {:<8} {}",
+                record.id,
+                record.date(),
+                record.command,
+                summary.join(" ")
+            )
+        })
+        .collect::<Vec<_>>();
+
+    Ok(lines.join("\n"))
+}
+
+/// Reverts the changes of the `count` most recent commands that have not been undone.
+///
+/// An entry that changed again since the command is not reverted, so that the later change is not
+/// lost, and the undo fails without changing the bibliography.
+pub(super) fn undo(
+    biblio: &mut Biblio,
+    journal: &Journal,
+    count: usize,
+) -> Result<Outcome, DynError> {
+    let records = journal.records()?;
+    let targets = journal::undoable(&records).take(count).collect::<Vec<_>>();
+    if targets.is_empty() {
+        return Err(Error::new(ErrorKind::NoValue, "There are no commands to undo").into());
+    }
+
+    // check every change before changing the bibliography so that a failed undo changes nothing
+    let mut expected = std::collections::HashMap::new();
+    for record in &targets {
+        for change in &record.changes {
+            // the most recent record's state is the state the entry is expected to be in
+            expected
+                .entry(change.cite.as_str())
+                .or_insert(&change.after);
+        }
+    }
+    for (cite, after) in &expected {
+        let current = biblio.get(cite).map(output::entry_json);
+        if current.as_ref() != after.as_ref() {
+            return Err(Error::new(
+                ErrorKind::Conflict,
+                format!("The entry '{cite}' changed since it was journaled so it cannot be undone"),
+            )
+            .into());
+        }
+    }
+
+    let mut lines = Vec::new();
+    for record in &targets {
+        for change in record.changes.iter().rev() {
+            biblio.take(&change.cite);
+            if let Some(before) = &change.before {
+                let entry: Entry = serde_json::from_value(before.clone()).map_err(|err| {
+                    Error::wrap_with(
+                        ErrorKind::Deserialize,
+                        err,
+                        format!("The journaled entry '{}' is not valid", change.cite),
+                    )
+                })?;
+                biblio.insert(entry);
+            }
+        }
+        info!("Undid the '{}' command ({})", record.command, record.id);
+        lines.push(format!("Undid `{}` ({})", record.command, record.id));
+    }
+
+    Ok(Outcome {
+        message: lines.join("\n"),
+        selected: Vec::new(),
+        reverts: targets.iter().map(|record| record.id).collect(),
+    })
+}
+
+#[test]
+fn undo_reverts_unless_entry_changed_since() {
+    use crate::output::Snapshot;
+    use seb::ast::manual_entry;
+
+    let dir = assert_fs::TempDir::new().expect("Cannot create temp directory for test");
+    let journal = Journal::next_to(&dir.path().join("refs.bib"));
+
+    let mut biblio = Biblio::new(vec![manual_entry("a", &[]), manual_entry("b", &[])]);
+    let snapshot = Snapshot::new(&biblio);
+    biblio.take("a");
+    biblio.insert(manual_entry("b", &[("title", "Edited")]));
+    journal
+        .append(
+            "rm",
+            Vec::new(),
+            journal::changes(&snapshot, &biblio, |_| None),
+        )
+        .unwrap();
+
+    let mut changed = Biblio::new(vec![manual_entry("b", &[("title", "Changed again")])]);
+    assert!(undo(&mut changed, &journal, 1).is_err());
+    assert!(changed.get("a").is_none());
+
+    let outcome = undo(&mut biblio, &journal, 1).unwrap();
+    assert_eq!(vec![1], outcome.reverts);
+    assert_eq!("a", &**biblio.get("a").unwrap().title());
+    assert_eq!("b", &**biblio.get("b").unwrap().title());
+}
@@ -0,0 +1,107 @@
+use std::{io::IsTerminal, path::Path};
+
+use crate::{
+    file::read_biblio_by_ext,
+    interact::{user_resolve_biblio_resolver, user_select_conflict_policy},
+};
+
+use seb::ast::{Biblio, ConflictPolicy, MergeReport};
+
+use clap::ArgEnum;
+use eyre::{eyre, Context};
+use log::info;
+
+type DynError = Box<dyn std::error::Error>;
+
+/// How to resolve an imported entry with the same cite key as an existing entry.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OnConflict {
+    /// Keep the existing entry
+    Ours,
+    /// Replace the existing entry with the imported entry
+    Theirs,
+    /// Add the imported entry with a new cite key
+    Rename,
+    /// Choose for each conflict, which requires the `interact` flag and a terminal
+    Interactive,
+}
+
+impl OnConflict {
+    const fn policy(self) -> Option<ConflictPolicy> {
+        match self {
+            Self::Ours => Some(ConflictPolicy::KeepOurs),
+            Self::Theirs => Some(ConflictPolicy::KeepTheirs),
+            Self::Rename => Some(ConflictPolicy::Rename),
+            Self::Interactive => None,
+        }
+    }
+}
+
+pub(super) fn execute(
+    biblio: &mut Biblio,
+    path: &Path,
+    on_conflict: OnConflict,
+    interact: bool,
+) -> Result<String, DynError> {
+    if on_conflict == OnConflict::Interactive && !(interact && std::io::stdin().is_terminal()) {
+        return Err(eyre!(
+            "Choosing for each conflict requires -i / --interact and a terminal, use another \
+            --on-conflict policy instead"
+        )
+        .into());
+    }
+
+    let incoming = match read_biblio_by_ext(path)? {
+        Ok(incoming) => incoming,
+        Err(resolver) if interact => user_resolve_biblio_resolver(resolver)?,
+        Err(resolver) => return Err(resolver.into()),
+    };
+
+    let report = if let Some(policy) = on_conflict.policy() {
+        biblio.merge(incoming, policy)
+    } else {
+        // cancelling a choice aborts the whole import
+        biblio.try_merge_with(incoming, |ours, _| {
+            user_select_conflict_policy(&format!("Entry '{}' already exists", ours.cite()))
+                .wrap_err("Import cancelled - the bibliography is unchanged")
+        })?
+    };
+
+    info!("Imported entries from {}", path.display());
+    Ok(summary(&report))
+}
+
+/// A summary line for the import followed by a line for each incoming entry.
+fn summary(report: &MergeReport) -> String {
+    let mut lines = vec![format!(
+        "{} added, {} skipped, {} conflicted",
+        report.added.len(),
+        report.skipped.len(),
+        report.conflicts.len()
+    )];
+
+    lines.extend(report.added.iter().map(|cite| format!("added: {cite}")));
+    lines.extend(
+        report
+            .skipped
+            .iter()
+            .map(|cite| format!("skipped: {cite} (already in the bibliography)")),
+    );
+    lines.extend(report.conflicts.iter().map(|(cite, policy)| {
+        let resolution = match policy {
+            ConflictPolicy::KeepOurs => "kept ours".to_owned(),
+            ConflictPolicy::KeepTheirs => "replaced with theirs".to_owned(),
+            ConflictPolicy::Rename => {
+                let new = report
+                    .renamed
+                    .iter()
+                    .find(|(old, _)| old == cite)
+                    .map_or("", |(_, new)| new.as_str());
+                format!("renamed to {new}")
+            }
+        };
+        format!("conflicted: {cite} ({resolution})")
+    }));
+
+    lines.join("\n")
+}
@@ -0,0 +1,267 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use super::Outcome;
+use crate::template::Template;
+
+use seb::{
+    ast::{Biblio, Entry, EntryKind, FieldQuery},
+    format::Format,
+    render::{Output, Renderer, Style},
+};
+
+use clap::ArgEnum;
+
+type DynError = Box<dyn std::error::Error>;
+
+/// The default template used for each entry in the `list` command.
+const LIST_TEMPLATE: &str = r"{cite}\t{year}\t{title}";
+
+/// The key used to sort the entries in the `list` command.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SortKey {
+    /// Sort by the cite key
+    Key,
+    /// Sort by the year field, entries without a year are last
+    Year,
+    /// Sort by the author field, entries without an author are last
+    Author,
+}
+
+/// An inclusive range of years, where either end of the range can be open.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct YearRange {
+    pub(crate) start: Option<u16>,
+    pub(crate) end: Option<u16>,
+}
+
+impl YearRange {
+    fn contains(self, year: u16) -> bool {
+        self.start.is_none_or(|start| start <= year) && self.end.is_none_or(|end| year <= end)
+    }
+}
+
+impl FromStr for YearRange {
+    type Err = String;
+
+    /// Parses a single year `2020` or a range of years `2010..2020`, `2010..` or `..2020`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |year: &str| {
+            let year = year.trim();
+            if year.is_empty() {
+                Ok(None)
+            } else {
+                year.parse()
+                    .map(Some)
+                    .map_err(|_| format!("'{year}' is not a valid year"))
+            }
+        };
+
+        let range = if let Some((start, end)) = s.split_once("..") {
+            Self {
+                start: parse(start)?,
+                end: parse(end)?,
+            }
+        } else {
+            let year = parse(s)?;
+            Self {
+                start: year,
+                end: year,
+            }
+        };
+
+        match range {
+            Self {
+                start: None,
+                end: None,
+            } => Err("expected a year or a range of years".to_owned()),
+            Self {
+                start: Some(start),
+                end: Some(end),
+            } if start > end => Err(format!("the range {start}..{end} ends before it starts")),
+            range => Ok(range),
+        }
+    }
+}
+
+/// Filters for which entries are listed.
+pub(super) struct Filters {
+    pub(super) kind: Option<EntryKind<'static>>,
+    pub(super) fields: Vec<(String, String)>,
+    pub(super) year: Option<YearRange>,
+    pub(super) missing: Vec<String>,
+}
+
+impl Filters {
+    fn matches(&self, entry: &Entry) -> bool {
+        self.kind.as_ref().is_none_or(|kind| *kind == entry.kind())
+            && self.fields.iter().all(|(name, value)| {
+                entry
+                    .get_field(name)
+                    .is_some_and(|v| v.to_lowercase().contains(&value.to_lowercase()))
+            })
+            && self
+                .year
+                .is_none_or(|range| year(entry).is_some_and(|y| range.contains(y)))
+            && self
+                .missing
+                .iter()
+                .all(|name| entry.get_field(name).is_none())
+    }
+}
+
+/// Returns the year of the entry from the leading digits of the `year` field.
+pub(crate) fn year(entry: &Entry) -> Option<u16> {
+    let year = entry.get_field("year")?;
+    let digits = year
+        .trim()
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .unwrap_or_default();
+    digits.parse().ok()
+}
+
+fn compare(key: SortKey, a: &Entry, b: &Entry) -> Ordering {
+    // entries missing the field are placed after those that have the field
+    fn some_first<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    let by_key = || a.cite().to_lowercase().cmp(&b.cite().to_lowercase());
+    match key {
+        SortKey::Key => by_key(),
+        SortKey::Year => some_first(year(a), year(b)).then_with(by_key),
+        SortKey::Author => some_first(
+            a.get_field("author").map(|s| s.to_lowercase()),
+            b.get_field("author").map(|s| s.to_lowercase()),
+        )
+        .then_with(by_key),
+    }
+}
+
+pub(super) fn list(
+    biblio: &Biblio,
+    filters: &Filters,
+    sort: SortKey,
+    reverse: bool,
+    template: Option<Template>,
+) -> Result<Outcome, DynError> {
+    let template = match template {
+        Some(template) => template,
+        None => LIST_TEMPLATE.parse()?,
+    };
+
+    let mut entries = biblio
+        .entries()
+        .filter(|entry| filters.matches(entry))
+        .collect::<Vec<_>>();
+
+    entries.sort_by(|a, b| compare(sort, a, b));
+    if reverse {
+        entries.reverse();
+    }
+
+    Ok(Outcome {
+        message: entries
+            .iter()
+            .map(|entry| template.render(entry))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        selected: entries
+            .iter()
+            .map(|entry| entry.cite().to_owned())
+            .collect(),
+        reverts: Vec::new(),
+    })
+}
+
+pub(super) fn show<F: Format>(
+    biblio: &Biblio,
+    cite: &str,
+    reference: bool,
+    template: Option<Template>,
+) -> Result<Outcome, DynError> {
+    let entry = biblio.get(cite).ok_or_else(|| super::no_entry(cite))?;
+
+    let output = match template {
+        Some(template) => template.render(entry),
+        None if reference => Renderer::new(Style::Apa, Output::Text).render_entry(entry),
+        None => F::compose_entry(entry).trim_end().to_owned(),
+    };
+
+    Ok(Outcome {
+        message: output,
+        selected: vec![entry.cite().to_owned()],
+        reverts: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use seb::ast::manual_entry;
+
+    #[test]
+    fn year_range_parsing() {
+        assert_eq!(
+            Ok(YearRange {
+                start: Some(2010),
+                end: Some(2020)
+            }),
+            "2010..2020".parse()
+        );
+        assert_eq!(
+            Ok(YearRange {
+                start: None,
+                end: Some(2020)
+            }),
+            "..2020".parse()
+        );
+        assert_eq!(
+            Ok(YearRange {
+                start: Some(2019),
+                end: Some(2019)
+            }),
+            "2019".parse()
+        );
+        assert_eq!(
+            Ok(YearRange {
+                start: Some(2015),
+                end: None
+            }),
+            " 2015 .. ".parse()
+        );
+        assert!("..".parse::<YearRange>().is_err());
+        assert!(" .. ".parse::<YearRange>().is_err());
+        assert!("".parse::<YearRange>().is_err());
+        assert!("2020..2010".parse::<YearRange>().is_err());
+        assert!("twenty".parse::<YearRange>().is_err());
+    }
+
+    #[test]
+    fn filtered_and_sorted_list() {
+        let biblio = Biblio::new(vec![
+            manual_entry("c", &[("year", "2019"), ("author", "Knuth")]),
+            manual_entry("a", &[("year", "2021")]),
+            manual_entry("b", &[("year", "2015"), ("doi", "10.1/b")]),
+        ]);
+
+        let filters = Filters {
+            kind: Some(EntryKind::Manual),
+            fields: vec![],
+            year: Some("2016..".parse().unwrap()),
+            missing: vec!["doi".to_owned()],
+        };
+        let template = Some("{cite}".parse().unwrap());
+
+        let res = list(&biblio, &filters, SortKey::Year, false, template).unwrap();
+
+        assert_eq!("c\na", res.message);
+        assert_eq!(vec!["c", "a"], res.selected);
+    }
+}
@@ -1,12 +1,47 @@
 mod add;
+mod config;
+mod convert;
+mod dedupe;
+mod edit;
+mod find;
+mod history;
+mod import;
+mod list;
+mod render;
+mod search;
+mod update;
 
-use crate::interact;
+use crate::{
+    app, config::Config, file::FileFormat, interact, journal::Journal, template::Template,
+};
 use add::AddCommands;
+use config::ConfigCommands;
 
-use seb::ast::Biblio;
+use seb::{ast::Biblio, format::Format};
 
 use clap::Subcommand;
 
+/// The result of a command that succeeded.
+pub struct Outcome {
+    /// The message shown to the user.
+    pub message: String,
+    /// The cite keys of the entries the command selected without changing them, such as the
+    /// entries that were listed or shown.
+    pub selected: Vec<String>,
+    /// The ids of the journal records of the commands that the command undid.
+    pub reverts: Vec<u64>,
+}
+
+impl From<String> for Outcome {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            selected: Vec::new(),
+            reverts: Vec::new(),
+        }
+    }
+}
+
 #[derive(Subcommand)]
 #[non_exhaustive]
 pub enum Commands {
@@ -27,6 +62,18 @@ pub enum Commands {
     /// set using `-i` or `--interact`.
     Check,
 
+    /// Show and change the settings of seb
+    ///
+    /// The settings are read from the user configuration file `$XDG_CONFIG_HOME/seb/config.toml`,
+    /// then the project `.seb.toml` file, then the `SEB_*` environment variables such as
+    /// `SEB_EMAIL`, where each overrides the settings before it and the command line flags
+    /// override them all.
+    #[clap(arg_required_else_help = true)]
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommands,
+    },
+
     /// Derive a new entry from an existing one.
     ///
     /// This can most commonly be used to make a new entry like a "book chapter" entry based on
@@ -70,6 +117,155 @@ pub enum Commands {
         fields: Option<Vec<String>>,
     },
 
+    /// Convert a bibliography file from one format to another
+    ///
+    /// The formats are chosen by the file extensions unless given with `--from` and `--to`. Any
+    /// fields that cannot be represented in the output format are reported. This command does not
+    /// use or change the current bibliography file.
+    #[clap(arg_required_else_help = true)]
+    Convert {
+        /// The path of the bibliography file to convert
+        #[clap(parse(from_os_str))]
+        input: std::path::PathBuf,
+
+        /// The path of the converted bibliography file
+        #[clap(parse(from_os_str))]
+        output: std::path::PathBuf,
+
+        /// The format of the input file
+        #[clap(long, arg_enum)]
+        from: Option<FileFormat>,
+
+        /// The format of the output file
+        #[clap(long, arg_enum)]
+        to: Option<FileFormat>,
+
+        /// Overwrite the output file if it already exists
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Find and merge duplicate entries
+    ///
+    /// Entries are duplicates when they have the same doi or isbn, ignoring case and formatting,
+    /// or when they have a similar title with the same year and overlapping authors.
+    ///
+    /// When the `interact` flag is set each group of duplicates can be merged by choosing the cite
+    /// key to keep and the value of any field that differs between the entries, otherwise the
+    /// duplicates are only shown. Any `crossref` to a merged entry is changed to the kept cite key.
+    Dedupe,
+
+    /// Edit the fields or kind of an existing entry
+    ///
+    /// Fields are set, unset and renamed in that order, when the kind of the entry is changed then
+    /// the entry must contain the required fields of the new kind. Missing required fields can be
+    /// resolved interactively when the `interact` flag is set.
+    #[clap(arg_required_else_help = true)]
+    Edit {
+        /// The cite key of the entry to edit
+        cite: String,
+
+        /// Set a field value using `name=value`, this can be used multiple times
+        #[clap(long, parse(try_from_str = edit::parse_key_value), conflicts_with = "editor")]
+        set: Vec<(String, String)>,
+
+        /// Remove a field by name, this can be used multiple times
+        #[clap(long, conflicts_with = "editor")]
+        unset: Vec<String>,
+
+        /// Rename a field using `old=new`, this can be used multiple times
+        #[clap(long, parse(try_from_str = edit::parse_key_value), conflicts_with = "editor")]
+        rename: Vec<(String, String)>,
+
+        /// Change the kind of the entry, see the `new` command for the known entry types
+        #[clap(long, parse(from_str), conflicts_with = "editor")]
+        kind: Option<seb::ast::EntryKind<'static>>,
+
+        /// Open the entry in `$VISUAL` or `$EDITOR` instead of using the field options
+        #[clap(long)]
+        editor: bool,
+    },
+
+    /// Find entries using every provider that can search and add the chosen entries
+    ///
+    /// The results of the providers are merged so that entries with the same doi or isbn are only
+    /// shown once, and are ranked by how well they match the query. Entries that are missing
+    /// required fields can be resolved before they are added.
+    ///
+    /// When the `interact` flag is set any of the results can be chosen to be added, otherwise the
+    /// results are only shown.
+    #[clap(arg_required_else_help = true)]
+    Find {
+        #[clap(flatten)]
+        args: find::FindArgs,
+    },
+
+    /// List the changes made to the entries by each command, most recent first
+    ///
+    /// The changes are journaled in a `.seb-history.jsonl` file next to the bibliography file,
+    /// where each entry that was added is shown as `+cite`, removed as `-cite` and changed as
+    /// `~cite`.
+    History {
+        /// The number of commands shown
+        #[clap(short = 'n', long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Import the entries of another bibliography file into the current one
+    ///
+    /// The format of the file is chosen by the file extension. Imported entries with the same doi
+    /// or isbn as an existing entry are skipped, and entries with the same cite key as an existing
+    /// entry but different fields are resolved using `--on-conflict`.
+    #[clap(arg_required_else_help = true)]
+    Import {
+        /// The path of the bibliography file to import
+        #[clap(parse(from_os_str))]
+        path: std::path::PathBuf,
+
+        /// How to resolve an imported entry with the same cite key as an existing entry
+        #[clap(long, arg_enum, default_value = "ours")]
+        on_conflict: import::OnConflict,
+    },
+
+    /// List the entries in the bibliography file
+    ///
+    /// Each entry is shown on a single line using the `--format` template, which by default is
+    /// '{cite}\t{year}\t{title}'.
+    List {
+        /// Only list entries of this kind, see the `new` command for the known entry types
+        #[clap(long, parse(from_str))]
+        kind: Option<seb::ast::EntryKind<'static>>,
+
+        /// Only list entries with a field that contains a value using `name=value`, the value is
+        /// case-insensitive and this can be used multiple times
+        #[clap(long, parse(try_from_str = edit::parse_key_value))]
+        field: Vec<(String, String)>,
+
+        /// Only list entries with a year in the range, such as `2019`, `2010..2020`, `2010..` or
+        /// `..2020`
+        #[clap(long)]
+        year: Option<list::YearRange>,
+
+        /// Only list entries that are missing this field, this can be used multiple times
+        #[clap(long)]
+        missing: Vec<String>,
+
+        /// The order of the entries
+        #[clap(long, arg_enum, default_value = "key")]
+        sort: list::SortKey,
+
+        /// Reverse the order of the entries
+        #[clap(long)]
+        reverse: bool,
+
+        /// Template for each entry, where `{name}` is replaced by the value of the field `name`
+        ///
+        /// The `{cite}` and `{kind}` placeholders can be used for the cite key and kind of the
+        /// entry, `\t` and `\n` can be used for tabs and new lines.
+        #[clap(long)]
+        format: Option<Template>,
+    },
+
     /// Add a new entry manually
     ///
     /// This subcommand will assume interact flag is set even if not explicitly used.
@@ -110,72 +306,258 @@ pub enum Commands {
         #[clap(long, multiple_values(true))]
         fields: Option<Vec<String>>,
     },
+    /// Render the entries as a reference list in a citation style
+    ///
+    /// The styles are `apa`, `ieee`, `chicago` (author-date) and `harvard`, and the reference list
+    /// can be written as `text`, `html` or `markdown`. References are sorted as the style expects,
+    /// so by author and year except for `ieee` which numbers the references.
+    ///
+    /// Any CSL 1.0.2 style can be used with `--csl` instead, which can also render an in-text
+    /// citation of the entries using `--citation`. The terms of the style are in `en-US` unless a
+    /// CSL locale file is given with `--locale`.
+    Render {
+        /// The cite keys of the entries to render, all entries are rendered when none are given
+        cites: Vec<String>,
+
+        /// The citation style
+        #[clap(long, default_value = "apa", conflicts_with = "csl")]
+        style: seb::render::Style,
+
+        /// The path of a CSL style file to use instead of a built-in style
+        #[clap(long, parse(from_os_str))]
+        csl: Option<std::path::PathBuf>,
+
+        /// The path of a CSL locale file for the terms of the CSL style
+        #[clap(long, parse(from_os_str), requires = "csl")]
+        locale: Option<std::path::PathBuf>,
+
+        /// Render an in-text citation of the entries with the CSL style instead of a bibliography
+        #[clap(long, requires = "csl")]
+        citation: bool,
+
+        /// The markup of the reference list
+        #[clap(long, default_value = "text")]
+        to: seb::render::Output,
+    },
+
     /// Remove an entry from the bibliography file using the cite key
     #[clap(arg_required_else_help = true)]
     Rm {
         /// The cite key of the entry to remove
         cite: String,
     },
+
+    /// Search the entries in the bibliography file
+    ///
+    /// The title, author, keywords and abstract fields are searched ignoring case and diacritics
+    /// with the best matches shown first. A term can be limited to a field using `name:term` and
+    /// numeric fields can be compared using `>`, `>=`, `<`, `<=` or `=`, such as `year:>2010`.
+    /// Phrases can be searched for using double quotes.
+    ///
+    /// When the `interact` flag is set an entry can be chosen from the results and the cite key of
+    /// that entry is shown.
+    #[clap(arg_required_else_help = true)]
+    Search {
+        /// The search query
+        #[clap(required = true)]
+        query: Vec<String>,
+
+        /// The maximum number of entries to show
+        #[clap(long)]
+        limit: Option<usize>,
+
+        /// Template for each entry, see the `list` command for details
+        #[clap(long)]
+        format: Option<Template>,
+    },
+
+    /// Show a single entry in the format of the bibliography file
+    #[clap(arg_required_else_help = true)]
+    Show {
+        /// The cite key of the entry to show
+        cite: String,
+
+        /// Show the entry as a formatted reference
+        #[clap(long)]
+        reference: bool,
+
+        /// Template for the entry, see the `list` command for details
+        #[clap(long, conflicts_with = "reference")]
+        format: Option<Template>,
+    },
+
+    /// Undo the changes made by the most recent commands
+    ///
+    /// The commands are undone most recent first, skipping commands that were already undone. An
+    /// undo fails without changing anything when one of the entries has changed since, so undo
+    /// the later commands first.
+    Undo {
+        /// The number of commands to undo
+        #[clap(default_value = "1")]
+        count: usize,
+    },
+
+    /// Update existing entries using the doi, isbn or IETF RFC number they contain
+    ///
+    /// Each entry is searched for again and the fields that have been added or changed since are
+    /// shown. Fields are never removed by an update and fields named in the `pinned` field of an
+    /// entry, such as `pinned = {title, pages}`, are kept as they are.
+    ///
+    /// The changes can be chosen interactively when the `interact` flag is set or applied
+    /// wholesale using `--yes`, otherwise the changes are only shown.
+    Update {
+        /// The cite keys of the entries to update, all entries are updated when none are given
+        cites: Vec<String>,
+
+        /// Apply all the changes without asking
+        #[clap(short, long)]
+        yes: bool,
+    },
 }
 
 impl Commands {
-    pub fn execute(
+    /// Whether the command reads or changes the current bibliography file.
+    pub const fn uses_biblio(&self) -> bool {
+        !matches!(self, Self::Config { .. } | Self::Convert { .. })
+    }
+
+    /// Whether the command can change the entries of the bibliography, rather than only reading
+    /// them.
+    pub const fn changes_biblio(&self) -> bool {
+        self.uses_biblio()
+            && !matches!(
+                self,
+                Self::Check
+                    | Self::History { .. }
+                    | Self::List { .. }
+                    | Self::Render { .. }
+                    | Self::Search { .. }
+                    | Self::Show { .. }
+            )
+    }
+
+    /// Whether the command adds new entries to the bibliography, which need a target file when
+    /// there is more than one file.
+    pub const fn adds_entries(&self) -> bool {
+        matches!(
+            self,
+            Self::Add { .. }
+                | Self::Derive { .. }
+                | Self::Find { .. }
+                | Self::Import { .. }
+                | Self::New { .. }
+        )
+    }
+
+    /// The name of the command as used on the command line.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Add { .. } => "add",
+            Self::Check => "check",
+            Self::Config { .. } => "config",
+            Self::Derive { .. } => "derive",
+            Self::Convert { .. } => "convert",
+            Self::Dedupe => "dedupe",
+            Self::Edit { .. } => "edit",
+            Self::Find { .. } => "find",
+            Self::History { .. } => "history",
+            Self::Import { .. } => "import",
+            Self::List { .. } => "list",
+            Self::New { .. } => "new",
+            Self::Render { .. } => "render",
+            Self::Rm { .. } => "rm",
+            Self::Search { .. } => "search",
+            Self::Show { .. } => "show",
+            Self::Undo { .. } => "undo",
+            Self::Update { .. } => "update",
+        }
+    }
+
+    pub fn execute<F: Format>(
         self,
         biblio: &mut Biblio,
+        config: &Config,
+        journal: Option<&Journal>,
         interact: bool,
-    ) -> Result<String, Box<dyn std::error::Error>> {
-        match self {
-            Commands::Add { command } => command.execute(biblio, interact),
+    ) -> Result<Outcome, Box<dyn std::error::Error>> {
+        // every command that uses the bibliography has a journal
+        let journal = || journal.ok_or_else(|| eyre::eyre!("There is no journal of changes"));
+        let cite_template = config.cite_template();
+        let message = match self {
+            Commands::Add { command } => command.execute(biblio, config, interact),
             // trivially if the biblio is already resolved at this point then it was either
             // resolved interactively or was valid so a success message can be returned.
             Commands::Check => Ok("All entries contain the required fields!".to_owned()),
+            Commands::Config { command } => command.execute(),
             Commands::Derive {
                 entry,
                 kind,
                 cite,
                 fields,
+            } => derive(biblio, &entry, kind, cite, fields),
+            Commands::Convert {
+                input,
+                output,
+                from,
+                to,
+                force,
+            } => convert::execute(&input, &output, from, to, force, interact),
+            Commands::Dedupe => dedupe::execute(biblio, interact),
+            Commands::Edit {
+                cite, editor: true, ..
+            } => edit::execute_in_editor::<F>(biblio, &cite, interact),
+            Commands::Edit {
+                cite,
+                set,
+                unset,
+                rename,
+                kind,
+                ..
             } => {
-                let mut resolver = seb::ast::Entry::resolver_with_cite(kind, cite);
-
-                if let Some(entry) = biblio.get(&entry) {
-                    resolver.set_fields_from_entry(entry);
-                } else {
-                    return Err(eyre::eyre!("No entry found with the cite key of '{entry}'").into());
-                }
-
-                if let Some(fields) = fields {
-                    resolver.add_required_fields(fields);
-                }
-
-                interact::user_resolve_entry(&mut resolver)?;
-                let derived_entry = resolver.resolve()?;
-                let cite = derived_entry.cite().to_owned();
-                biblio.insert(derived_entry);
-
-                log::info!(
-                    "Entry with cite '{cite}' derived from '{entry}' and added to bibliography"
-                );
-                Ok(cite)
+                let edits = edit::FieldEdits {
+                    set,
+                    unset,
+                    rename,
+                    kind,
+                };
+                edit::execute(biblio, &cite, edits, interact)
             }
-            Commands::New { kind, cite, fields } => {
-                let mut resolver = if let Some(cite) = cite {
-                    seb::ast::Entry::resolver_with_cite(kind, cite)
-                } else {
-                    seb::ast::Entry::resolver(kind)
+            Commands::Find { args } => find::execute(biblio, config, args, interact),
+            Commands::History { limit } => history::history(journal()?, limit),
+            Commands::Import { path, on_conflict } => {
+                import::execute(biblio, &path, on_conflict, interact)
+            }
+            Commands::List {
+                kind,
+                field,
+                year,
+                missing,
+                sort,
+                reverse,
+                format,
+            } => {
+                let filters = list::Filters {
+                    kind,
+                    fields: field,
+                    year,
+                    missing,
                 };
-
-                if let Some(fields) = fields {
-                    resolver.add_required_fields(fields);
-                }
-
-                interact::user_resolve_entry(&mut resolver)?;
-                let entry = resolver.resolve()?;
-                let cite = entry.cite().to_owned();
-                biblio.insert(entry);
-
-                log::info!("Entry with cite '{cite}' added to bibliography");
-                Ok(cite)
+                return list::list(biblio, &filters, sort, reverse, format);
             }
+            Commands::New { kind, cite, fields } => {
+                new(biblio, kind, cite, fields, cite_template.as_ref())
+            }
+            Commands::Render {
+                cites,
+                csl: Some(csl),
+                locale,
+                citation,
+                to,
+                ..
+            } => render::execute_csl(biblio, &cites, &csl, locale.as_deref(), citation, to),
+            Commands::Render {
+                cites, style, to, ..
+            } => render::execute(biblio, &cites, style, to),
             Commands::Rm { cite } => {
                 log::trace!("Checking current bibliography for entry with this cite key..");
                 if biblio.remove(&cite) {
@@ -184,6 +566,92 @@ impl Commands {
                     Ok(format!("No entry found with the cite key of '{cite}'"))
                 }
             }
-        }
+            Commands::Search {
+                query,
+                limit,
+                format,
+            } => return search::execute(biblio, &query, limit, format, interact),
+            Commands::Show {
+                cite,
+                reference,
+                format,
+            } => return list::show::<F>(biblio, &cite, reference, format),
+            Commands::Undo { count } => return history::undo(biblio, journal()?, count),
+            Commands::Update { cites, yes } => {
+                update::execute(biblio, &config.registry()?, &cites, yes, interact)
+            }
+        };
+        message.map(Outcome::from)
+    }
+}
+
+/// The error returned when there is no entry with the cite key.
+pub(crate) fn no_entry(cite: &str) -> seb::Error {
+    seb::Error::new(
+        seb::ErrorKind::NoValue,
+        format!("No entry found with the cite key of '{cite}'"),
+    )
+}
+
+fn derive(
+    biblio: &mut Biblio,
+    entry: &str,
+    kind: seb::ast::EntryKind<'static>,
+    cite: String,
+    fields: Option<Vec<String>>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut resolver = seb::ast::Entry::resolver_with_cite(kind, cite);
+
+    if let Some(entry) = biblio.get(entry) {
+        resolver.set_fields_from_entry(entry);
+    } else {
+        return Err(no_entry(entry).into());
+    }
+
+    if let Some(fields) = fields {
+        resolver.add_required_fields(fields);
     }
+
+    interact::user_resolve_entry(&mut resolver)?;
+    let derived_entry = resolver.resolve()?;
+    let cite = derived_entry.cite().to_owned();
+    app::check_cite_is_unique(biblio, &cite)?;
+    biblio.insert(derived_entry);
+
+    log::info!("Entry with cite '{cite}' derived from '{entry}' and added to bibliography");
+    Ok(cite)
+}
+
+fn new(
+    biblio: &mut Biblio,
+    kind: seb::ast::EntryKind<'static>,
+    cite: Option<String>,
+    fields: Option<Vec<String>>,
+    cite_template: Option<&Template>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let has_cite = cite.is_some();
+    let mut resolver = if let Some(cite) = cite {
+        seb::ast::Entry::resolver_with_cite(kind, cite)
+    } else {
+        seb::ast::Entry::resolver(kind)
+    };
+
+    if let Some(fields) = fields {
+        resolver.add_required_fields(fields);
+    }
+
+    interact::user_resolve_entry(&mut resolver)?;
+    let mut entry = resolver.resolve()?;
+    if let Some(cite) = cite_template
+        .filter(|_| !has_cite)
+        .and_then(|t| t.render_cite(&entry))
+    {
+        entry.set_cite(cite);
+    }
+    let cite = entry.cite().to_owned();
+    app::check_cite_is_unique(biblio, &cite)?;
+    biblio.insert(entry);
+
+    log::info!("Entry with cite '{cite}' added to bibliography");
+    Ok(cite)
 }
@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use seb::{
+    ast::{Biblio, Entry},
+    render::{
+        csl::{CslRenderer, CslStyle, Locale},
+        Output, Renderer, Style,
+    },
+};
+
+type DynError = Box<dyn std::error::Error>;
+
+pub(super) fn execute(
+    biblio: &Biblio,
+    cites: &[String],
+    style: Style,
+    output: Output,
+) -> Result<String, DynError> {
+    let renderer = Renderer::new(style, output);
+    if cites.is_empty() {
+        return Ok(renderer.render_biblio(biblio));
+    }
+
+    Ok(renderer.render_entries(entries(biblio, cites)?))
+}
+
+pub(super) fn execute_csl(
+    biblio: &Biblio,
+    cites: &[String],
+    style: &Path,
+    locale: Option<&Path>,
+    citation: bool,
+    output: Output,
+) -> Result<String, DynError> {
+    let style = CslStyle::load(style)?;
+    let locale = locale.map_or_else(|| Ok(Locale::default()), Locale::load)?;
+    log::info!("Rendering with the '{}' CSL style", style.title());
+    let renderer = CslRenderer::new(style, locale, output);
+
+    if citation {
+        let all;
+        let cites = if cites.is_empty() {
+            let mut keys = biblio.entries().map(Entry::cite).collect::<Vec<_>>();
+            keys.sort_unstable();
+            all = keys;
+            &all
+        } else {
+            &cites.iter().map(String::as_str).collect::<Vec<_>>()
+        };
+        return Ok(renderer.render_citation(biblio, cites)?);
+    }
+
+    if cites.is_empty() {
+        return Ok(renderer.render_biblio(biblio)?);
+    }
+    Ok(renderer.render_entries(entries(biblio, cites)?)?)
+}
+
+/// The entries with the cite keys, in the order of the cite keys.
+fn entries<'b>(biblio: &'b Biblio, cites: &[String]) -> Result<Vec<&'b Entry>, DynError> {
+    cites
+        .iter()
+        .map(|cite| biblio.get(cite).ok_or_else(|| super::no_entry(cite).into()))
+        .collect()
+}
@@ -0,0 +1,67 @@
+use super::Outcome;
+use crate::{interact::user_select_map, template::Template};
+
+use seb::{ast::Biblio, search::Query};
+
+use eyre::eyre;
+
+type DynError = Box<dyn std::error::Error>;
+
+/// The default template used for each search result.
+const SEARCH_TEMPLATE: &str = r"{cite}\t{year}\t{title}";
+
+pub(super) fn execute(
+    biblio: &Biblio,
+    query: &[String],
+    limit: Option<usize>,
+    template: Option<Template>,
+    interact: bool,
+) -> Result<Outcome, DynError> {
+    let query: Query = query.join(" ").parse()?;
+    if query.is_empty() {
+        return Err(eyre!("The search query is empty").into());
+    }
+
+    let mut hits = biblio.search(&query);
+    if let Some(limit) = limit {
+        hits.truncate(limit);
+    }
+
+    if hits.is_empty() {
+        return Err(seb::Error::new(
+            seb::ErrorKind::NoValue,
+            "No entries found matching the search query",
+        )
+        .into());
+    }
+
+    if interact {
+        let index = user_select_map("Choose an entry", &hits, |hit| {
+            format!("{} ({})", &**hit.entry().title(), hit.entry().cite())
+        })?;
+        let cite = hits[index].entry().cite().to_owned();
+        return Ok(Outcome {
+            message: cite.clone(),
+            selected: vec![cite],
+            reverts: Vec::new(),
+        });
+    }
+
+    let template = match template {
+        Some(template) => template,
+        None => SEARCH_TEMPLATE.parse()?,
+    };
+
+    Ok(Outcome {
+        message: hits
+            .iter()
+            .map(|hit| template.render(hit.entry()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        selected: hits
+            .iter()
+            .map(|hit| hit.entry().cite().to_owned())
+            .collect(),
+        reverts: Vec::new(),
+    })
+}
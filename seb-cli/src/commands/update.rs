@@ -0,0 +1,228 @@
+use crate::{app::take_first_resolvable, interact::user_multi_select};
+
+use seb::{
+    ast::{Biblio, BiblioResolver, Entry, EntryDiff, FieldChange, FieldQuery},
+    provider::{IdKind, Registry},
+};
+
+use eyre::{eyre, Context};
+use log::{info, trace, warn};
+
+/// The name of the field that holds a comma separated list of field names that should not be
+/// changed when updating an entry.
+pub const PINNED_FIELD: &str = "pinned";
+
+type DynError = Box<dyn std::error::Error>;
+
+pub(super) fn execute(
+    biblio: &mut Biblio,
+    providers: &Registry,
+    cites: &[String],
+    yes: bool,
+    interact: bool,
+) -> Result<String, DynError> {
+    let cites = if cites.is_empty() {
+        biblio
+            .entries()
+            .filter(|entry| has_identifier(entry))
+            .map(|entry| entry.cite().to_owned())
+            .collect()
+    } else {
+        cites.to_vec()
+    };
+
+    let mut report = Vec::new();
+    let mut updated = 0;
+    let mut pending = 0;
+
+    for cite in cites {
+        let existing = biblio.get(&cite).ok_or_else(|| super::no_entry(&cite))?;
+
+        let fetched = match fetch_entry(providers, existing) {
+            Ok(Some(fetched)) => fetched,
+            Ok(None) => {
+                warn!("Entry '{cite}' has no doi, isbn or RFC number to update from");
+                continue;
+            }
+            Err(e) => {
+                warn!("Cannot update entry '{cite}': {e}");
+                continue;
+            }
+        };
+
+        let diff = update_diff(existing, &fetched);
+
+        if diff.is_empty() {
+            trace!("Entry '{cite}' is up to date");
+            continue;
+        }
+
+        let diff = if yes {
+            diff
+        } else if interact {
+            user_select_changes(&cite, diff)?
+        } else {
+            report.push(format!("{cite}:\n{diff}"));
+            pending += 1;
+            continue;
+        };
+
+        if diff.is_empty() {
+            continue;
+        }
+
+        let mut resolver = Entry::resolver_with_cite(existing.kind(), existing.cite());
+        resolver.set_fields_from_entry(existing);
+        diff.apply(&mut resolver);
+        let entry = resolver.resolve()?;
+        biblio.insert(entry);
+
+        info!("Entry '{cite}' updated");
+        report.push(format!("{cite}:\n{diff}"));
+        updated += 1;
+    }
+
+    if pending > 0 {
+        report.push(format!(
+            "{pending} entries can be updated, use --yes or -i / --interact to apply the changes"
+        ));
+    } else {
+        report.push(format!("{updated} entries updated"));
+    }
+
+    Ok(report.join("\n"))
+}
+
+/// Returns the field names in the pinned field of the entry.
+fn pinned_fields(entry: &Entry) -> Vec<String> {
+    entry
+        .get_field(PINNED_FIELD)
+        .map(|pinned| {
+            pinned
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_lowercase)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The changes from the existing entry to the fetched entry that should be applied.
+///
+/// Fields that are not present in the fetched entry are kept as they are likely to have been
+/// added by hand, the same goes for the fields that have been pinned.
+fn update_diff(existing: &Entry, fetched: &Entry) -> EntryDiff {
+    let pinned = pinned_fields(existing);
+    let mut diff = existing.diff(fetched);
+    diff.retain(|change| {
+        !matches!(change, FieldChange::Removed { .. }) && !pinned.iter().any(|p| p == change.name())
+    });
+    diff
+}
+
+fn has_identifier(entry: &Entry) -> bool {
+    entry.get_field("doi").is_some()
+        || entry.get_field("isbn").is_some()
+        || rfc_number(entry).is_some()
+}
+
+fn rfc_number(entry: &Entry) -> Option<usize> {
+    entry
+        .get_field("series")
+        .filter(|series| series.starts_with("Request for Comment"))
+        .and(entry.get_field("number"))
+        .and_then(|number| number.parse().ok())
+}
+
+/// Re-fetch the entry using the doi, isbn or RFC number stored in the entry.
+///
+/// Returns `Ok(None)` when the entry has no identifier to fetch by.
+fn fetch_entry(providers: &Registry, entry: &Entry) -> eyre::Result<Option<Entry>> {
+    let res = if let Some(doi) = entry.get_field("doi") {
+        trace!("Fetching entry by doi of '{}'", &**doi);
+        providers.lookup(IdKind::Doi, doi)
+    } else if let Some(isbn) = entry.get_field("isbn") {
+        trace!("Fetching entry by isbn of '{}'", &**isbn);
+        providers.lookup(IdKind::Isbn, isbn)
+    } else if let Some(number) = rfc_number(entry) {
+        trace!("Fetching entry by RFC number '{number}'");
+        providers.lookup(IdKind::Rfc, &number.to_string())
+    } else {
+        return Ok(None);
+    };
+
+    let fetched = res.wrap_err_with(|| eyre!("Cannot find the entry"))?;
+    fill_from_existing(fetched, entry).map(Some)
+}
+
+/// Resolves the fetched entry using the existing entry to fill any missing required fields.
+fn fill_from_existing(
+    fetched: Result<Biblio, BiblioResolver>,
+    existing: &Entry,
+) -> eyre::Result<Entry> {
+    match take_first_resolvable(fetched) {
+        Ok(entry) => Ok(entry),
+        Err(mut resolver) => {
+            let missing = resolver
+                .required_fields()
+                .map(str::to_owned)
+                .collect::<Vec<_>>();
+
+            for name in missing {
+                if let Some(value) = existing.get_field(&name) {
+                    resolver.set_field(&name, value.clone());
+                }
+            }
+
+            resolver
+                .resolve()
+                .map_err(|resolver| eyre!("Fetched entry is incomplete\n{resolver}"))
+        }
+    }
+}
+
+fn user_select_changes(cite: &str, diff: EntryDiff) -> eyre::Result<EntryDiff> {
+    let changes = diff.into_changes();
+    let selected = user_multi_select(
+        &format!("Select changes to apply to '{cite}'"),
+        &changes,
+        true,
+    )?;
+
+    Ok(changes
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| selected.contains(i))
+        .map(|(_, change)| change)
+        .collect())
+}
+
+#[test]
+fn pinned_and_removed_fields_are_kept() {
+    use seb::ast::manual_entry;
+
+    let existing = manual_entry(
+        "cite",
+        &[
+            ("title", "My title"),
+            ("note", "Added by hand"),
+            ("pinned", "title, Pages"),
+            ("pages", "1-2"),
+        ],
+    );
+    let fetched = manual_entry(
+        "cite",
+        &[("title", "Their title"), ("pages", "1-10"), ("volume", "2")],
+    );
+
+    let diff = update_diff(&existing, &fetched);
+
+    assert_eq!(
+        &[FieldChange::Added {
+            name: "volume".to_owned(),
+            value: "2".into(),
+        }][..],
+        diff.changes()
+    );
+}
@@ -0,0 +1,572 @@
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{file::FileFormat, template::Template};
+
+use clap::ArgEnum;
+use eyre::{eyre, Context};
+use seb::provider::Registry;
+use serde::{Deserialize, Serialize};
+
+/// The name of the project configuration file, which is found in the current directory or any of
+/// its parents.
+pub const PROJECT_FILE: &str = ".seb.toml";
+
+/// The number of backups kept of each bibliography file when the `backups` setting is not set.
+pub const DEFAULT_BACKUPS: usize = 1;
+
+/// The settings of seb, where every setting is optional so that the layers of configuration can
+/// be merged.
+///
+/// The layers are, from lowest to highest precedence, the user configuration file at
+/// `$XDG_CONFIG_HOME/seb/config.toml`, the project `.seb.toml` file, the `SEB_*` environment
+/// variables and then the command line flags.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// The bibliography files used as one bibliography when `--file` is not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<PathBuf>>,
+    /// The file that new entries are added to when there is more than one bibliography file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<PathBuf>,
+    /// The format of the bibliography file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<FileFormat>,
+    /// The number of rotating `.bak` backups kept of each bibliography file when it is written.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backups: Option<usize>,
+    /// The template of the cite key of new entries, see [`Template`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cite_template: Option<String>,
+    /// The names of the providers used to search for entries in the order they are tried.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub providers: Option<Vec<String>>,
+    /// The contact email given to APIs that ask for one to be polite.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// The directory of cached API responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<PathBuf>,
+    /// How long cached API responses are used before they are revalidated, see [`parse_duration`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<String>,
+    /// How long a request to an API may take, see [`parse_duration`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    /// The proxy used for requests to the APIs instead of the `HTTPS_PROXY` and `HTTP_PROXY`
+    /// environment variables.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+}
+
+/// The keys of the settings.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    /// The bibliography files used when `--file` is not given, such as 'papers.bib,books.bib'
+    Files,
+    /// The file that new entries are added to when there is more than one bibliography file
+    Target,
+    /// The format of the bibliography file, `bibtex` or `ris`
+    Format,
+    /// The number of rotating `.bak` backups kept of each bibliography file, `0` keeps none
+    Backups,
+    /// The template of the cite key of new entries, such as '{author}{year}'
+    CiteTemplate,
+    /// The providers used to search for entries in order, such as 'crossref,open-library'
+    Providers,
+    /// The contact email given to APIs that ask for one
+    Email,
+    /// The directory of cached API responses
+    CacheDir,
+    /// How long cached API responses are used before they are revalidated, such as '12h' or '7d'
+    CacheTtl,
+    /// How long a request to an API may take, such as '30s'
+    Timeout,
+    /// The url of the proxy used for requests to the APIs
+    Proxy,
+}
+
+impl Key {
+    /// The name of the key in the configuration files.
+    pub fn name(self) -> &'static str {
+        self.to_possible_value()
+            .map(|value| value.get_name())
+            .expect("Keys are not skipped")
+    }
+
+    /// The environment variable that sets the key, such as `SEB_CITE_TEMPLATE`.
+    pub fn var(self) -> String {
+        format!("SEB_{}", self.name().replace('-', "_").to_uppercase())
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Config {
+    /// Returns the value of the key as a string.
+    pub fn get(&self, key: Key) -> Option<String> {
+        match key {
+            Key::Files => self.files.as_ref().map(|paths| {
+                paths
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }),
+            Key::Target => self.target.as_ref().map(|path| path.display().to_string()),
+            Key::Format => self
+                .format
+                .and_then(|format| format.to_possible_value())
+                .map(|value| value.get_name().to_owned()),
+            Key::Backups => self.backups.map(|count| count.to_string()),
+            Key::CiteTemplate => self.cite_template.clone(),
+            Key::Providers => self.providers.as_ref().map(|names| names.join(",")),
+            Key::Email => self.email.clone(),
+            Key::CacheDir => self
+                .cache_dir
+                .as_ref()
+                .map(|path| path.display().to_string()),
+            Key::CacheTtl => self.cache_ttl.clone(),
+            Key::Timeout => self.timeout.clone(),
+            Key::Proxy => self.proxy.clone(),
+        }
+    }
+
+    /// Sets the key from a string value, which is checked to be valid for the key.
+    pub fn set(&mut self, key: Key, value: &str) -> eyre::Result<()> {
+        let value = value.trim();
+        match key {
+            Key::Files => {
+                self.files = Some(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|path| !path.is_empty())
+                        .map(PathBuf::from)
+                        .collect(),
+                );
+            }
+            Key::Target => self.target = Some(value.into()),
+            Key::Format => {
+                self.format = Some(FileFormat::from_str(value, true).map_err(|_| {
+                    eyre!("'{value}' is not a supported format, use 'bibtex' or 'ris'")
+                })?);
+            }
+            Key::Backups => {
+                self.backups = Some(value.parse().map_err(|_| {
+                    eyre!("'{value}' is not a number of backups, such as '0' or '3'")
+                })?);
+            }
+            Key::CiteTemplate => {
+                value.parse::<Template>().map_err(|err| eyre!(err))?;
+                self.cite_template = Some(value.to_owned());
+            }
+            Key::Providers => {
+                self.providers = Some(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_owned)
+                        .collect(),
+                );
+            }
+            Key::Email => {
+                if !value.contains('@') {
+                    return Err(eyre!("'{value}' is not an email address"));
+                }
+                self.email = Some(value.to_owned());
+            }
+            Key::CacheDir => self.cache_dir = Some(value.into()),
+            Key::CacheTtl => {
+                parse_duration(value)?;
+                self.cache_ttl = Some(value.to_owned());
+            }
+            Key::Timeout => {
+                parse_duration(value)?;
+                self.timeout = Some(value.to_owned());
+            }
+            Key::Proxy => {
+                if !value.contains("://") {
+                    return Err(eyre!(
+                        "'{value}' is not a proxy url, such as 'http://host:8080'"
+                    ));
+                }
+                self.proxy = Some(value.to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Overrides the settings with those that are set in `other`.
+    fn merge(&mut self, other: Self) {
+        let Self {
+            files,
+            target,
+            format,
+            backups,
+            cite_template,
+            providers,
+            email,
+            cache_dir,
+            cache_ttl,
+            timeout,
+            proxy,
+        } = other;
+
+        self.files = files.or_else(|| self.files.take());
+        self.target = target.or_else(|| self.target.take());
+        self.format = format.or(self.format);
+        self.backups = backups.or(self.backups);
+        self.cite_template = cite_template.or_else(|| self.cite_template.take());
+        self.providers = providers.or_else(|| self.providers.take());
+        self.email = email.or_else(|| self.email.take());
+        self.cache_dir = cache_dir.or_else(|| self.cache_dir.take());
+        self.cache_ttl = cache_ttl.or_else(|| self.cache_ttl.take());
+        self.timeout = timeout.or_else(|| self.timeout.take());
+        self.proxy = proxy.or_else(|| self.proxy.take());
+    }
+
+    /// The format of the bibliography files, which is the format of the extension of the first
+    /// `file` given with `--file`, and otherwise the `format` setting or the extension of the
+    /// first file of the `files` setting.
+    pub fn file_format(&self, file: Option<&Path>) -> FileFormat {
+        file.and_then(|path| FileFormat::from_path(path).ok())
+            .or(self.format)
+            .or_else(|| {
+                self.files
+                    .iter()
+                    .flatten()
+                    .next()
+                    .and_then(|path| FileFormat::from_path(path).ok())
+            })
+            .unwrap_or(FileFormat::Bibtex)
+    }
+
+    /// The cite key template, which is checked when the configuration is loaded.
+    pub fn cite_template(&self) -> Option<Template> {
+        self.cite_template
+            .as_deref()
+            .and_then(|template| template.parse().ok())
+    }
+
+    /// The registry of the built-in providers, or only those named by the `providers` setting in
+    /// its order.
+    pub fn registry(&self) -> Result<Registry, seb::Error> {
+        let registry = Registry::default();
+        match &self.providers {
+            Some(names) => registry.select(names),
+            None => Ok(registry),
+        }
+    }
+
+    /// The time to live of cached API responses, which is checked when the configuration is
+    /// loaded.
+    pub fn cache_ttl(&self) -> Option<Duration> {
+        self.cache_ttl
+            .as_deref()
+            .and_then(|ttl| parse_duration(ttl).ok())
+    }
+
+    /// How long a request to an API may take, which is checked when the configuration is loaded.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+            .as_deref()
+            .and_then(|timeout| parse_duration(timeout).ok())
+    }
+
+    /// The directory of cached API responses, which defaults to [`default_cache_dir`].
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        self.cache_dir.clone().or_else(default_cache_dir)
+    }
+
+    /// Reads a configuration file, where a file that does not exist has no settings.
+    ///
+    /// Relative paths in the file are relative to the directory of the file.
+    pub fn read(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut config = Self::read_verbatim(path)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        if let Some(files) = &mut config.files {
+            for file in files {
+                *file = dir.join(&*file);
+            }
+        }
+        config.target = config.target.map(|target| dir.join(target));
+        config.cache_dir = config.cache_dir.map(|cache_dir| dir.join(cache_dir));
+        Ok(config)
+    }
+
+    /// Reads a configuration file without changing the relative paths, which is used when the
+    /// file is changed and written again.
+    pub fn read_verbatim(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| eyre!("Cannot read the '{}' file", path.display()))?
+            }
+        };
+
+        let config: Self = toml::from_str(&text).map_err(|err| {
+            seb::Error::wrap_with(
+                seb::ErrorKind::Deserialize,
+                err,
+                format!("The configuration file '{}' is not valid", path.display()),
+            )
+        })?;
+        if let Some(template) = &config.cite_template {
+            template.parse::<Template>().map_err(|err| {
+                seb::Error::new(
+                    seb::ErrorKind::Deserialize,
+                    format!(
+                        "The cite-template in '{}' is not valid: {err}",
+                        path.display()
+                    ),
+                )
+            })?;
+        }
+        for (key, duration) in [
+            ("cache-ttl", &config.cache_ttl),
+            ("timeout", &config.timeout),
+        ] {
+            if let Some(duration) = duration {
+                parse_duration(duration).map_err(|err| {
+                    seb::Error::new(
+                        seb::ErrorKind::Deserialize,
+                        format!("The {key} in '{}' is not valid: {err}", path.display()),
+                    )
+                })?;
+            }
+        }
+        Ok(config)
+    }
+
+    /// Writes the settings to a configuration file, creating the directory of the file if needed.
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| eyre!("Cannot create the '{}' directory", dir.display()))?;
+        }
+        let text = toml::to_string(self)?;
+        fs::write(path, text)
+            .wrap_err_with(|| eyre!("Cannot write the '{}' file", path.display()))?;
+        Ok(())
+    }
+
+    /// Reads the settings from the `SEB_*` environment variables.
+    fn from_env() -> eyre::Result<Self> {
+        let mut config = Self::default();
+        for &key in Key::value_variants() {
+            if let Ok(value) = env::var(key.var()) {
+                config.set(key, &value).map_err(|err| {
+                    eyre!("The {} environment variable is not valid: {err}", key.var())
+                })?;
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Where a setting comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    User,
+    Project,
+    Env,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::User => f.write_str("user"),
+            Self::Project => f.write_str("project"),
+            Self::Env => f.write_str("env"),
+        }
+    }
+}
+
+/// Each layer of the configuration, other than the command line flags.
+#[derive(Debug, Default)]
+pub struct Layers {
+    pub user: Config,
+    pub project: Config,
+    pub env: Config,
+}
+
+impl Layers {
+    /// Loads the user and project configuration files and the environment variables.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let user = match user_path() {
+            Some(path) => Config::read(&path)?,
+            None => Config::default(),
+        };
+        let project = match project_path() {
+            Some(path) => Config::read(&path)?,
+            None => Config::default(),
+        };
+
+        Ok(Self {
+            user,
+            project,
+            env: Config::from_env()?,
+        })
+    }
+
+    /// The settings of all the layers merged in order of precedence.
+    pub fn merged(&self) -> Config {
+        let mut config = self.user.clone();
+        config.merge(self.project.clone());
+        config.merge(self.env.clone());
+        config
+    }
+
+    /// The value of the key and the layer it comes from.
+    pub fn get(&self, key: Key) -> Option<(String, Source)> {
+        [
+            (&self.env, Source::Env),
+            (&self.project, Source::Project),
+            (&self.user, Source::User),
+        ]
+        .into_iter()
+        .find_map(|(config, source)| config.get(key).map(|value| (value, source)))
+    }
+}
+
+/// The path of the user configuration file, `$XDG_CONFIG_HOME/seb/config.toml` which defaults to
+/// `$HOME/.config/seb/config.toml`.
+pub fn user_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(dir.join("seb").join("config.toml"))
+}
+
+/// The directory of cached API responses, `$XDG_CACHE_HOME/seb` which defaults to
+/// `$HOME/.cache/seb`.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(dir.join("seb"))
+}
+
+/// Parses a duration such as `90s`, `30m`, `12h` or `7d`, where a number without a unit is in
+/// seconds.
+pub fn parse_duration(value: &str) -> eyre::Result<Duration> {
+    let value = value.trim();
+    let (number, unit) = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((value, ""), |index| value.split_at(index));
+    let seconds = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => 0,
+    };
+    match number.parse::<u64>() {
+        Ok(number) if seconds > 0 => Ok(Duration::from_secs(number.saturating_mul(seconds))),
+        _ => Err(eyre!(
+            "'{value}' is not a duration, such as '90s', '30m', '12h' or '7d'"
+        )),
+    }
+}
+
+/// The path of the nearest project configuration file in the current directory or its parents.
+pub fn project_path() -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_merge_in_order() {
+        let text = r#"
+            files = ["refs.bib"]
+            cite-template = "{author}{year}"
+            providers = ["crossref", "google-books"]
+        "#;
+        let user: Config = toml::from_str(text).expect("Valid configuration");
+
+        let mut project = Config::default();
+        project.set(Key::Files, "other.ris, books.ris").unwrap();
+        project.set(Key::Format, "RIS").unwrap();
+
+        let mut env = Config::default();
+        env.set(Key::Providers, "openlibrary, crossref").unwrap();
+
+        let layers = Layers { user, project, env };
+        let config = layers.merged();
+
+        assert_eq!(
+            Some(vec![PathBuf::from("other.ris"), PathBuf::from("books.ris")]),
+            config.files
+        );
+        assert_eq!(Some(FileFormat::Ris), config.format);
+        assert_eq!(Some("{author}{year}"), config.cite_template.as_deref());
+        assert_eq!(
+            Some(("openlibrary,crossref".to_owned(), Source::Env)),
+            layers.get(Key::Providers)
+        );
+        assert_eq!(
+            Some(("{author}{year}".to_owned(), Source::User)),
+            layers.get(Key::CiteTemplate)
+        );
+        assert_eq!(None, layers.get(Key::Email));
+    }
+
+    #[test]
+    fn extension_of_the_file_flag_takes_precedence_over_the_format() {
+        let mut config = Config::default();
+        assert_eq!(FileFormat::Bibtex, config.file_format(None));
+
+        config.set(Key::Format, "ris").unwrap();
+        assert_eq!(
+            FileFormat::Bibtex,
+            config.file_format(Some(Path::new("refs.bib")))
+        );
+        assert_eq!(FileFormat::Ris, config.file_format(None));
+        // a file without the extension of a format uses the setting
+        assert_eq!(FileFormat::Ris, config.file_format(Some(Path::new("refs"))));
+
+        config.format = None;
+        config.set(Key::Files, "refs.ris").unwrap();
+        assert_eq!(FileFormat::Ris, config.file_format(None));
+    }
+
+    #[test]
+    fn invalid_values_and_keys() {
+        let mut config = Config::default();
+        assert!(config.set(Key::Format, "json").is_err());
+        assert!(config.set(Key::CiteTemplate, "{author").is_err());
+        assert!(config.set(Key::Email, "nobody").is_err());
+        assert!(config.set(Key::Backups, "-1").is_err());
+        assert!(config.set(Key::CacheTtl, "7 weeks").is_err());
+        assert!(config.set(Key::Timeout, "soon").is_err());
+        assert!(config.set(Key::Proxy, "proxy").is_err());
+        assert_eq!(Config::default(), config);
+
+        assert!(toml::from_str::<Config>("colour = true").is_err());
+        assert_eq!("SEB_CACHE_DIR", Key::CacheDir.var());
+
+        config.set(Key::CacheTtl, "12h").unwrap();
+        assert_eq!(Some(Duration::from_hours(12)), config.cache_ttl());
+        assert_eq!(Some(Duration::from_secs(90)), parse_duration("90").ok());
+    }
+}
@@ -1,25 +1,129 @@
-use std::path::PathBuf;
+use std::{fs, path::Path, path::PathBuf};
 
-use seb::{file::FormatFile, format::Format};
+use seb::{
+    ast::{Biblio, BiblioResolver, Entry},
+    file::{FormatFile, FormatFileSet},
+    format::{BibTex, Format, Ris},
+};
 
+use clap::ArgEnum;
+use eyre::{eyre, Context};
+use serde::{Deserialize, Serialize};
+
+/// Opens the bibliography files, creating those that do not exist, or finds the file of the format
+/// in the current directory when no files are given.
+///
+/// More than one file of the format in the current directory is an error rather than a set of
+/// files, as the files of a directory are not always one bibliography.
 #[allow(clippy::module_name_repetitions)]
-pub fn open_or_create_format_file<F: Format>(
-    file_name: Option<PathBuf>,
-) -> Result<FormatFile<F>, Box<dyn std::error::Error>> {
-    let file = if let Some(path) = file_name {
-        log::trace!("opening {} file as a {} file", path.display(), F::name());
-        FormatFile::open(&path).or_else(|_| {
-            log::info!(
-                "No .{} file found in the current directory - creating the file `{}`",
-                F::ext(),
-                path.display()
-            );
-            FormatFile::create(&path)
-        })?
-    } else {
+pub fn open_or_create_format_files<F: Format>(
+    paths: &[PathBuf],
+) -> Result<FormatFileSet<F>, Box<dyn std::error::Error>> {
+    let files = if paths.is_empty() {
         log::trace!("Searching current directory for any {} files", F::name());
-        FormatFile::find(".")?
+        let file = FormatFile::<F>::find(".")?;
+        FormatFileSet::open([file.path()])?
+    } else {
+        for path in paths {
+            log::trace!("opening {} file as a {} file", path.display(), F::name());
+            if !path.with_extension(F::ext()).exists() {
+                log::info!(
+                    "No .{} file found in the current directory - creating the file `{}`",
+                    F::ext(),
+                    path.display()
+                );
+            }
+        }
+        FormatFileSet::open_or_create(paths)?
     };
 
-    Ok(file)
+    Ok(files)
+}
+
+/// The supported formats of a bibliography file.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    /// BibTeX `.bib` files
+    Bibtex,
+    /// RIS `.ris` files
+    Ris,
+}
+
+impl FileFormat {
+    /// Finds the format that matches the extension of the file path.
+    pub fn from_path(path: &Path) -> eyre::Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(std::ffi::OsStr::to_str)
+            .unwrap_or_default();
+
+        Self::value_variants()
+            .iter()
+            .copied()
+            .find(|format| ext.eq_ignore_ascii_case(format.ext()))
+            .ok_or_else(|| {
+                let supported = Self::value_variants()
+                    .iter()
+                    .map(|format| format!("{} (.{})", format.name(), format.ext()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                eyre!("Unsupported file extension '{ext}' - supported formats are: {supported}")
+            })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bibtex => BibTex::name(),
+            Self::Ris => Ris::name(),
+        }
+    }
+
+    pub fn ext(self) -> &'static str {
+        match self {
+            Self::Bibtex => BibTex::ext(),
+            Self::Ris => Ris::ext(),
+        }
+    }
+
+    pub fn parse(self, text: String) -> Result<Result<Biblio, BiblioResolver>, seb::Error> {
+        match self {
+            Self::Bibtex => BibTex::new(text).parse(),
+            Self::Ris => Ris::new(text).parse(),
+        }
+    }
+
+    pub fn compose(self, biblio: &Biblio) -> String {
+        match self {
+            Self::Bibtex => BibTex::compose(biblio).raw(),
+            Self::Ris => Ris::compose(biblio).raw(),
+        }
+    }
+
+    pub fn unsupported_fields(self, entry: &Entry) -> Vec<String> {
+        match self {
+            Self::Bibtex => BibTex::unsupported_fields(entry),
+            Self::Ris => Ris::unsupported_fields(entry),
+        }
+    }
+}
+
+/// Reads and parses a file using the supported format that matches the file extension.
+pub fn read_biblio_by_ext(
+    path: &Path,
+) -> Result<Result<Biblio, BiblioResolver>, Box<dyn std::error::Error>> {
+    let format = FileFormat::from_path(path)?;
+    read_biblio(path, format)
+}
+
+/// Reads and parses a file using the format.
+pub fn read_biblio(
+    path: &Path,
+    format: FileFormat,
+) -> Result<Result<Biblio, BiblioResolver>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)
+        .wrap_err_with(|| eyre!("Cannot read the '{}' file", path.display()))?;
+
+    log::trace!("Reading {} as a {} file", path.display(), format.name());
+    Ok(format.parse(text)?)
 }
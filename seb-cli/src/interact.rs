@@ -1,6 +1,8 @@
 use dialoguer::Input;
 use eyre::{eyre, Context, Result};
-use seb::ast::{Biblio, BiblioResolver, Entry, QuotedString, Resolver as EntryResolver};
+use seb::ast::{
+    Biblio, BiblioResolver, ConflictPolicy, Entry, QuotedString, Resolver as EntryResolver,
+};
 
 pub fn user_select<S: ToString>(prompt: &str, items: &[S]) -> Result<usize> {
     let selection = dialoguer::Select::with_theme(&dialoguer::theme::ColorfulTheme::default())
@@ -17,6 +19,24 @@ pub fn user_select<S: ToString>(prompt: &str, items: &[S]) -> Result<usize> {
     }
 }
 
+/// Asks the user to choose any of the `items`, which are all chosen to begin with when `checked`
+/// is `true`.
+pub fn user_multi_select<S: ToString>(
+    prompt: &str,
+    items: &[S],
+    checked: bool,
+) -> Result<Vec<usize>> {
+    let defaults = vec![checked; items.len()];
+    let selection = dialoguer::MultiSelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(items)
+        .defaults(&defaults)
+        .interact_opt()
+        .wrap_err_with(|| eyre!("User selection cancelled"))?;
+
+    selection.ok_or_else(|| eyre!("No selection made - cancelling operation"))
+}
+
 pub fn user_select_map<T, F>(prompt: &str, items: &[T], f: F) -> Result<usize>
 where
     F: Fn(&T) -> String,
@@ -25,6 +45,19 @@ where
     user_select(prompt, &display_items)
 }
 
+/// Asks how to resolve a conflict between our entry and their entry, keeping our entry when
+/// the selection is cancelled.
+pub fn user_select_conflict_policy(prompt: &str) -> Result<ConflictPolicy> {
+    let options = ["Keep ours", "Keep theirs", "Rename theirs"];
+    let policies = [
+        ConflictPolicy::KeepOurs,
+        ConflictPolicy::KeepTheirs,
+        ConflictPolicy::Rename,
+    ];
+
+    user_select(prompt, &options).map(|index| policies[index])
+}
+
 pub fn user_select_entry(mut entries: Vec<Entry>) -> Result<Entry> {
     let items = entries_titles(&entries);
     user_select("Confirm entry", &items).map(|i| entries.remove(i))
@@ -0,0 +1,247 @@
+//! The journal of the changes each command made to the entries of a bibliography, which is used
+//! to show the history of the bibliography and to undo commands.
+//!
+//! The journal is a `.seb-history.jsonl` file next to the bibliography file with a JSON record on
+//! each line, so a record can be appended without reading the journal first:
+//!
+//! ```json
+//! {"id":3,"time":1792335120,"command":"rm","changes":[{"cite":"knuth1984","file":"/refs.bib",
+//!  "before":{"cite":"knuth1984","kind":"book","fields":{"title":"..."}},"after":null}]}
+//! ```
+//!
+//! An undo is also a record, with the `reverts` ids of the records it undid.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::output::Snapshot;
+
+use seb::{ast::Biblio, file::FileLock};
+
+use eyre::{eyre, Context};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The name of the journal file, which is in the directory of the bibliography file.
+pub const FILE_NAME: &str = ".seb-history.jsonl";
+
+/// The change made to the entry with a cite key, where an entry that was added has no `before`
+/// and an entry that was removed has no `after`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Change {
+    pub cite: String,
+    /// The bibliography file the entry was in, which is where a removed entry is restored to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl Change {
+    /// A short summary of the change, `+cite` for an added entry, `-cite` for a removed entry
+    /// and `~cite` for a changed entry.
+    pub fn summary(&self) -> String {
+        let sign = match (&self.before, &self.after) {
+            (None, _) => '+',
+            (_, None) => '-',
+            _ => '~',
+        };
+        format!("{sign}{}", self.cite)
+    }
+}
+
+/// The changes made by a command.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Record {
+    pub id: u64,
+    /// The seconds since the unix epoch when the command finished.
+    pub time: u64,
+    pub command: String,
+    /// The ids of the records that this record undid.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reverts: Vec<u64>,
+    pub changes: Vec<Change>,
+}
+
+impl Record {
+    /// The time of the record as an HTTP date, such as `Sun, 18 Oct 2026 15:12:00 GMT`.
+    pub fn date(&self) -> String {
+        httpdate::fmt_http_date(UNIX_EPOCH + Duration::from_secs(self.time))
+    }
+}
+
+/// The journal file of a bibliography.
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    /// The journal of the bibliography file, which is in the same directory as the file.
+    pub fn next_to(file: &Path) -> Self {
+        let dir = file.parent().unwrap_or_else(|| Path::new(""));
+        Self {
+            path: dir.join(FILE_NAME),
+        }
+    }
+
+    /// Reads every record of the journal in order, where a missing journal has no records.
+    ///
+    /// Lines that are not valid records are skipped with a warning, so that one bad line does not
+    /// lose the rest of the history.
+    pub fn records(&self) -> eyre::Result<Vec<Record>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| eyre!("Cannot read the '{}' file", self.path.display()))
+            }
+        };
+
+        Ok(text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(index, line)| {
+                serde_json::from_str(line)
+                    .map_err(|err| {
+                        log::warn!(
+                            "Skipping line {} of '{}' as it is not valid: {err}",
+                            index + 1,
+                            self.path.display()
+                        );
+                    })
+                    .ok()
+            })
+            .collect())
+    }
+
+    /// Appends a record of the changes made by the command and returns the id of the record.
+    ///
+    /// The journal is locked from reading the last id until the record is appended, so that two
+    /// commands finishing at the same time do not append records with the same id.
+    pub fn append(
+        &self,
+        command: &str,
+        reverts: Vec<u64>,
+        changes: Vec<Change>,
+    ) -> eyre::Result<u64> {
+        let _lock = FileLock::exclusive(&self.path)?;
+        let id = self.records()?.last().map_or(1, |record| record.id + 1);
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        let record = Record {
+            id,
+            time,
+            command: command.to_owned(),
+            reverts,
+            changes,
+        };
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .wrap_err_with(|| eyre!("Cannot write the '{}' file", self.path.display()))?;
+        Ok(id)
+    }
+}
+
+/// The records that can be undone, most recent first, which are the records that are not an undo
+/// and have not been undone.
+pub fn undoable(records: &[Record]) -> impl Iterator<Item = &Record> {
+    records
+        .iter()
+        .rev()
+        .filter(|record| record.reverts.is_empty() && !is_undone(records, record.id))
+}
+
+/// Whether a later record undid the record with the id.
+pub fn is_undone(records: &[Record], id: u64) -> bool {
+    records.iter().any(|record| record.reverts.contains(&id))
+}
+
+/// The changes made to the bibliography since the snapshot, in order of cite key, with the file
+/// of each entry found by `source`.
+pub fn changes<S>(snapshot: &Snapshot, biblio: &Biblio, source: S) -> Vec<Change>
+where
+    S: Fn(&str) -> Option<PathBuf>,
+{
+    snapshot
+        .differences(biblio)
+        .into_iter()
+        .map(|difference| Change {
+            file: source(&difference.cite).map(|file| fs::canonicalize(&file).unwrap_or(file)),
+            cite: difference.cite,
+            before: difference.before,
+            after: difference.after,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use seb::ast::manual_entry;
+
+    #[test]
+    fn changes_since_snapshot_are_journaled() {
+        let mut biblio = Biblio::new(vec![manual_entry("kept", &[]), manual_entry("edited", &[])]);
+        let snapshot = Snapshot::new(&biblio);
+
+        biblio.insert(manual_entry("edited", &[("title", "After")]));
+        biblio.insert(manual_entry("added", &[]));
+        biblio.take("kept");
+
+        let changes = changes(&snapshot, &biblio, |_| None);
+        let summary = changes.iter().map(Change::summary).collect::<Vec<_>>();
+        assert_eq!(vec!["+added", "~edited", "-kept"], summary);
+
+        let dir = assert_fs::TempDir::new().expect("Cannot create temp directory for test");
+        let journal = Journal::next_to(&dir.path().join("refs.bib"));
+        assert_eq!(
+            1,
+            journal.append("edit", Vec::new(), changes.clone()).unwrap()
+        );
+        assert_eq!(2, journal.append("undo", vec![1], Vec::new()).unwrap());
+
+        let records = journal.records().unwrap();
+        assert_eq!(changes, records[0].changes);
+        assert!(is_undone(&records, 1));
+        assert_eq!(0, undoable(&records).count());
+    }
+
+    #[test]
+    fn concurrent_appends_have_unique_ids() {
+        let dir = assert_fs::TempDir::new().expect("Cannot create temp directory for test");
+        let file = dir.path().join("refs.bib");
+
+        let appends = (0..8)
+            .map(|_| {
+                let file = file.clone();
+                std::thread::spawn(move || {
+                    Journal::next_to(&file)
+                        .append("add", Vec::new(), Vec::new())
+                        .unwrap()
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut ids = appends
+            .into_iter()
+            .map(|append| append.join().unwrap())
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+
+        assert_eq!((1..=8).collect::<Vec<_>>(), ids);
+    }
+}
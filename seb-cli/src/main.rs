@@ -8,41 +8,85 @@
 )]
 #![allow(clippy::as_conversions, clippy::mod_module_files)]
 
-use std::{error, path::PathBuf, process};
+use std::{
+    error,
+    path::{Path, PathBuf},
+    process,
+};
 
 mod app;
 mod commands;
+mod config;
 mod file;
 mod interact;
+mod journal;
+mod output;
+mod template;
 
-use commands::Commands;
+use commands::{Commands, Outcome};
+use config::{Config, Layers};
+use file::FileFormat;
 use interact::user_resolve_biblio_resolver;
+use journal::Journal;
+use output::{Affected, OutputMode, Snapshot};
 
-use seb::format::{BibTex, Reader, Writer};
+use seb::{
+    api::{
+        cache::{self, CacheMode, CacheSettings},
+        http::{self, ClientSettings},
+    },
+    ast::{Biblio, ConflictPolicy},
+    file::FormatFileSet,
+    format::{BibTex, Format, Ris},
+};
 
 use clap::{Args, Parser};
-use log::trace;
+use eyre::eyre;
+use log::{info, trace, warn};
 
 fn main() {
-    if let Err(err) = try_main() {
-        eprintln!("{}", err);
-        process::exit(2);
+    let cli = Cli::parse();
+    let mode = cli.global_opts.output;
+    let command = cli.command.name();
+
+    let mut affected = Affected::default();
+    let res = try_main(cli, &mut affected);
+
+    match mode {
+        OutputMode::Text => match res {
+            Ok(outcome) => println!("{}", outcome.message),
+            Err(err) => {
+                eprintln!("{err}");
+                process::exit(output::exit_code(&*err) as i32);
+            }
+        },
+        OutputMode::Json => match res {
+            Ok(outcome) => println!("{:#}", output::success(command, &outcome, &affected)),
+            Err(err) => {
+                let (document, code) = output::failure(command, &*err, &affected);
+                println!("{document:#}");
+                process::exit(code as i32);
+            }
+        },
     }
 }
 
-fn try_main() -> Result<(), Box<dyn error::Error>> {
+fn try_main(cli: Cli, affected: &mut Affected) -> Result<Outcome, Box<dyn error::Error>> {
     let Cli {
         command,
         global_opts:
             GlobalOpts {
                 file,
+                target,
                 interact,
+                cache: cache_opts,
                 verbosity,
                 quiet,
+                output,
             },
-    } = Cli::parse();
+    } = cli;
 
-    setup_errlog(verbosity as usize, quiet)?;
+    setup_errlog(verbosity as usize, quiet, output)?;
 
     // `quiet` and `interact` cannot be set at the same time
     let interact = quiet ^ interact;
@@ -51,28 +95,170 @@ fn try_main() -> Result<(), Box<dyn error::Error>> {
         trace!("Interact mode enabled");
     }
 
-    let mut file = file::open_or_create_format_file::<BibTex>(file)?;
-    let biblio = file.read_ast()?;
+    if !command.uses_biblio() {
+        return command.execute::<BibTex>(
+            &mut Biblio::default(),
+            &Config::default(),
+            None,
+            interact,
+        );
+    }
+
+    let mut config = Layers::load()?.merged();
+    // the `--file` and `--target` flags override the settings
+    let format = config.file_format(file.first().map(PathBuf::as_path));
+    let files = if file.is_empty() {
+        config.files.clone().unwrap_or_default()
+    } else {
+        file
+    };
+    config.target = target.or_else(|| config.target.take());
+    cache::set_settings(config.cache_dir().map(|dir| CacheSettings {
+        dir: dir.join("http"),
+        ttl: config.cache_ttl().unwrap_or(cache::DEFAULT_TTL),
+        mode: cache_opts.mode(),
+    }));
+    let client = ClientSettings::default();
+    http::set_settings(Some(ClientSettings {
+        email: config.email.clone(),
+        timeout: config.timeout().unwrap_or(client.timeout),
+        proxy: config.proxy.clone(),
+        ..client
+    }));
+
+    match format {
+        FileFormat::Bibtex => execute::<BibTex>(command, &files, &config, interact, affected),
+        FileFormat::Ris => execute::<Ris>(command, &files, &config, interact, affected),
+    }
+}
+
+/// Executes the command with the bibliography files in the format `F`.
+fn execute<F: Format>(
+    command: Commands,
+    files: &[PathBuf],
+    config: &Config,
+    interact: bool,
+    affected: &mut Affected,
+) -> Result<Outcome, Box<dyn error::Error>> {
+    let mut files = file::open_or_create_format_files::<F>(files)?;
+    files.set_backups(config.backups.unwrap_or(config::DEFAULT_BACKUPS));
+    if let Some(target) = &config.target {
+        files.set_target(target)?;
+    } else if files.len() > 1 && command.adds_entries() {
+        let paths = files
+            .paths()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(eyre!(
+            "There is more than one bibliography file ({paths}) - choose the file that new \
+            entries are added to with `--target` or the `target` setting"
+        )
+        .into());
+    }
+    let biblio = files.read_ast()?;
 
     let mut biblio = match biblio {
         Err(resolver) if interact => user_resolve_biblio_resolver(resolver)?,
         res => res?,
     };
 
-    let command_res = command.execute(&mut biblio, interact);
+    let first = files
+        .paths()
+        .next()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let journal = Journal::next_to(&first);
+    let name = command.name();
+
+    // the entries before the command are only needed to find the entries the command changed
+    let snapshot = command.changes_biblio().then(|| Snapshot::new(&biblio));
+    let command_res = command.execute::<F>(&mut biblio, config, Some(&journal), interact);
+    if let Some(snapshot) = &snapshot {
+        *affected = snapshot.changes(&biblio);
+    }
+    if let Ok(outcome) = &command_res {
+        affected.select(&biblio, &outcome.selected);
+    }
 
     if biblio.dirty() {
-        trace!("Updating the bibliography file..");
-        file.write_ast(biblio)?;
+        let changes = snapshot.as_ref().map_or_else(Vec::new, |snapshot| {
+            journal::changes(snapshot, &biblio, |cite| {
+                files.source(cite).map(Path::to_path_buf)
+            })
+        });
+        let reverts = command_res
+            .as_ref()
+            .map(|outcome| outcome.reverts.clone())
+            .unwrap_or_default();
+        restore_sources(&mut files, &journal, &reverts)?;
+
+        trace!("Updating the bibliography files..");
+        if interact {
+            // changes made to the files while the command ran are merged rather than refused
+            let report = files.write_ast_merged(biblio, |ours, _| {
+                interact::user_select_conflict_policy(&format!(
+                    "Entry '{}' was also changed in the file",
+                    ours.cite()
+                ))
+                .unwrap_or_else(|err| {
+                    warn!("{err} - keeping the existing entry");
+                    ConflictPolicy::KeepOurs
+                })
+            })?;
+            if !report.added.is_empty() || !report.removed.is_empty() {
+                info!(
+                    "Merged the changes made to the file: {} added or changed, {} removed",
+                    report.added.len(),
+                    report.removed.len()
+                );
+            }
+        } else {
+            files.write_ast(biblio)?;
+        }
         trace!("Done!");
+
+        if !changes.is_empty() {
+            // the bibliography is already written so a journal that cannot be written is only a
+            // warning rather than failing the command
+            if let Err(err) = journal.append(name, reverts, changes) {
+                warn!("The changes were not journaled so they cannot be undone: {err}");
+            }
+        }
+    }
+
+    command_res
+}
+
+/// Writes the entries that are restored by an undo to the files they were removed from, rather
+/// than to the target file.
+fn restore_sources<F: Format>(
+    files: &mut FormatFileSet<F>,
+    journal: &Journal,
+    reverts: &[u64],
+) -> eyre::Result<()> {
+    if reverts.is_empty() {
+        return Ok(());
     }
 
-    let message = command_res?;
-    println!("{message}");
+    for record in journal.records()? {
+        if !reverts.contains(&record.id) {
+            continue;
+        }
+        for change in record.changes {
+            if let (Some(file), Some(_)) = (&change.file, &change.before) {
+                files.set_source(&change.cite, file);
+            }
+        }
+    }
     Ok(())
 }
 
-fn setup_errlog(verbosity: usize, quiet: bool) -> Result<(), Box<dyn error::Error>> {
+fn setup_errlog(
+    verbosity: usize,
+    quiet: bool,
+    output: OutputMode,
+) -> Result<(), Box<dyn error::Error>> {
     // if quiet then ignore verbosity but still show errors
     let verbosity = if quiet {
         dbg!("quiet flag used but dbg! and error will still be shown");
@@ -81,7 +267,11 @@ fn setup_errlog(verbosity: usize, quiet: bool) -> Result<(), Box<dyn error::Erro
         verbosity + 2
     };
 
-    stderrlog::new().verbosity(verbosity).init()?;
+    match output {
+        OutputMode::Text => stderrlog::new().verbosity(verbosity).init()?,
+        // the log records are included in the JSON document instead of written to stderr
+        OutputMode::Json => output::setup_diagnostics(verbosity)?,
+    }
     Ok(())
 }
 
@@ -89,6 +279,7 @@ fn setup_errlog(verbosity: usize, quiet: bool) -> Result<(), Box<dyn error::Erro
 #[clap(name = "seb")]
 #[clap(about = "Search and edit bibliographic entries to a supported format file in the terminal")]
 #[clap(version, author)]
+#[clap(after_help = output::EXIT_CODES)]
 struct Cli {
     #[clap(subcommand)]
     command: Commands,
@@ -99,14 +290,33 @@ struct Cli {
 
 #[derive(Debug, Args)]
 struct GlobalOpts {
-    /// The name of the file
-    #[clap(short, long, parse(from_os_str), global = true)]
-    file: Option<PathBuf>,
+    /// The name of the file, which overrides the `files` setting
+    ///
+    /// The flag can be used more than once to work on a set of files as one bibliography, the
+    /// single file of the format in the current directory is used when there is no file.
+    #[clap(
+        short,
+        long,
+        parse(from_os_str),
+        multiple_occurrences = true,
+        global = true
+    )]
+    file: Vec<PathBuf>,
+
+    /// The file that new entries are added to, which overrides the `target` setting
+    ///
+    /// The target must be one of the files and is needed to add entries when there is more than
+    /// one file.
+    #[clap(long, parse(from_os_str), global = true)]
+    target: Option<PathBuf>,
 
     /// Enables interactive mode, which allows for dynamically resolving invalid entries.
     #[clap(short, long, global = true)]
     interact: bool,
 
+    #[clap(flatten)]
+    cache: CacheOpts,
+
     /// How chatty the program is when performing commands
     ///
     /// The number of times this flag is used will increase how chatty
@@ -117,4 +327,34 @@ struct GlobalOpts {
     /// Prevents the program from writing to stdout, errors will still be printed to stderr.
     #[clap(short, long, global = true)]
     quiet: bool,
+
+    /// How the result of the command is written to stdout.
+    ///
+    /// The `json` mode writes a single JSON document for every command with the entries that were
+    /// added, changed, removed or selected, the log messages as diagnostics, and the kind of any
+    /// error with the missing fields of each entry when entries are missing required fields.
+    #[clap(long, arg_enum, default_value = "text", global = true)]
+    output: OutputMode,
+}
+
+#[derive(Args, Debug)]
+struct CacheOpts {
+    /// Only uses cached API responses and never sends a request
+    #[clap(long, global = true, conflicts_with = "refresh")]
+    offline: bool,
+
+    /// Ignores cached API responses and requests them again, updating the cache
+    #[clap(long, global = true)]
+    refresh: bool,
+}
+
+impl CacheOpts {
+    /// The mode of the response cache selected by the flags.
+    const fn mode(&self) -> CacheMode {
+        match (self.offline, self.refresh) {
+            (true, _) => CacheMode::Offline,
+            (false, true) => CacheMode::Refresh,
+            (false, false) => CacheMode::Normal,
+        }
+    }
 }
@@ -0,0 +1,380 @@
+//! The output of the commands, which is either text for people or a JSON document for scripts.
+//!
+//! Every JSON document is an object with a `version`, which only changes when the document does
+//! in a way that is not backwards compatible, and a `status` of `ok` or `error`:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "status": "ok",
+//!   "command": "add",
+//!   "message": "knuth1984",
+//!   "entries": {
+//!     "added": [{ "cite": "knuth1984", "kind": "book", "fields": { "title": "..." } }],
+//!     "changed": [],
+//!     "removed": [],
+//!     "selected": []
+//!   },
+//!   "diagnostics": [{ "level": "info", "message": "..." }]
+//! }
+//! ```
+//!
+//! A failed command has an `error` object instead of the `message`, with the `kind` of the error,
+//! the `message` and the `missing` required fields of each entry when the error is caused by
+//! entries with missing fields, and the `exit_code` of the program.
+
+use std::{collections::BTreeMap, error::Error, io, iter, sync::Mutex};
+
+use crate::commands::Outcome;
+
+use seb::{
+    ast::{Biblio, BiblioResolver, Entry, Resolver},
+    ErrorKind,
+};
+
+use clap::ArgEnum;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::{json, Value};
+
+/// The version of the JSON documents.
+const VERSION: u32 = 1;
+
+/// The exit codes of the program, shown in the help of the program.
+pub const EXIT_CODES: &str = "EXIT CODES:
+    0    The command succeeded
+    1    The command failed for another reason
+    2    The arguments are not valid
+    3    A file could not be read or written
+    4    A bibliography file, style or response is not valid
+    5    Entries are missing required fields
+    6    No entry was found
+    7    The bibliography file changed since it was read
+    8    An API refused the requests because too many were sent
+    9    An API could not be reached or the request timed out";
+
+/// How the result of a command is written to stdout.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputMode {
+    /// The message of the command, errors are written to stderr
+    Text,
+    /// A JSON document with the affected entries, diagnostics and any error
+    Json,
+}
+
+/// The exit code of the program when a command fails, see [`EXIT_CODES`].
+///
+/// The program exits with `0` when the command succeeds and clap exits with `2` when the
+/// arguments cannot be parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExitCode {
+    Failure = 1,
+    Io = 3,
+    InvalidData = 4,
+    MissingFields = 5,
+    NotFound = 6,
+    Conflict = 7,
+    RateLimited = 8,
+    Network = 9,
+}
+
+/// The kind of error that caused a command to fail and the entries with missing fields.
+struct Failure<'e> {
+    kind: &'static str,
+    code: ExitCode,
+    missing: Vec<&'e Resolver>,
+}
+
+impl<'e> Failure<'e> {
+    /// Classifies the error by the first error in the chain of sources that is known.
+    fn new(err: &'e (dyn Error + 'static)) -> Self {
+        let failure = |kind, code| Self {
+            kind,
+            code,
+            missing: Vec::new(),
+        };
+
+        for err in iter::successors(Some(err), |&err| err.source()) {
+            if let Some(resolver) = err.downcast_ref::<Resolver>() {
+                return Self {
+                    missing: vec![resolver],
+                    ..failure("missing-fields", ExitCode::MissingFields)
+                };
+            }
+            if let Some(resolver) = err.downcast_ref::<BiblioResolver>() {
+                return Self {
+                    missing: resolver.resolvers().collect(),
+                    ..failure("missing-fields", ExitCode::MissingFields)
+                };
+            }
+            if let Some(err) = err.downcast_ref::<seb::Error>() {
+                return match err.kind() {
+                    ErrorKind::IO => failure("io", ExitCode::Io),
+                    ErrorKind::Deserialize => failure("invalid-data", ExitCode::InvalidData),
+                    ErrorKind::NoValue | ErrorKind::NotFound => {
+                        failure("not-found", ExitCode::NotFound)
+                    }
+                    ErrorKind::Conflict => failure("conflict", ExitCode::Conflict),
+                    ErrorKind::RateLimited => failure("rate-limited", ExitCode::RateLimited),
+                    ErrorKind::Network => failure("network", ExitCode::Network),
+                };
+            }
+            if err.is::<io::Error>() {
+                return failure("io", ExitCode::Io);
+            }
+        }
+
+        failure("other", ExitCode::Failure)
+    }
+}
+
+/// The exit code of the program when a command fails with the error.
+pub fn exit_code(err: &(dyn Error + 'static)) -> ExitCode {
+    Failure::new(err).code
+}
+
+/// An entry as the JSON object of its serde representation, with the cite key, kind and fields.
+pub fn entry_json(entry: &Entry) -> Value {
+    serde_json::to_value(entry).unwrap_or(Value::Null)
+}
+
+/// The entries of a bibliography before a command is executed, used to find which entries the
+/// command added, changed and removed and to journal the changes.
+pub struct Snapshot(BTreeMap<String, Value>);
+
+/// An entry that is different in the bibliography since a [`Snapshot`], where an added entry has
+/// no `before` and a removed entry has no `after`.
+pub struct Difference {
+    pub cite: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl Snapshot {
+    pub fn new(biblio: &Biblio) -> Self {
+        Self(
+            biblio
+                .entries()
+                .map(|entry| (entry.cite().to_owned(), entry_json(entry)))
+                .collect(),
+        )
+    }
+
+    /// The entries that are different in the bibliography since the snapshot, in order of cite
+    /// key.
+    pub fn differences(&self, biblio: &Biblio) -> Vec<Difference> {
+        let mut after = biblio
+            .entries()
+            .map(|entry| (entry.cite().to_owned(), entry_json(entry)))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut cites = self
+            .0
+            .keys()
+            .chain(after.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        cites.sort();
+        cites.dedup();
+
+        cites
+            .into_iter()
+            .filter_map(|cite| {
+                let before = self.0.get(&cite).cloned();
+                let after = after.remove(&cite);
+                (before != after).then_some(Difference {
+                    cite,
+                    before,
+                    after,
+                })
+            })
+            .collect()
+    }
+
+    /// The entries that the command added, changed and removed since the snapshot.
+    pub fn changes(&self, biblio: &Biblio) -> Affected {
+        let mut affected = Affected::default();
+        for difference in self.differences(biblio) {
+            match (difference.before, difference.after) {
+                (None, Some(after)) => affected.added.push(after),
+                (Some(_), Some(after)) => affected.changed.push(after),
+                (Some(before), None) => affected.removed.push(before),
+                (None, None) => {}
+            }
+        }
+        affected
+    }
+}
+
+/// The entries affected by a command.
+#[derive(Default)]
+pub struct Affected {
+    added: Vec<Value>,
+    changed: Vec<Value>,
+    removed: Vec<Value>,
+    selected: Vec<Value>,
+}
+
+impl Affected {
+    /// Adds the entries the command selected without changing them.
+    pub fn select(&mut self, biblio: &Biblio, cites: &[String]) {
+        self.selected = cites
+            .iter()
+            .filter_map(|cite| biblio.get(cite))
+            .map(entry_json)
+            .collect();
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "added": self.added,
+            "changed": self.changed,
+            "removed": self.removed,
+            "selected": self.selected,
+        })
+    }
+}
+
+/// The JSON document of a command that succeeded.
+pub fn success(command: &str, outcome: &Outcome, affected: &Affected) -> Value {
+    json!({
+        "version": VERSION,
+        "status": "ok",
+        "command": command,
+        "message": outcome.message,
+        "entries": affected.to_json(),
+        "diagnostics": DIAGNOSTICS.take(),
+    })
+}
+
+/// The JSON document of a command that failed and the exit code of the program.
+pub fn failure(
+    command: &str,
+    err: &(dyn Error + 'static),
+    affected: &Affected,
+) -> (Value, ExitCode) {
+    let failure = Failure::new(err);
+    let missing = failure
+        .missing
+        .iter()
+        .map(|resolver| {
+            json!({
+                "cite": resolver.cite(),
+                "kind": resolver.kind().to_string(),
+                "fields": resolver.required_fields().collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let document = json!({
+        "version": VERSION,
+        "status": "error",
+        "command": command,
+        "error": {
+            "kind": failure.kind,
+            "message": err.to_string(),
+            "missing": missing,
+        },
+        "exit_code": failure.code as i32,
+        "entries": affected.to_json(),
+        "diagnostics": DIAGNOSTICS.take(),
+    });
+    (document, failure.code)
+}
+
+/// The logger used in the JSON mode, which collects the log records as diagnostics for the
+/// document instead of writing them to stderr.
+struct Diagnostics(Mutex<Vec<Value>>);
+
+static DIAGNOSTICS: Diagnostics = Diagnostics(Mutex::new(Vec::new()));
+
+impl Diagnostics {
+    fn take(&self) -> Vec<Value> {
+        self.0
+            .lock()
+            .map(|mut records| std::mem::take(&mut *records))
+            .unwrap_or_default()
+    }
+}
+
+impl Log for Diagnostics {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Ok(mut records) = self.0.lock() {
+            records.push(json!({
+                "level": record.level().as_str().to_lowercase(),
+                "message": record.args().to_string(),
+            }));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Sets up the diagnostics logger with the level of the `verbosity`, where `0` only logs errors.
+pub fn setup_diagnostics(verbosity: usize) -> Result<(), log::SetLoggerError> {
+    let level = [Level::Error, Level::Warn, Level::Info, Level::Debug]
+        .get(verbosity)
+        .map_or(LevelFilter::Trace, Level::to_level_filter);
+
+    log::set_logger(&DIAGNOSTICS)?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use seb::ast::{manual_entry, EntryKind};
+
+    #[test]
+    fn errors_are_classified_by_their_source() {
+        let not_found: Box<dyn Error> = Box::new(seb::Error::new(ErrorKind::NoValue, "none"));
+        assert_eq!(ExitCode::NotFound, exit_code(&*not_found));
+
+        let rate_limited: Box<dyn Error> = eyre::Report::new(seb::Error::new(
+            ErrorKind::RateLimited,
+            "The request failed with status 429",
+        ))
+        .wrap_err("Cannot find the entry")
+        .into();
+        assert_eq!(ExitCode::RateLimited, exit_code(&*rate_limited));
+
+        let network: Box<dyn Error> = Box::new(seb::Error::new(ErrorKind::Network, "timed out"));
+        assert_eq!(ExitCode::Network, exit_code(&*network));
+
+        let wrapped: Box<dyn Error> = eyre::Report::new(io::Error::other("disk"))
+            .wrap_err("Cannot read the file")
+            .into();
+        assert_eq!(ExitCode::Io, exit_code(&*wrapped));
+
+        let resolver: Box<dyn Error> = Box::new(Entry::resolver_with_cite(EntryKind::Book, "b"));
+        let (document, code) = failure("new", &*resolver, &Affected::default());
+        assert_eq!(ExitCode::MissingFields, code);
+        assert_eq!("missing-fields", document["error"]["kind"]);
+        assert_eq!("b", document["error"]["missing"][0]["cite"]);
+        assert_eq!(5, document["exit_code"]);
+    }
+
+    #[test]
+    fn changes_since_snapshot() {
+        let mut biblio = Biblio::new(vec![manual_entry("kept", &[]), manual_entry("edited", &[])]);
+        let snapshot = Snapshot::new(&biblio);
+
+        biblio.insert(manual_entry("edited", &[("title", "After")]));
+        biblio.insert(manual_entry("added", &[]));
+        biblio.remove("kept");
+
+        let affected = snapshot.changes(&biblio).to_json();
+        assert_eq!("added", affected["added"][0]["cite"]);
+        assert_eq!("After", affected["changed"][0]["fields"]["title"]);
+        assert_eq!("kept", affected["removed"][0]["cite"]);
+        assert_eq!(0, affected["selected"].as_array().map_or(0, Vec::len));
+    }
+}
@@ -0,0 +1,111 @@
+use seb::ast::{Entry, FieldQuery};
+
+/// An output template for an entry where `{name}` is replaced with the value of the field `name`.
+///
+/// The `{cite}` and `{kind}` placeholders are replaced with the cite key and the kind of the
+/// entry and any field that is missing from the entry is replaced with an empty string. The `\t`
+/// and `\n` escapes are supported so that templates can be given in single quotes in a shell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Field(String),
+}
+
+impl Template {
+    /// Renders the entry using this template.
+    pub fn render(&self, entry: &Entry) -> String {
+        let mut res = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => res.push_str(text),
+                Part::Field(name) => match name.as_str() {
+                    "cite" => res.push_str(entry.cite()),
+                    "kind" => res.push_str(&entry.kind().to_string()),
+                    name => {
+                        if let Some(value) = entry.get_field(name) {
+                            res.push_str(value);
+                        }
+                    }
+                },
+            }
+        }
+        res
+    }
+
+    /// Renders a cite key for the entry using this template, where any character that cannot be
+    /// used in a cite key is removed, and returns `None` when nothing of the cite key is left.
+    pub fn render_cite(&self, entry: &Entry) -> Option<String> {
+        let mut cite = self.render(entry);
+        cite.retain(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'));
+        (!cite.is_empty()).then_some(cite)
+    }
+}
+
+impl std::str::FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = s.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("unclosed '{{' in template '{s}'")),
+                        }
+                    }
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(name.trim().to_lowercase()));
+                }
+                '\\' => match chars.next() {
+                    Some('t') => text.push('\t'),
+                    Some('n') => text.push('\n'),
+                    Some(c) => text.push(c),
+                    None => text.push('\\'),
+                },
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+}
+
+#[test]
+fn template_renders_fields_and_escapes() {
+    use seb::ast::EntryKind;
+
+    let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+    resolver.title("Title");
+    resolver.year("2020");
+    let entry = resolver.resolve().expect("Manual only requires title");
+
+    let template: Template = r"{cite}\t{year}\t{ Title }\n{author}"
+        .parse()
+        .expect("Valid template");
+
+    assert_eq!("cite\t2020\tTitle\n", template.render(&entry));
+    assert!("{cite".parse::<Template>().is_err());
+
+    let cite: Template = "{title}:{year}".parse().expect("Valid template");
+    assert_eq!(Some("Title:2020".to_owned()), cite.render_cite(&entry));
+    let empty: Template = "{author} ".parse().expect("Valid template");
+    assert_eq!(None, empty.render_cite(&entry));
+}
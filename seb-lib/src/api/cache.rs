@@ -0,0 +1,425 @@
+//! An on-disk cache of API responses.
+//!
+//! A [`CachedClient`] wraps another [`Client`] and stores each successful response in the cache
+//! directory, keyed by the requested url and the request headers that change the content of the
+//! response, such as `Accept`. A stored response is reused until it is older than the
+//! time to live, after which it is revalidated with the `ETag` and `Last-Modified` headers that
+//! were sent with it.
+
+use std::fs;
+use std::hash::Hasher;
+use std::io::ErrorKind as IoErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use fnv::FnvHasher;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use super::{Client, HttpClient, Request, Response};
+use crate::{Error, ErrorKind};
+
+/// The time to live of cached responses when none is configured, which is a week.
+pub const DEFAULT_TTL: Duration = Duration::from_hours(7 * 24);
+
+/// The request headers that change the content of a response, so that the same url requested
+/// with different values of these headers has a different cached response.
+const KEY_HEADERS: &[&str] = &["accept", "accept-language"];
+
+static SETTINGS: RwLock<Option<CacheSettings>> = RwLock::new(None);
+
+/// How a [`CachedClient`] uses the responses in the cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Reuse fresh responses and revalidate stale ones.
+    #[default]
+    Normal,
+    /// Only use cached responses, however old, and never send a request.
+    Offline,
+    /// Ignore cached responses and request every url again, storing the new responses.
+    Refresh,
+}
+
+/// The settings of the response cache.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheSettings {
+    /// The directory that the responses are stored in.
+    pub dir: PathBuf,
+    /// How long a stored response is used before it is revalidated.
+    pub ttl: Duration,
+    /// How the cached responses are used.
+    pub mode: CacheMode,
+}
+
+impl CacheSettings {
+    /// Creates the settings of a cache in `dir` with the [`DEFAULT_TTL`] and [`CacheMode::Normal`].
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: DEFAULT_TTL,
+            mode: CacheMode::Normal,
+        }
+    }
+}
+
+/// Sets the cache used by the clients of the `entries_by_*` functions for the whole process.
+///
+/// Passing `None` disables the cache, which is the default.
+pub fn set_settings(settings: Option<CacheSettings>) {
+    match SETTINGS.write() {
+        Ok(mut current) => *current = settings,
+        Err(poisoned) => *poisoned.into_inner() = settings,
+    }
+}
+
+/// Returns the cache settings set with [`set_settings`].
+#[must_use]
+pub fn settings() -> Option<CacheSettings> {
+    match SETTINGS.read() {
+        Ok(current) => current.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// A [`Client`] that serves responses from the on-disk cache before using the `inner` client.
+///
+/// The default client uses the process wide [`settings`], when there are none the requests are
+/// passed straight to the `inner` client.
+#[derive(Debug)]
+pub struct CachedClient<C: Client = HttpClient> {
+    inner: C,
+    settings: Option<CacheSettings>,
+}
+
+impl<C: Client> CachedClient<C> {
+    /// Creates a client that caches the responses of `inner` with the `settings`.
+    pub const fn new(inner: C, settings: Option<CacheSettings>) -> Self {
+        Self { inner, settings }
+    }
+}
+
+impl<C: Client> Default for CachedClient<C> {
+    fn default() -> Self {
+        Self::new(C::default(), settings())
+    }
+}
+
+impl<C: Client> Client for CachedClient<C> {
+    fn email(&self) -> Option<&str> {
+        self.inner.email()
+    }
+
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let Some(settings) = &self.settings else {
+            return self.inner.send(request);
+        };
+        let headers = key_headers(request);
+        let path = entry_path(&settings.dir, &request.url, &headers);
+        let cached = match settings.mode {
+            CacheMode::Refresh => None,
+            CacheMode::Normal | CacheMode::Offline => {
+                CacheEntry::read(&path, &request.url, &headers)
+            }
+        };
+
+        if let Some(entry) = &cached {
+            if settings.mode == CacheMode::Offline || entry.age() < settings.ttl {
+                debug!("Using the cached response of '{}'", request.url);
+                return Ok(entry.response());
+            }
+        } else if settings.mode == CacheMode::Offline {
+            return Err(Error::new(
+                ErrorKind::NoValue,
+                format!(
+                    "There is no cached response of '{}' to use while offline",
+                    request.url
+                ),
+            ));
+        }
+
+        let mut revalidate = request.clone();
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                revalidate
+                    .headers
+                    .push(("If-None-Match".into(), etag.clone()));
+            }
+            if let Some(modified) = &entry.last_modified {
+                revalidate
+                    .headers
+                    .push(("If-Modified-Since".into(), modified.clone()));
+            }
+        }
+
+        let response = self.inner.send(&revalidate)?;
+        match cached {
+            Some(mut entry) if response.status == 304 => {
+                debug!("The cached response of '{}' is still valid", request.url);
+                entry.fetched = now();
+                if let Some(etag) = response.header("etag") {
+                    entry.etag = Some(etag.to_owned());
+                }
+                entry.write(&path);
+                Ok(entry.response())
+            }
+            _ => {
+                if response.is_success() {
+                    CacheEntry::new(&request.url, headers, &response).write(&path);
+                }
+                Ok(response)
+            }
+        }
+    }
+}
+
+/// A response stored in the cache.
+#[derive(Debug, Deserialize, Serialize)]
+struct CacheEntry {
+    url: String,
+    // The request headers of the key of the response, see `key_headers`.
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    // Seconds since the unix epoch when the response was last fetched or revalidated.
+    fetched: u64,
+    status: u16,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_type: Option<String>,
+    body: String,
+}
+
+impl CacheEntry {
+    fn new(url: &str, headers: Vec<(String, String)>, response: &Response) -> Self {
+        Self {
+            url: url.to_owned(),
+            headers,
+            fetched: now(),
+            status: response.status,
+            etag: response.header("etag").map(str::to_owned),
+            last_modified: response.header("last-modified").map(str::to_owned),
+            content_type: response.header("content-type").map(str::to_owned),
+            body: response.body.clone(),
+        }
+    }
+
+    /// Reads the entry at `path`, unless it is missing, invalid or was stored for another `url`
+    /// or `headers`.
+    fn read(path: &Path, url: &str, headers: &[(String, String)]) -> Option<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == IoErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!(
+                    "Unable to read the cached response '{}': {e}",
+                    path.display()
+                );
+                return None;
+            }
+        };
+
+        match serde_json::from_str::<Self>(&text) {
+            Ok(entry) if entry.url == url && entry.headers == headers => Some(entry),
+            Ok(_) => None,
+            Err(e) => {
+                warn!(
+                    "Ignoring the invalid cached response '{}': {e}",
+                    path.display()
+                );
+                None
+            }
+        }
+    }
+
+    /// Writes the entry to `path`, a failure only loses the cached response so it is logged.
+    fn write(&self, path: &Path) {
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| {
+                let text = serde_json::to_string(self)?;
+                // Write to a temporary file first so that readers never see a partial entry.
+                let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+                fs::write(&temp, text)?;
+                fs::rename(&temp, path)
+            });
+
+        if let Err(e) = result {
+            warn!("Unable to cache the response of '{}': {e}", self.url);
+        }
+    }
+
+    fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.fetched))
+    }
+
+    fn response(&self) -> Response {
+        let headers = [
+            ("etag", &self.etag),
+            ("last-modified", &self.last_modified),
+            ("content-type", &self.content_type),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_ref().map(|v| (name.to_owned(), v.clone())))
+        .collect();
+
+        Response {
+            status: self.status,
+            headers,
+            body: self.body.clone(),
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// The headers of the request that are in [`KEY_HEADERS`], with lowercase names in order.
+fn key_headers(request: &Request) -> Vec<(String, String)> {
+    let mut headers = request
+        .headers
+        .iter()
+        .filter(|(name, _)| KEY_HEADERS.iter().any(|key| name.eq_ignore_ascii_case(key)))
+        .map(|(name, value)| (name.to_lowercase(), value.clone()))
+        .collect::<Vec<_>>();
+    headers.sort();
+    headers
+}
+
+/// Returns the path of the cache entry of `url` requested with the key `headers`.
+///
+/// The file name is the FNV-1a hash of the url and headers, which unlike the std hashers is
+/// stable across releases, and the url and headers are kept in the entry to detect collisions.
+fn entry_path(dir: &Path, url: &str, headers: &[(String, String)]) -> PathBuf {
+    let mut hasher = FnvHasher::default();
+    hasher.write(url.as_bytes());
+    for (name, value) in headers {
+        hasher.write(format!("\n{name}: {value}").as_bytes());
+    }
+    dir.join(format!("{:016x}.json", hasher.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use assert_fs::TempDir;
+
+    use super::*;
+
+    thread_local! {
+        static RESPONSES: RefCell<Vec<Response>> = const { RefCell::new(Vec::new()) };
+        static REQUESTS: RefCell<Vec<Request>> = const { RefCell::new(Vec::new()) };
+    }
+
+    #[derive(Default)]
+    struct ScriptedClient;
+
+    impl Client for ScriptedClient {
+        fn send(&self, request: &Request) -> Result<Response, Error> {
+            REQUESTS.with(|requests| requests.borrow_mut().push(request.clone()));
+            RESPONSES
+                .with(|responses| responses.borrow_mut().pop())
+                .ok_or_else(|| Error::new(ErrorKind::Network, "Network error"))
+        }
+    }
+
+    fn respond(status: u16, headers: &[(&str, &str)], body: &str) {
+        let response = Response {
+            status,
+            headers: headers
+                .iter()
+                .map(|(n, v)| ((*n).to_owned(), (*v).to_owned()))
+                .collect(),
+            body: body.to_owned(),
+        };
+        RESPONSES.with(|responses| responses.borrow_mut().insert(0, response));
+    }
+
+    fn take_requests() -> Vec<Request> {
+        REQUESTS.with(|requests| requests.borrow_mut().drain(..).collect())
+    }
+
+    fn client(dir: &TempDir, ttl: Duration, mode: CacheMode) -> CachedClient<ScriptedClient> {
+        CachedClient::new(
+            ScriptedClient,
+            Some(CacheSettings {
+                dir: dir.path().to_owned(),
+                ttl,
+                mode,
+            }),
+        )
+    }
+
+    const URL: &str = "https://api.example.org/works/1";
+
+    #[test]
+    fn fresh_responses_are_reused_and_stale_ones_revalidated() {
+        let dir = TempDir::new().unwrap();
+
+        respond(200, &[("ETag", "\"v1\"")], "first");
+        let fresh = client(&dir, DEFAULT_TTL, CacheMode::Normal);
+        assert_eq!("first", fresh.get_text(URL).unwrap());
+        assert_eq!("first", fresh.get_text(URL).unwrap());
+        assert_eq!(
+            1,
+            take_requests().len(),
+            "the second request should be cached"
+        );
+
+        respond(304, &[], "");
+        let stale = client(&dir, Duration::ZERO, CacheMode::Normal);
+        assert_eq!("first", stale.get_text(URL).unwrap());
+        let requests = take_requests();
+        assert_eq!(
+            vec![("If-None-Match".to_owned(), "\"v1\"".to_owned())],
+            requests[0].headers
+        );
+
+        respond(200, &[("ETag", "\"v2\"")], "second");
+        assert_eq!("second", stale.get_text(URL).unwrap());
+        take_requests();
+
+        respond(500, &[], "server error");
+        let refreshed = client(&dir, Duration::ZERO, CacheMode::Refresh).get_text(URL);
+        assert_eq!(ErrorKind::Network, refreshed.unwrap_err().kind());
+        assert_eq!(
+            Vec::<(String, String)>::new(),
+            take_requests()[0].headers,
+            "refreshing should not revalidate"
+        );
+
+        let offline = client(&dir, Duration::ZERO, CacheMode::Offline);
+        assert_eq!("second", offline.get_text(URL).unwrap());
+        let missing = offline.get_text("https://api.example.org/works/2");
+        assert_eq!(ErrorKind::NoValue, missing.unwrap_err().kind());
+        assert!(
+            take_requests().is_empty(),
+            "offline should never send requests"
+        );
+    }
+
+    #[test]
+    fn responses_are_cached_by_the_accept_header() {
+        let dir = TempDir::new().unwrap();
+        let cache = client(&dir, DEFAULT_TTL, CacheMode::Normal);
+        let html = Request::get(URL).header("Accept", "text/html");
+
+        respond(200, &[], "json");
+        assert_eq!("json", cache.get_text(URL).unwrap());
+        respond(200, &[], "<html>");
+        assert_eq!("<html>", cache.send(&html).unwrap().body);
+        assert_eq!(2, take_requests().len());
+
+        assert_eq!("json", cache.get_text(URL).unwrap());
+        let request = Request::get(URL).header("ACCEPT", "text/html");
+        assert_eq!("<html>", cache.send(&request).unwrap().body);
+        assert!(
+            take_requests().is_empty(),
+            "both responses should be cached"
+        );
+    }
+}
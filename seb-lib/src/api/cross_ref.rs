@@ -1,3 +1,4 @@
+use log::warn;
 use serde::Deserialize;
 
 use crate::{
@@ -7,66 +8,227 @@ use crate::{
     Error, ErrorKind,
 };
 
-use super::Client;
+use reqwest::Url;
+
+use super::{Client, EntryStub, TitleQuery};
 
 #[inline]
 pub(crate) fn get_entries_by_doi<C: Client>(
+    client: &C,
     doi: &str,
 ) -> Result<Result<Biblio, BiblioResolver>, Error> {
-    let url = format!("https://api.crossref.org/works/{doi}/transform/application/x-bibtex");
-    format_api::get_entry_by_url::<C, BibTex>(&url)
+    client
+        .get_text(&doi_url(doi, client.email()))
+        .and_then(format_api::parse_entries::<BibTex>)
+}
+
+pub(crate) fn doi_url(doi: &str, email: Option<&str>) -> String {
+    polite(
+        format!("https://api.crossref.org/works/{doi}/transform/application/x-bibtex"),
+        email,
+    )
+}
+
+/// Adds the `mailto` parameter of the contact `email` to the `url`, so that the request is served
+/// by the `CrossRef` polite pool.
+fn polite(url: String, email: Option<&str>) -> String {
+    let Some(email) = email else {
+        return url;
+    };
+    match Url::parse(&url) {
+        Ok(mut polite) => {
+            polite.query_pairs_mut().append_pair("mailto", email);
+            polite.into()
+        }
+        Err(_) => url,
+    }
 }
 
 #[derive(Deserialize)]
-struct QueryResult {
+pub(crate) struct QueryResult {
     message: Message,
 }
 
 #[derive(Deserialize)]
 struct Message {
-    items: Vec<EntryStub>,
+    items: Vec<Work>,
 }
 
+/// The fields of a work that are selected for an [`EntryStub`].
 #[derive(Deserialize)]
-struct EntryStub {
+struct Work {
     #[serde(rename = "DOI")]
     doi: String,
+    #[serde(default)]
     title: Vec<String>,
+    #[serde(default)]
+    author: Vec<Author>,
+    issued: Option<Issued>,
+    #[serde(rename = "container-title", default)]
+    container_title: Vec<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Author {
+    given: Option<String>,
+    family: Option<String>,
+    // organisations are authors with only a name
+    name: Option<String>,
 }
 
-impl EntryStub {
-    fn into_tuple(mut self) -> (String, String) {
-        (self.doi, self.title.remove(0))
+#[derive(Deserialize)]
+struct Issued {
+    #[serde(rename = "date-parts", default)]
+    date_parts: Vec<Vec<Option<i64>>>,
+}
+
+impl From<Work> for EntryStub {
+    fn from(work: Work) -> Self {
+        let authors = work
+            .author
+            .into_iter()
+            .filter_map(|author| match (author.given, author.family, author.name) {
+                (Some(given), Some(family), _) => Some(format!("{given} {family}")),
+                (_, Some(name), _) | (_, _, Some(name)) => Some(name),
+                _ => None,
+            })
+            .collect();
+        let year = work
+            .issued
+            .and_then(|issued| issued.date_parts.into_iter().next())
+            .and_then(|parts| parts.into_iter().next().flatten())
+            .and_then(|year| u16::try_from(year).ok());
+
+        Self {
+            doi: work.doi,
+            title: work.title.into_iter().next().unwrap_or_default(),
+            authors,
+            year,
+            container: work.container_title.into_iter().next(),
+            kind: work.kind,
+        }
     }
 }
 
 /// Get "stubs" of information about an entry when finding multiple entries by title.
 ///
-/// This reduces the amount of information requested by only getting the title and the DOI of the
-/// entry - the DOI can be used later with `get_entries_by_doi` function.
-pub(crate) fn get_entry_stubs_by_title<C: Client>(
-    title: &str,
-) -> Result<Vec<(String, String)>, Error> {
-    let url = format!("https://api.crossref.org/works?query.title={title}&select=DOI,title");
-    let client = C::default();
-
-    let query_result: QueryResult = client.get_json(&url)?;
+/// This reduces the amount of information requested by only getting the fields of an
+/// [`EntryStub`] - the DOI can be used later with `get_entries_by_doi` function.
+pub(crate) fn get_entry_stubs<C: Client>(
+    client: &C,
+    query: &TitleQuery,
+) -> Result<Vec<EntryStub>, Error> {
+    let query_result: QueryResult = client.get_json(&query_url(query, client.email())?)?;
+    stubs_from(query_result, query)
+}
+
+/// The url of the `CrossRef` works that match the `query`, with the query encoded.
+pub(crate) fn query_url(query: &TitleQuery, email: Option<&str>) -> Result<String, Error> {
+    let mut params = vec![("query.title", query.title.clone())];
+    params.extend(filter_params(query));
+    params.push(("select", STUB_FIELDS.to_owned()));
+    params.push(("rows", query.rows.to_string()));
+    if query.offset > 0 {
+        params.push(("offset", query.offset.to_string()));
+    }
+
+    Url::parse_with_params("https://api.crossref.org/works", &params)
+        .map(|url| polite(url.into(), email))
+        .map_err(|e| Error::wrap(ErrorKind::Deserialize, e))
+}
+
+/// The fields of a work that are selected for an [`EntryStub`].
+const STUB_FIELDS: &str = "DOI,title,author,issued,container-title,type";
+
+/// The stub of each entry in the result of the `query`.
+pub(crate) fn stubs_from(
+    query_result: QueryResult,
+    query: &TitleQuery,
+) -> Result<Vec<EntryStub>, Error> {
     let items = query_result.message.items;
     // check for empty array of items
     if items.is_empty() {
         Err(Error::new(
             ErrorKind::NoValue,
-            format!("No entries found with a title of {title}"),
+            format!("No entries found with a title of {}", query.title),
         ))
     } else {
-        Ok(items.into_iter().map(EntryStub::into_tuple).collect())
+        Ok(items.into_iter().map(EntryStub::from).collect())
     }
 }
 
+/// Search for works that match the `query` and get the entries of the first `limit` results by
+/// their DOI.
+///
+/// Results that cannot be found by their DOI are skipped, and the search and the DOI of every
+/// result are requested with the same `client`.
+pub(crate) fn search_works<C: Client>(
+    client: &C,
+    query: &TitleQuery,
+    limit: usize,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    let mut params = vec![("query.bibliographic", query.title.clone())];
+    params.extend(filter_params(query));
+    params.push(("select", "DOI,title".to_owned()));
+    params.push(("rows", limit.to_string()));
+
+    let url = Url::parse_with_params("https://api.crossref.org/works", &params)
+        .map_err(|e| Error::wrap(ErrorKind::Deserialize, e))?;
+
+    let query_result: QueryResult = client.get_json(&polite(url.into(), client.email()))?;
+    let results = query_result
+        .message
+        .items
+        .into_iter()
+        .take(limit)
+        .filter_map(|work| match get_entries_by_doi(client, &work.doi) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                warn!("Skipping the search result '{}': {e}", work.doi);
+                None
+            }
+        });
+
+    BiblioResolver::combine(results).ok_or_else(|| {
+        Error::new(
+            ErrorKind::NoValue,
+            format!("No entries found matching '{query}'"),
+        )
+    })
+}
+
+/// The parameters of the author, venue, years and type of the works that match the `query`.
+fn filter_params(query: &TitleQuery) -> Vec<(&'static str, String)> {
+    let mut params = Vec::new();
+    if let Some(author) = &query.author {
+        params.push(("query.author", author.clone()));
+    }
+    if let Some(venue) = &query.venue {
+        params.push(("query.container-title", venue.clone()));
+    }
+
+    let filters = date_filters(query.from_year, query.until_year)
+        .chain(query.kind.as_ref().map(|kind| format!("type:{kind}")))
+        .collect::<Vec<_>>();
+    if !filters.is_empty() {
+        params.push(("filter", filters.join(",")));
+    }
+    params
+}
+
+/// The filters of the works published from the year `from` until the year `until`.
+fn date_filters(from: Option<u16>, until: Option<u16>) -> impl Iterator<Item = String> {
+    let from = from.map(|year| format!("from-pub-date:{year}"));
+    let until = until.map(|year| format!("until-pub-date:{year}"));
+    from.into_iter().chain(until)
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
-        api::{assert_url, impl_text_producer, MockClient},
+        api::{assert_url, impl_text_producer, EntryStub, MockClient, TitleQuery},
         ErrorKind,
     };
 
@@ -76,16 +238,60 @@ mod test {
 
     #[test]
     fn by_doi_url_format_is_correct() {
-        assert!(super::get_entries_by_doi::<MockClient>("balloons").is_err());
+        let client: MockClient = MockClient::default();
+        assert!(super::get_entries_by_doi(&client, "balloons").is_err());
         assert_url!("https://api.crossref.org/works/balloons/transform/application/x-bibtex");
     }
 
+    #[test]
+    fn email_of_the_client_is_the_polite_mailto() {
+        assert_eq!(
+            "https://api.crossref.org/works/balloons/transform/application/x-bibtex\
+            ?mailto=me%40example.org",
+            super::doi_url("balloons", Some("me@example.org"))
+        );
+    }
+
     #[test]
     fn json_can_be_deserialized_to_query_result() {
         let qr: QueryResult = serde_json::from_str(ENTRY_STUB_JSON).unwrap();
         assert_eq!(20, qr.message.items.len());
     }
 
+    #[test]
+    fn works_are_converted_to_stubs() {
+        let qr: QueryResult = serde_json::from_str(
+            r#"{ "message": { "items": [{
+                "DOI": "10.1000/1",
+                "title": ["BibTeX"],
+                "author": [
+                    { "given": "Oren", "family": "Patashnik" },
+                    { "name": "TeX Users Group" },
+                    { "given": "Nobody" }
+                ],
+                "issued": { "date-parts": [[1988, 3]] },
+                "container-title": ["TUGboat"],
+                "type": "journal-article"
+            }, { "DOI": "10.1000/2" }] } }"#,
+        )
+        .unwrap();
+        let stubs = super::stubs_from(qr, &TitleQuery::new("BibTeX")).unwrap();
+
+        assert_eq!(
+            EntryStub {
+                doi: "10.1000/1".to_owned(),
+                title: "BibTeX".to_owned(),
+                authors: vec!["Oren Patashnik".to_owned(), "TeX Users Group".to_owned()],
+                year: Some(1988),
+                container: Some("TUGboat".to_owned()),
+                kind: Some("journal-article".to_owned()),
+            },
+            stubs[0]
+        );
+        assert_eq!("", stubs[1].title);
+        assert_eq!(None, stubs[1].year);
+    }
+
     impl_text_producer! {
         ValidJsonProducer => Ok(ENTRY_STUB_JSON.to_owned()),
         EmptyItemProducer => Ok(
@@ -99,24 +305,39 @@ mod test {
 
     #[test]
     fn valid_json_produces_resolved_biblio() {
-        let res = super::get_entry_stubs_by_title::<MockClient<ValidJsonProducer>>("test")
-            .expect("ValidJsonProducer always produces a valid json String to be deserialized");
+        let res = super::get_entry_stubs(
+            &MockClient::<ValidJsonProducer>::default(),
+            &TitleQuery::new("test"),
+        )
+        .expect("ValidJsonProducer always produces a valid json String to be deserialized");
 
         assert_eq!(20, res.len());
+        assert!(res.iter().all(|stub| !stub.doi.is_empty()));
     }
 
     type EmptyItemClient = MockClient<EmptyItemProducer>;
 
     #[test]
     fn by_title_url_format_is_correct() {
-        assert!(super::get_entry_stubs_by_title::<EmptyItemClient>("My test title").is_err());
-        // Not expecting percent encoding here, the str to URL conversion will do this.
-        assert_url!("https://api.crossref.org/works?query.title=My test title&select=DOI,title");
+        assert!(super::get_entry_stubs(
+            &EmptyItemClient::default(),
+            &TitleQuery::new("My test title")
+        )
+        .is_err());
+        assert_url!("https://api.crossref.org/works?query.title=My+test+title&select=DOI%2Ctitle%2Cauthor%2Cissued%2Ccontainer-title%2Ctype&rows=20");
+
+        let query = TitleQuery::new("Rust & C")
+            .author("Klabnik")
+            .years(Some(2018), None)
+            .kind("book")
+            .next_page();
+        assert!(super::get_entry_stubs(&EmptyItemClient::default(), &query).is_err());
+        assert_url!("https://api.crossref.org/works?query.title=Rust+%26+C&query.author=Klabnik&filter=from-pub-date%3A2018%2Ctype%3Abook&select=DOI%2Ctitle%2Cauthor%2Cissued%2Ccontainer-title%2Ctype&rows=20&offset=20");
     }
 
     #[test]
     fn empty_item_returns_no_value_error() {
-        let res = super::get_entry_stubs_by_title::<EmptyItemClient>("test")
+        let res = super::get_entry_stubs(&EmptyItemClient::default(), &TitleQuery::new("test"))
             .expect_err("EmptyItemProducer returns an Err");
 
         assert_eq!(ErrorKind::NoValue, res.kind());
@@ -0,0 +1,292 @@
+use log::info;
+use serde::Deserialize;
+
+use crate::{
+    ast::{self, Biblio, BiblioResolver, EntryKind, Resolver},
+    format::page_range,
+    Error,
+};
+
+use super::Client;
+
+const DATA_CITE_URL: &str = "https://api.datacite.org/dois/";
+
+/// Search entries by a `doi` registered with `DataCite`, such as the DOIs of Zenodo and Figshare.
+pub(crate) fn get_entries_by_doi<C: Client>(
+    client: &C,
+    doi: &str,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    info!("Searching for DOI '{doi}' using DataCite API");
+    let model = client.get_json(&doi_url(doi))?;
+    Ok(entries_from(model))
+}
+
+pub(crate) fn doi_url(doi: &str) -> String {
+    format!("{DATA_CITE_URL}{doi}")
+}
+
+pub(crate) fn entries_from(model: DataCiteModel) -> Result<Biblio, BiblioResolver> {
+    Biblio::try_resolve(vec![Resolver::from(model.data.attributes)])
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DataCiteModel {
+    data: Data,
+}
+
+#[derive(Deserialize)]
+struct Data {
+    attributes: Attributes,
+}
+
+/// The metadata of a DOI, where every field may be missing.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Attributes {
+    doi: Option<String>,
+    #[serde(default)]
+    creators: Vec<Creator>,
+    #[serde(default)]
+    titles: Vec<Title>,
+    publisher: Option<String>,
+    publication_year: Option<Year>,
+    types: Option<Types>,
+    version: Option<String>,
+    url: Option<String>,
+    container: Option<Container>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Creator {
+    name: Option<String>,
+    given_name: Option<String>,
+    family_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Title {
+    title: String,
+    // only the main title has no type, the others are subtitles, translations and so on
+    title_type: Option<String>,
+}
+
+/// The publication year, which older records have as a string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Year {
+    Number(u16),
+    Text(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Types {
+    resource_type_general: Option<String>,
+}
+
+/// The journal, book or series that the resource is published in.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Container {
+    title: Option<String>,
+    volume: Option<String>,
+    issue: Option<String>,
+    first_page: Option<String>,
+    last_page: Option<String>,
+}
+
+/// The entry kind of a `resourceTypeGeneral` of the `DataCite` metadata schema.
+///
+/// Datasets and software use the `dataset` and `software` kinds of `BibLaTeX` and any type
+/// without a matching kind is a `misc` entry.
+fn kind_of(resource_type: &str) -> EntryKind<'static> {
+    match resource_type {
+        "JournalArticle" | "DataPaper" => EntryKind::Article,
+        "Book" => EntryKind::Book,
+        "BookChapter" => EntryKind::BookSection,
+        "ConferencePaper" => EntryKind::InProceedings,
+        "ConferenceProceeding" => EntryKind::Proceedings,
+        "Dissertation" => EntryKind::PhdThesis,
+        "Report" => EntryKind::TechReport,
+        "Dataset" => EntryKind::Other("dataset".into()),
+        "Software" | "ComputationalNotebook" => EntryKind::Other("software".into()),
+        _ => EntryKind::Other("misc".into()),
+    }
+}
+
+impl From<Attributes> for Resolver {
+    fn from(attributes: Attributes) -> Self {
+        let kind = attributes
+            .types
+            .and_then(|types| types.resource_type_general)
+            .map_or_else(|| EntryKind::Other("misc".into()), |kind| kind_of(&kind));
+        // the publisher of a thesis or report is the school or institution that issued it
+        let publisher_field = match kind {
+            EntryKind::PhdThesis => "school",
+            EntryKind::TechReport => "institution",
+            _ => "publisher",
+        };
+        let container_field = match kind {
+            EntryKind::Article => "journal",
+            _ => "booktitle",
+        };
+        let mut resolver = ast::Entry::resolver(kind);
+
+        let title = attributes
+            .titles
+            .iter()
+            .find(|title| title.title_type.is_none())
+            .or_else(|| attributes.titles.first());
+        if let Some(title) = title {
+            resolver.title(title.title.as_str());
+        }
+
+        let authors = attributes
+            .creators
+            .into_iter()
+            .filter_map(|creator| match (creator.family_name, creator.given_name) {
+                (Some(family), Some(given)) => Some(format!("{family}, {given}")),
+                (family, _) => creator.name.or(family),
+            })
+            .collect::<Vec<_>>();
+        if !authors.is_empty() {
+            resolver.author(authors.join(" and "));
+        }
+
+        let year = attributes.publication_year.map(|year| match year {
+            Year::Number(year) => year.to_string(),
+            Year::Text(year) => year,
+        });
+        let container = attributes.container.unwrap_or_default();
+        let pages = page_range(container.first_page, container.last_page);
+
+        let fields = [
+            ("year", year),
+            (publisher_field, attributes.publisher),
+            (container_field, container.title),
+            ("volume", container.volume),
+            ("number", container.issue),
+            ("pages", pages),
+            ("version", attributes.version),
+            ("doi", attributes.doi),
+            ("url", attributes.url),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                resolver.set_field(name, value);
+            }
+        }
+
+        resolver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{assert_url, impl_text_producer, MockClient},
+        ast::{EntryKind, FieldQuery},
+    };
+
+    impl_text_producer! {
+        SoftwareJsonProducer => Ok(
+            r#"{
+                "data": {
+                    "id": "10.5281/zenodo.3233986",
+                    "type": "dois",
+                    "attributes": {
+                        "doi": "10.5281/zenodo.3233986",
+                        "creators": [
+                            {
+                                "name": "Doe, Jane",
+                                "nameType": "Personal",
+                                "givenName": "Jane",
+                                "familyName": "Doe",
+                                "nameIdentifiers": [
+                                    {
+                                        "nameIdentifier": "https://orcid.org/0000-0002-1825-0097",
+                                        "nameIdentifierScheme": "ORCID"
+                                    }
+                                ]
+                            },
+                            { "name": "The Seb Developers", "nameType": "Organizational" }
+                        ],
+                        "titles": [
+                            { "title": "seb: a bibliography manager", "titleType": "Subtitle" },
+                            { "title": "seb" }
+                        ],
+                        "publisher": "Zenodo",
+                        "publicationYear": 2019,
+                        "types": { "resourceTypeGeneral": "Software", "resourceType": "" },
+                        "version": "v0.2.0",
+                        "url": "https://zenodo.org/record/3233986"
+                    }
+                }
+            }"#.to_owned()
+        ),
+        ArticleJsonProducer => Ok(
+            r#"{
+                "data": {
+                    "attributes": {
+                        "doi": "10.1000/article",
+                        "creators": [{ "name": "Roe, Richard" }],
+                        "titles": [{ "title": "Citing data" }],
+                        "publisher": "Copernicus",
+                        "publicationYear": "2020",
+                        "types": { "resourceTypeGeneral": "JournalArticle" },
+                        "container": {
+                            "type": "Journal",
+                            "title": "Earth System Science Data",
+                            "volume": "12",
+                            "firstPage": "1",
+                            "lastPage": "10"
+                        }
+                    }
+                }
+            }"#.to_owned()
+        ),
+    }
+
+    #[test]
+    fn software_is_built_with_the_version_and_creators() {
+        let biblio = super::get_entries_by_doi(
+            &MockClient::<SoftwareJsonProducer>::default(),
+            "10.5281/zenodo.3233986",
+        )
+        .unwrap()
+        .expect("The software has all the required fields");
+        assert_url!("https://api.datacite.org/dois/10.5281/zenodo.3233986");
+
+        let entries = biblio.into_entries();
+        assert_eq!(1, entries.len());
+        let entry = &entries[0];
+        assert_eq!(EntryKind::Other("software".into()), entry.kind());
+        assert_eq!("seb", &**entry.title());
+        let field = |name| entry.get_field(name).map(|value| &**value);
+        assert_eq!(Some("Doe, Jane and The Seb Developers"), field("author"));
+        assert_eq!(Some("2019"), field("year"));
+        assert_eq!(Some("Zenodo"), field("publisher"));
+        assert_eq!(Some("v0.2.0"), field("version"));
+        assert_eq!(Some("10.5281/zenodo.3233986"), field("doi"));
+    }
+
+    #[test]
+    fn journal_articles_use_the_container() {
+        let biblio = super::get_entries_by_doi(
+            &MockClient::<ArticleJsonProducer>::default(),
+            "10.1000/article",
+        )
+        .unwrap()
+        .expect("The article has all the required fields");
+
+        let entries = biblio.into_entries();
+        let entry = &entries[0];
+        assert_eq!(EntryKind::Article, entry.kind());
+        let field = |name| entry.get_field(name).map(|value| &**value);
+        assert_eq!(Some("Earth System Science Data"), field("journal"));
+        assert_eq!(Some("1--10"), field("pages"));
+        assert_eq!(Some("2020"), field("year"));
+    }
+}
@@ -0,0 +1,169 @@
+use log::{debug, info};
+use serde::Deserialize;
+
+use crate::{
+    ast::{Biblio, BiblioResolver},
+    Error, ErrorKind,
+};
+
+use super::{cross_ref, data_cite, Client};
+
+/// The registration agency of a DOI, which decides the API that has the metadata of the DOI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Agency {
+    CrossRef,
+    DataCite,
+    /// Any other agency, such as `mEDRA` or `JaLC`, by the name given by the DOI foundation.
+    Other(String),
+}
+
+impl std::fmt::Display for Agency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CrossRef => f.write_str("Crossref"),
+            Self::DataCite => f.write_str("DataCite"),
+            Self::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+/// The API that has the metadata of a DOI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Api {
+    CrossRef,
+    DataCite,
+}
+
+impl Api {
+    /// The API of the DOIs of the `agency`, where DOIs of any agency other than `DataCite` are
+    /// looked up using the `CrossRef` API, as are DOIs whose agency could not be looked up.
+    pub(crate) const fn of(agency: Option<&Agency>) -> Self {
+        match agency {
+            Some(Agency::DataCite) => Self::DataCite,
+            _ => Self::CrossRef,
+        }
+    }
+}
+
+/// Search entries by `doi` using the API of the registration agency of the `doi`, see
+/// [`Api::of`].
+pub(crate) fn get_entries_by_doi<C: Client>(
+    client: &C,
+    doi: &str,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    match Api::of(registration_agency(client, doi)?.as_ref()) {
+        Api::DataCite => data_cite::get_entries_by_doi(client, doi),
+        Api::CrossRef => cross_ref::get_entries_by_doi(client, doi),
+    }
+}
+
+/// The registration agency of the `doi`, or `None` when the agency could not be looked up.
+///
+/// # Errors
+///
+/// An [`ErrorKind::NotFound`] error is returned when the `doi` does not exist.
+pub(crate) fn registration_agency<C: Client>(
+    client: &C,
+    doi: &str,
+) -> Result<Option<Agency>, Error> {
+    info!("Looking up the registration agency of the DOI '{doi}'");
+    agency_of(client.get_json(&ra_url(doi)), doi)
+}
+
+/// The registration agency of the `doi` from the `response` of the request of [`ra_url`], or
+/// `None` when the request failed.
+pub(crate) fn agency_of(
+    response: Result<Vec<RaRecord>, Error>,
+    doi: &str,
+) -> Result<Option<Agency>, Error> {
+    match response {
+        Ok(records) => agency_from(records, doi).map(Some),
+        Err(e) => {
+            debug!("The registration agency of '{doi}' could not be looked up: {e}");
+            Ok(None)
+        }
+    }
+}
+
+pub(crate) fn ra_url(doi: &str) -> String {
+    format!("https://doi.org/ra/{doi}")
+}
+
+/// A record of the response of the DOI foundation, which has either the agency of the DOI or
+/// the reason that there is none.
+#[derive(Deserialize)]
+pub(crate) struct RaRecord {
+    #[serde(rename = "RA")]
+    agency: Option<String>,
+    status: Option<String>,
+}
+
+/// The agency of the first of the `records` of the `doi`.
+pub(crate) fn agency_from(records: Vec<RaRecord>, doi: &str) -> Result<Agency, Error> {
+    let record = records.into_iter().next().ok_or_else(|| {
+        Error::new(
+            ErrorKind::NoValue,
+            format!("No registration agency found for the DOI '{doi}'"),
+        )
+    })?;
+
+    match (record.agency, record.status) {
+        (Some(agency), _) => Ok(match agency.to_lowercase().as_str() {
+            "crossref" => Agency::CrossRef,
+            "datacite" => Agency::DataCite,
+            _ => Agency::Other(agency),
+        }),
+        (None, status) => Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "The DOI '{doi}' is not registered: {}",
+                status.as_deref().unwrap_or("unknown")
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Agency;
+    use crate::{
+        api::{assert_url, impl_text_producer, MockClient, NetworkErrorProducer},
+        ErrorKind,
+    };
+
+    impl_text_producer! {
+        DataCiteProducer => Ok(r#"[{ "DOI": "10.5281/zenodo.3233986", "RA": "DataCite" }]"#.to_owned()),
+        MedraProducer => Ok(r#"[{ "DOI": "10.1400/1234", "RA": "mEDRA" }]"#.to_owned()),
+        MissingProducer => Ok(r#"[{ "DOI": "10.1000/missing", "status": "DOI does not exist" }]"#.to_owned()),
+    }
+
+    #[test]
+    fn agency_is_read_from_the_first_record() {
+        let agency = super::registration_agency(
+            &MockClient::<DataCiteProducer>::default(),
+            "10.5281/zenodo.3233986",
+        );
+        assert_eq!(Some(Agency::DataCite), agency.unwrap());
+        assert_url!("https://doi.org/ra/10.5281/zenodo.3233986");
+
+        let agency =
+            super::registration_agency(&MockClient::<MedraProducer>::default(), "10.1400/1234");
+        assert_eq!(Some(Agency::Other("mEDRA".to_owned())), agency.unwrap());
+    }
+
+    #[test]
+    fn missing_doi_returns_not_found_error_and_failed_lookup_returns_none() {
+        let err = super::registration_agency(
+            &MockClient::<MissingProducer>::default(),
+            "10.1000/missing",
+        )
+        .unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+
+        let agency = super::registration_agency(
+            &MockClient::<NetworkErrorProducer>::default(),
+            "10.1000/offline",
+        );
+        assert_eq!(None, agency.unwrap());
+    }
+}
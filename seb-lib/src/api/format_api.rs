@@ -11,19 +11,21 @@ pub(crate) fn get_entry_by_url<C: Client, F: Format>(
 ) -> Result<Result<Biblio, BiblioResolver>, Error> {
     let client = C::default();
 
-    client
-        .get_text(url)
-        .and_then(|text| {
-            if text.is_empty() {
-                Err(Error::new(
-                    ErrorKind::NoValue,
-                    "Request did not find any results",
-                ))
-            } else {
-                Ok(F::new(text))
-            }
-        })
-        .and_then(Format::parse)
+    client.get_text(url).and_then(parse_entries::<F>)
+}
+
+/// Parses the `text` of a response in the format `F`.
+pub(crate) fn parse_entries<F: Format>(
+    text: String,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    if text.is_empty() {
+        Err(Error::new(
+            ErrorKind::NoValue,
+            "Request did not find any results",
+        ))
+    } else {
+        F::new(text).parse()
+    }
 }
 
 #[cfg(test)]
@@ -48,7 +50,7 @@ mod tests {
         let err = get_entry_by_url::<MockClient<NetworkErrorProducer>, BibTex>("test")
             .expect_err("MockErrorClient should always cause an error");
 
-        assert_eq!(ErrorKind::IO, err.kind());
+        assert_eq!(ErrorKind::Network, err.kind());
     }
 
     #[test]
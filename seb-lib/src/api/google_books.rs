@@ -6,30 +6,37 @@ use crate::{
     Error, ErrorKind,
 };
 
-use super::Client;
+use reqwest::Url;
+
+use super::{Client, TitleQuery};
 
 const GOOGLE_BOOKS_URL: &str = "https://www.googleapis.com/books/v1/volumes?q=isbn:";
+const GOOGLE_BOOKS_SEARCH_URL: &str = "https://www.googleapis.com/books/v1/volumes";
 
 pub(crate) fn get_entries_by_isbn<C: Client>(
+    client: &C,
     isbn: &str,
 ) -> Result<std::result::Result<Biblio, BiblioResolver>, Error> {
     // remove hypen from ISBN-13 (if applicable)
     let isbn = isbn.replace('-', "");
-    get_book_info::<C>(isbn)
-        .and_then(Resolver::try_from)
-        .map(|e| vec![e])
-        .map(Biblio::try_resolve)
+    get_book_info(client, isbn).and_then(entries_from_book)
 }
 
-pub(crate) fn get_book_info<C: Client>(isbn: String) -> Result<Book, Error> {
+pub(crate) fn get_book_info<C: Client>(client: &C, isbn: String) -> Result<Book, Error> {
     info!("Searching for ISBN '{isbn}' using Google Books API");
-    let mut url = GOOGLE_BOOKS_URL.to_owned();
-    url.push_str(&isbn);
-
-    let client = C::default();
-    let GoogleModel { mut items } = client.get_json(&url)?;
+    let model = client.get_json(&isbn_url(&isbn))?;
 
     trace!("Request was successful");
+    book_from(model, isbn)
+}
+
+pub(crate) fn isbn_url(isbn: &str) -> String {
+    format!("{GOOGLE_BOOKS_URL}{isbn}")
+}
+
+/// The first book of the response to a search by `isbn`.
+pub(crate) fn book_from(model: GoogleModel, isbn: String) -> Result<Book, Error> {
+    let GoogleModel { mut items } = model;
 
     let resolver = items
         .drain(..)
@@ -39,9 +46,72 @@ pub(crate) fn get_book_info<C: Client>(isbn: String) -> Result<Book, Error> {
     Ok(resolver.build(isbn))
 }
 
+pub(crate) fn entries_from_book(book: Book) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    Resolver::try_from(book)
+        .map(|e| vec![e])
+        .map(Biblio::try_resolve)
+}
+
+/// Search for books that match the `query` and use the ISBN of each book in the results.
+///
+/// Books that are missing the information of an entry are skipped.
+pub(crate) fn search_books<C: Client>(
+    client: &C,
+    query: &TitleQuery,
+    limit: usize,
+) -> Result<std::result::Result<Biblio, BiblioResolver>, Error> {
+    info!("Searching for books matching '{query}' using Google Books API");
+    // the author and publisher are searched using the keywords of the query
+    let mut terms = vec![query.title.clone()];
+    terms.extend(
+        query
+            .author
+            .iter()
+            .map(|author| format!("inauthor:{author}")),
+    );
+    terms.extend(
+        query
+            .venue
+            .iter()
+            .map(|venue| format!("inpublisher:{venue}")),
+    );
+    let url = Url::parse_with_params(
+        GOOGLE_BOOKS_SEARCH_URL,
+        &[("q", terms.join(" ")), ("maxResults", limit.to_string())],
+    )
+    .map_err(|e| Error::wrap(ErrorKind::Deserialize, e))?;
+
+    let SearchModel { items } = client.get_json(url.as_str())?;
+
+    let resolvers = items
+        .into_iter()
+        .filter_map(|item| serde_json::from_value::<Item>(item).ok())
+        .filter_map(|item| {
+            let isbn = item.volume_info.isbn().unwrap_or_default();
+            Resolver::try_from(item.build(isbn)).ok()
+        })
+        .collect::<Vec<_>>();
+
+    if resolvers.is_empty() {
+        Err(Error::new(
+            ErrorKind::NoValue,
+            format!("No books found matching '{query}'"),
+        ))
+    } else {
+        Ok(Biblio::try_resolve(resolvers))
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchModel {
+    // The items are deserialized one at a time so that an incomplete book is skipped.
+    #[serde(default)]
+    items: Vec<serde_json::Value>,
+}
+
 #[derive(Deserialize)]
 #[cfg_attr(test, derive(Debug))]
-struct GoogleModel {
+pub(crate) struct GoogleModel {
     items: Vec<Item>,
 }
 
@@ -69,6 +139,29 @@ struct VolumeInfo {
     publisher: String,
     #[serde(rename = "publishedDate")]
     published_date: String,
+    #[serde(rename = "industryIdentifiers", default)]
+    industry_identifiers: Vec<IndustryIdentifier>,
+}
+
+/// An identifier of a book, such as an ISBN.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Debug))]
+struct IndustryIdentifier {
+    #[serde(rename = "type")]
+    kind: String,
+    identifier: String,
+}
+
+impl VolumeInfo {
+    /// The ISBN-13 of the book, or the ISBN-10 when it has no ISBN-13.
+    fn isbn(&self) -> Option<String> {
+        ["ISBN_13", "ISBN_10"].into_iter().find_map(|kind| {
+            self.industry_identifiers
+                .iter()
+                .find(|id| id.kind == kind)
+                .map(|id| id.identifier.clone())
+        })
+    }
 }
 
 impl Item {
@@ -95,6 +188,7 @@ impl TryFrom<Book> for Resolver {
                     title,
                     publisher,
                     published_date,
+                    ..
                 },
         } = book;
 
@@ -129,7 +223,9 @@ impl TryFrom<Book> for Resolver {
         }
 
         resolver.publisher(publisher);
-        resolver.set_field("isbn", isbn);
+        if !isbn.is_empty() {
+            resolver.set_field("isbn", isbn);
+        }
 
         Ok(resolver)
     }
@@ -139,7 +235,7 @@ impl TryFrom<Book> for Resolver {
 mod tests {
     use super::{GoogleModel, Item, VolumeInfo};
     use crate::{
-        api::{assert_url, impl_text_producer, MockClient},
+        api::{assert_url, impl_text_producer, MockClient, TitleQuery},
         ast::{self, FieldQuery, Resolver},
         Error, ErrorKind,
     };
@@ -148,6 +244,23 @@ mod tests {
 
     impl_text_producer! {
         ValidJsonProducer => Ok(GOOGLE_BOOK_JSON.to_owned()),
+        SearchJsonProducer => Ok(
+            r#"{
+                "items": [
+                    { "volumeInfo": { "title": "Code Complete" } },
+                    { "volumeInfo": {
+                        "title": "Code Complete",
+                        "authors": ["Steve McConnell"],
+                        "publisher": "DV-Professional",
+                        "publishedDate": "2004",
+                        "industryIdentifiers": [
+                            { "type": "ISBN_10", "identifier": "0735619670" },
+                            { "type": "ISBN_13", "identifier": "9780735619678" }
+                        ]
+                    } }
+                ]
+            }"#.to_owned()
+        ),
         EmptyBookProducer => Ok(
             r#"{
                 "items": []
@@ -158,7 +271,10 @@ mod tests {
     #[test]
     #[should_panic(expected = "No books found!")]
     fn no_items_in_json_returns_err_no_value() {
-        let err = super::get_entries_by_isbn::<MockClient<EmptyBookProducer>>(&String::default());
+        let err = super::get_entries_by_isbn(
+            &MockClient::<EmptyBookProducer>::default(),
+            &String::default(),
+        );
         let kind = err.as_ref().map_err(Error::kind).map(|_| ());
 
         assert_eq!(Err(ErrorKind::NoValue), kind, "{:?}", err);
@@ -167,22 +283,28 @@ mod tests {
 
     #[test]
     fn isbn_10_url_is_format_is_correct() {
-        assert!(super::get_entries_by_isbn::<MockClient<ValidJsonProducer>>("0735619670").is_ok());
+        assert!(super::get_entries_by_isbn(
+            &MockClient::<ValidJsonProducer>::default(),
+            "0735619670"
+        )
+        .is_ok());
         assert_url!("https://www.googleapis.com/books/v1/volumes?q=isbn:0735619670");
     }
 
     #[test]
     fn isbn_13_url_is_format_is_correct() {
-        assert!(
-            super::get_entries_by_isbn::<MockClient<ValidJsonProducer>>("978-0380815937").is_ok()
-        );
+        assert!(super::get_entries_by_isbn(
+            &MockClient::<ValidJsonProducer>::default(),
+            "978-0380815937"
+        )
+        .is_ok());
         // should strip the hypen in a ISBN-13 string
         assert_url!("https://www.googleapis.com/books/v1/volumes?q=isbn:9780380815937");
     }
 
     #[test]
     fn valid_json_produces_resolved_biblio() {
-        let res = super::get_entries_by_isbn::<MockClient<ValidJsonProducer>>("test")
+        let res = super::get_entries_by_isbn(&MockClient::<ValidJsonProducer>::default(), "test")
             .expect("ValidJsonProducer always produces a valid json String to be deserialized");
 
         let biblio = res.expect("Should produce a resolved Biblio");
@@ -208,6 +330,7 @@ mod tests {
                 title: ignore.clone(),
                 publisher: ignore.clone(),
                 published_date: "2022@apples".to_owned(),
+                industry_identifiers: Vec::new(),
             },
         };
 
@@ -228,6 +351,7 @@ mod tests {
                 title: "Ignore".to_owned(),
                 publisher: "Ignore".to_owned(),
                 published_date: "2002-09-01".to_owned(),
+                industry_identifiers: Vec::new(),
             },
         };
 
@@ -251,6 +375,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn search_skips_incomplete_books() {
+        let query = TitleQuery::new("code complete").author("McConnell");
+        let biblio = super::search_books(&MockClient::<SearchJsonProducer>::default(), &query, 5)
+            .expect("One of the books is complete")
+            .expect("The complete book should be resolved");
+        assert_url!("https://www.googleapis.com/books/v1/volumes?q=code+complete+inauthor%3AMcConnell&maxResults=5");
+
+        let entries = biblio.into_entries();
+        assert_eq!(1, entries.len());
+        assert_eq!("9780735619678", &**entries[0].get_field("isbn").unwrap());
+    }
+
     #[test]
     fn book_can_be_derived_from_json() {
         let isbn = "0735619670";
@@ -0,0 +1,386 @@
+//! The HTTP client that sends requests to the online APIs.
+//!
+//! The [`HttpClient`] identifies itself with a User-Agent that includes the contact email, waits
+//! between requests to the same host and retries requests that were rate limited or failed with
+//! a transient error, backing off exponentially or for as long as the `Retry-After` header asks.
+//!
+//! Proxies are read from the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables
+//! unless [`ClientSettings::proxy`] is set.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use log::{debug, warn};
+use reqwest::{header::HeaderMap, StatusCode, Url};
+
+use super::{Client, Request, Response};
+use crate::{Error, ErrorKind};
+
+static SETTINGS: RwLock<Option<ClientSettings>> = RwLock::new(None);
+
+// The earliest time that the next request to each host may be sent.
+static NEXT_REQUEST: Mutex<Option<HashMap<String, Instant>>> = Mutex::new(None);
+
+/// The settings of the [`HttpClient`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientSettings {
+    /// The contact email given to APIs in the User-Agent and, for `CrossRef`, the `mailto`
+    /// parameter of the polite pool.
+    pub email: Option<String>,
+    /// How long a request may take before it fails.
+    pub timeout: Duration,
+    /// The proxy used for every request instead of those in the environment.
+    pub proxy: Option<String>,
+    /// How many times a rate limited or failed request is retried.
+    pub retries: u32,
+    /// How long to wait before the first retry, which is doubled for each retry after it.
+    pub backoff: Duration,
+    /// The longest time to wait before a retry, a request that asks for longer is not retried.
+    pub max_wait: Duration,
+    /// The shortest time between two requests to the same host.
+    pub rate_limit: Duration,
+    /// The shortest time between two requests to the hosts that differ from the `rate_limit`.
+    pub host_rate_limits: HashMap<String, Duration>,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            email: None,
+            timeout: Duration::from_secs(30),
+            proxy: None,
+            retries: 3,
+            backoff: Duration::from_secs(1),
+            max_wait: Duration::from_mins(1),
+            rate_limit: Duration::from_millis(200),
+            host_rate_limits: HashMap::new(),
+        }
+    }
+}
+
+impl ClientSettings {
+    /// The User-Agent sent with every request, which includes the contact email when there is
+    /// one.
+    #[must_use]
+    pub fn user_agent(&self) -> String {
+        let name = concat!("seb/", env!("CARGO_PKG_VERSION"));
+        match &self.email {
+            Some(email) => format!("{name} (https://github.com/mc1098/ci601; mailto:{email})"),
+            None => format!("{name} (https://github.com/mc1098/ci601)"),
+        }
+    }
+
+    /// The shortest time between two requests to the `host`.
+    #[must_use]
+    pub fn rate_limit_of(&self, host: &str) -> Duration {
+        self.host_rate_limits
+            .get(host)
+            .copied()
+            .unwrap_or(self.rate_limit)
+    }
+
+    /// How long to wait before the retry after `attempt` failed attempts.
+    fn backoff_of(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_wait)
+    }
+}
+
+/// Sets the settings of the clients of the `entries_by_*` functions for the whole process.
+///
+/// Passing `None` uses the default settings.
+pub fn set_settings(settings: Option<ClientSettings>) {
+    match SETTINGS.write() {
+        Ok(mut current) => *current = settings,
+        Err(poisoned) => *poisoned.into_inner() = settings,
+    }
+}
+
+/// Returns the client settings set with [`set_settings`] or the default settings.
+#[must_use]
+pub fn settings() -> ClientSettings {
+    match SETTINGS.read() {
+        Ok(current) => current.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+    .unwrap_or_default()
+}
+
+/// A [`Client`] that sends requests with a `reqwest` client built from the [`ClientSettings`].
+#[derive(Debug)]
+pub struct HttpClient {
+    // The error is kept as a message so that building the client can fail in `Default`.
+    client: Result<reqwest::blocking::Client, String>,
+    settings: ClientSettings,
+}
+
+/// Builds a `reqwest` client, blocking or not, from its `builder` with the proxy, User-Agent and
+/// timeout of the `settings`, keeping an error as a message.
+macro_rules! build_client {
+    ($builder: expr, $settings: expr) => {{
+        let settings: &$crate::api::http::ClientSettings = $settings;
+        settings
+            .proxy
+            .as_deref()
+            .map(reqwest::Proxy::all)
+            .transpose()
+            .and_then(|proxy| {
+                proxy
+                    .into_iter()
+                    .fold($builder, |builder, proxy| builder.proxy(proxy))
+                    .user_agent(settings.user_agent())
+                    .timeout(settings.timeout)
+                    .build()
+            })
+            .map_err(|e| format!("Unable to create the HTTP client: {e}"))
+    }};
+}
+
+#[cfg(feature = "async")]
+pub(crate) use build_client;
+
+impl HttpClient {
+    /// Creates a client with the `settings`.
+    #[must_use]
+    pub fn new(settings: ClientSettings) -> Self {
+        let client = build_client!(reqwest::blocking::Client::builder(), &settings);
+        Self { client, settings }
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(settings())
+    }
+}
+
+impl Client for HttpClient {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let client = self
+            .client
+            .as_ref()
+            .map_err(|message| Error::new(ErrorKind::Network, message.clone()))?;
+        send_with_retries(&self.settings, request, |request| client.send(request))
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.settings.email.as_deref()
+    }
+}
+
+/// Sends the `request` with `send`, waiting for the rate limit of the host before each attempt
+/// and retrying as allowed by the `settings`.
+fn send_with_retries<F>(
+    settings: &ClientSettings,
+    request: &Request,
+    mut send: F,
+) -> Result<Response, Error>
+where
+    F: FnMut(&Request) -> Result<Response, Error>,
+{
+    let host = host_of(&request.url);
+    let mut attempt = 0;
+
+    loop {
+        thread::sleep(reserve(&host, settings.rate_limit_of(&host)));
+        let result = send(request);
+        match retry_wait(settings, attempt, &result) {
+            Some(wait) => {
+                debug!(
+                    "Retrying the request of '{}' in {}ms",
+                    request.url,
+                    wait.as_millis()
+                );
+                thread::sleep(wait);
+                attempt += 1;
+            }
+            None => return result,
+        }
+    }
+}
+
+/// The host of the `url`, which is empty when the url is not valid.
+pub(crate) fn host_of(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+/// Reserves the next request to the `host`, `interval` after the last one, and returns how long
+/// to wait until the request is allowed.
+pub(crate) fn reserve(host: &str, interval: Duration) -> Duration {
+    let now = Instant::now();
+    let mut next = match NEXT_REQUEST.lock() {
+        Ok(next) => next,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let hosts = next.get_or_insert_with(HashMap::new);
+    let ready = hosts
+        .get(host)
+        .copied()
+        .filter(|&at| at > now)
+        .unwrap_or(now);
+    hosts.insert(host.to_owned(), ready + interval);
+    ready - now
+}
+
+/// How long to wait before retrying the attempt with the `result`, or `None` when the result is
+/// final because it succeeded, cannot succeed or the `settings` do not allow another retry.
+pub(crate) fn retry_wait(
+    settings: &ClientSettings,
+    attempt: u32,
+    result: &Result<Response, Error>,
+) -> Option<Duration> {
+    let wait = match result {
+        Ok(response) if is_transient(response.status) => {
+            retry_after(response).unwrap_or_else(|| settings.backoff_of(attempt))
+        }
+        Err(e) if e.kind() == ErrorKind::Network => settings.backoff_of(attempt),
+        _ => return None,
+    };
+    (attempt < settings.retries && wait <= settings.max_wait).then_some(wait)
+}
+
+/// Returns `true` for the status codes of responses that may succeed when retried.
+const fn is_transient(status: u16) -> bool {
+    matches!(status, 408 | 429 | 502 | 503 | 504)
+}
+
+/// The wait requested by the `Retry-After` header, in seconds or as an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.header("retry-after")?.trim();
+    value.parse().map(Duration::from_secs).ok().or_else(|| {
+        httpdate::parse_http_date(value)
+            .ok()
+            .map(|at| at.duration_since(SystemTime::now()).unwrap_or_default())
+    })
+}
+
+impl Client for reqwest::blocking::Client {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let resp = request
+            .headers
+            .iter()
+            .fold(self.get(&request.url), |builder, (name, value)| {
+                builder.header(name, value)
+            })
+            .send()
+            .map_err(|e| Error::wrap(ErrorKind::Network, e))?;
+        let (status, headers) = (resp.status(), resp.headers().clone());
+        response_from(request, status, &headers, resp.text())
+    }
+}
+
+/// The [`Response`] to the `request` with the status, headers and body read from a `reqwest`
+/// response, blocking or not.
+pub(crate) fn response_from(
+    request: &Request,
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: reqwest::Result<String>,
+) -> Result<Response, Error> {
+    let headers = headers
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_owned(), value.to_owned()))
+        })
+        .collect();
+    let body = body.map_err(|e| {
+        warn!("Unable to read the response of '{}'", request.url);
+        Error::wrap(ErrorKind::Network, e)
+    })?;
+
+    Ok(Response {
+        status: status.as_u16(),
+        headers,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&str, &str)]) -> Response {
+        Response {
+            status,
+            headers: headers
+                .iter()
+                .map(|(n, v)| ((*n).to_owned(), (*v).to_owned()))
+                .collect(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn transient_failures_are_retried_until_the_limit() {
+        let settings = ClientSettings {
+            retries: 2,
+            backoff: Duration::ZERO,
+            rate_limit: Duration::ZERO,
+            ..ClientSettings::default()
+        };
+        let request = Request::get("https://api.example.org/retry");
+
+        let mut responses = vec![response(200, &[]), response(503, &[])];
+        let mut attempts = 0;
+        let result = send_with_retries(&settings, &request, |_| {
+            attempts += 1;
+            Ok(responses.pop().unwrap())
+        });
+        assert_eq!(200, result.unwrap().status);
+        assert_eq!(2, attempts);
+
+        let mut attempts = 0;
+        let result = send_with_retries(&settings, &request, |_| {
+            attempts += 1;
+            Err(Error::new(ErrorKind::Network, "Connection refused"))
+        });
+        assert_eq!(ErrorKind::Network, result.unwrap_err().kind());
+        assert_eq!(3, attempts, "the first attempt and two retries");
+
+        let mut attempts = 0;
+        let result = send_with_retries(&settings, &request, |_| {
+            attempts += 1;
+            Ok(response(429, &[("Retry-After", "3600")]))
+        });
+        assert_eq!(429, result.unwrap().status);
+        assert_eq!(1, attempts, "a wait longer than max_wait is not retried");
+
+        let mut attempts = 0;
+        let result = send_with_retries(&settings, &request, |_| {
+            attempts += 1;
+            Ok(response(404, &[]))
+        });
+        assert_eq!(404, result.unwrap().status);
+        assert_eq!(1, attempts);
+    }
+
+    #[test]
+    fn retry_after_and_backoff() {
+        assert_eq!(
+            Some(Duration::from_secs(2)),
+            retry_after(&response(429, &[("Retry-After", "2")]))
+        );
+        assert_eq!(
+            Some(Duration::ZERO),
+            retry_after(&response(
+                503,
+                &[("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT")]
+            ))
+        );
+        assert_eq!(None, retry_after(&response(503, &[])));
+
+        let settings = ClientSettings::default();
+        assert_eq!(Duration::from_secs(4), settings.backoff_of(2));
+        assert_eq!(settings.max_wait, settings.backoff_of(30));
+        assert!(settings.user_agent().starts_with("seb/"));
+    }
+}
@@ -10,8 +10,11 @@ use super::Client;
 pub(crate) fn get_entry_by_rfc<C: Client>(
     number: usize,
 ) -> Result<Result<Biblio, BiblioResolver>, Error> {
-    let url = format!("https://datatracker.ietf.org/doc/rfc{number}/bibtex");
-    format_api::get_entry_by_url::<C, BibTex>(&url)
+    format_api::get_entry_by_url::<C, BibTex>(&rfc_url(number))
+}
+
+pub(crate) fn rfc_url(number: usize) -> String {
+    format!("https://datatracker.ietf.org/doc/rfc{number}/bibtex")
 }
 
 #[cfg(test)]
@@ -1,29 +1,112 @@
+//! Clients used to request bibliographic data from the online APIs.
+//!
+//! The `entries_by_*` functions construct their [`Client`] on demand, so the [`cache`] of API
+//! responses and the [`http`] client are configured for the whole process with
+//! [`cache::set_settings`] and [`http::set_settings`].
+
 use serde::de::DeserializeOwned;
 
+pub mod cache;
 pub(crate) mod cross_ref;
+pub(crate) mod data_cite;
+pub(crate) mod doi;
 pub(crate) mod format_api;
 pub(crate) mod google_books;
+pub mod http;
 pub(crate) mod ietf;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+pub(crate) mod open_library;
+pub mod stub;
+pub(crate) mod web_page;
 
-pub trait Client
-where
-    Self: Default,
-{
-    fn get_text(&self, url: &str) -> Result<String, Error>;
-    fn get_json<T>(&self, url: &str) -> Result<T, Error>
-    where
-        T: DeserializeOwned;
+pub use cache::CachedClient;
+pub use http::HttpClient;
+pub use stub::{EntryStub, TitleQuery};
+
+/// A `GET` request sent by a [`Client`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Request {
+    /// The url that is requested.
+    pub url: String,
+    /// The additional headers sent with the request.
+    pub headers: Vec<(String, String)>,
 }
 
-impl Client for reqwest::blocking::Client {
-    fn get_text(&self, url: &str) -> Result<String, Error> {
-        let resp = self
-            .get(url)
-            .send()
-            .map_err(|e| Error::wrap(ErrorKind::IO, e))?;
-        let text = resp
-            .text()
-            .map_err(|e| Error::wrap(ErrorKind::Deserialize, e))?;
+impl Request {
+    /// Creates a `GET` request of the `url` without any additional headers.
+    #[must_use]
+    pub fn get<S: Into<String>>(url: S) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Adds the `name` header with the `value` to the request.
+    #[must_use]
+    pub fn header<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// The response to a [`Request`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// The headers of the response, with lowercase names.
+    pub headers: Vec<(String, String)>,
+    /// The text of the response body.
+    pub body: String,
+}
+
+impl Response {
+    /// Returns the value of the `name` header, ignoring the case of the name.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns `true` when the status code is in the `2xx` range.
+    #[must_use]
+    pub const fn is_success(&self) -> bool {
+        self.status >= 200 && self.status < 300
+    }
+
+    /// Returns the response when it is successful or an error of the kind that matches the
+    /// status code of the response to the `url`.
+    ///
+    /// # Errors
+    ///
+    /// An [`ErrorKind::NotFound`] error is returned for the `404` and `410` status codes.
+    /// An [`ErrorKind::RateLimited`] error is returned for the `429` status code.
+    /// An [`ErrorKind::Network`] error is returned for any other unsuccessful status code.
+    pub fn error_for_status(self, url: &str) -> Result<Self, Error> {
+        let kind = match self.status {
+            _ if self.is_success() => return Ok(self),
+            404 | 410 => ErrorKind::NotFound,
+            429 => ErrorKind::RateLimited,
+            _ => ErrorKind::Network,
+        };
+        Err(Error::new(
+            kind,
+            format!("The request of '{url}' failed with status {}", self.status),
+        ))
+    }
+
+    /// Returns the body of the response when it is successful and not empty.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned when the status code is unsuccessful, see
+    /// [`Response::error_for_status`], or an [`ErrorKind::NoValue`] error when the body is empty.
+    pub fn into_text(self, url: &str) -> Result<String, Error> {
+        let text = self.error_for_status(url)?.body;
 
         if text.is_empty() {
             Err(Error::new(ErrorKind::NoValue, "Response text is empty"))
@@ -31,15 +114,47 @@ impl Client for reqwest::blocking::Client {
             Ok(text)
         }
     }
+}
+
+/// A client that requests data from the online APIs.
+pub trait Client
+where
+    Self: Default,
+{
+    /// Sends the `request` and returns the response, whatever its status code.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when the request could not be sent or the response could not be read.
+    fn send(&self, request: &Request) -> Result<Response, Error>;
+
+    /// The contact email given to the APIs that ask for one, which is none by default.
+    fn email(&self) -> Option<&str> {
+        None
+    }
+
+    /// Requests the `url` and returns the text of the response body.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when the request fails, the response has an unsuccessful status code
+    /// or the response body is empty.
+    fn get_text(&self, url: &str) -> Result<String, Error> {
+        self.send(&Request::get(url))?.into_text(url)
+    }
 
+    /// Requests the `url` and deserializes the JSON response body.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when the request fails, the response has an unsuccessful status code
+    /// or the response body is not valid JSON for `T`.
     fn get_json<T>(&self, url: &str) -> Result<T, Error>
     where
         T: DeserializeOwned,
     {
-        self.get(url)
-            .send()
-            .map_err(|e| Error::wrap(ErrorKind::IO, e))
-            .and_then(|r| r.json().map_err(|e| Error::wrap(ErrorKind::Deserialize, e)))
+        let response = self.send(&Request::get(url))?.error_for_status(url)?;
+        serde_json::from_str(&response.body).map_err(|e| Error::wrap(ErrorKind::Deserialize, e))
     }
 }
 
@@ -56,7 +171,7 @@ mod test {
     use super::*;
 
     thread_local! {
-        pub(crate) static URL_SINK: std::cell::RefCell<Option<String>> = std::cell::RefCell::new(None);
+        pub(crate) static URL_SINK: std::cell::RefCell<Option<String>> = const { std::cell::RefCell::new(None) };
     }
 
     /// Asserts that the expected URL is the same as the one provided to the [`MockClient`].
@@ -96,6 +211,15 @@ mod test {
     }
 
     impl<P: Producer<String>> Client for MockClient<P> {
+        fn send(&self, request: &Request) -> Result<Response, Error> {
+            URL_SINK.with(|sink| *sink.borrow_mut() = Some(request.url.clone()));
+            P::produce().map(|body| Response {
+                status: 200,
+                headers: Vec::new(),
+                body,
+            })
+        }
+
         fn get_text(&self, url: &str) -> Result<String, Error> {
             URL_SINK.with(|sink| *sink.borrow_mut() = Some(url.to_owned()));
             P::produce()
@@ -128,7 +252,7 @@ mod test {
     }
     impl_text_producer! {
         EmptyTextProducer => Ok("".to_owned()),
-        NetworkErrorProducer => Err(Error::new(ErrorKind::IO, "Network error")),
+        NetworkErrorProducer => Err(Error::new(ErrorKind::Network, "Network error")),
     }
 
     pub(crate) use assert_url;
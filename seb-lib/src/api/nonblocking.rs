@@ -0,0 +1,322 @@
+//! Async equivalents of the `entries_by_*` functions for use inside an async runtime.
+//!
+//! The blocking client of the crate root functions cannot be used inside a `tokio` runtime, so
+//! these functions take an [`AsyncClient`] instead, such as a `reqwest::Client` or an
+//! [`AsyncHttpClient`] that shares the [`super::http`] settings. Responses are not cached.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), seb::Error> {
+//! let client = seb::nonblocking::AsyncHttpClient::default();
+//! let entries = seb::nonblocking::entries_by_doi(&client, "10.1145/3290380").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use log::{debug, trace};
+use serde::de::DeserializeOwned;
+
+use super::{
+    cross_ref, data_cite, doi, format_api, google_books,
+    http::{self, ClientSettings},
+    ietf, EntryStub, Request, Response, TitleQuery,
+};
+use crate::{
+    ast::{Biblio, BiblioResolver},
+    format::{BibTex, Format},
+    Error, ErrorKind,
+};
+
+/// A client that requests data from the online APIs without blocking.
+pub trait AsyncClient: Sync {
+    /// Sends the `request` and returns the response, whatever its status code.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when the request could not be sent or the response could not be read.
+    fn send(&self, request: &Request) -> impl Future<Output = Result<Response, Error>> + Send;
+
+    /// The contact email given to the APIs that ask for one, which is none by default.
+    fn email(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl AsyncClient for reqwest::Client {
+    async fn send(&self, request: &Request) -> Result<Response, Error> {
+        let resp = request
+            .headers
+            .iter()
+            .fold(self.get(&request.url), |builder, (name, value)| {
+                builder.header(name, value)
+            })
+            .send()
+            .await
+            .map_err(|e| Error::wrap(ErrorKind::Network, e))?;
+        let (status, headers) = (resp.status(), resp.headers().clone());
+        http::response_from(request, status, &headers, resp.text().await)
+    }
+}
+
+/// An [`AsyncClient`] that sends requests with a `reqwest` client built from the
+/// [`ClientSettings`], waiting for the rate limit of each host and retrying transient failures.
+///
+/// The waits use the `tokio` timer, so the client must be used inside a `tokio` runtime.
+#[derive(Debug)]
+pub struct AsyncHttpClient {
+    // The error is kept as a message so that building the client can fail in `Default`.
+    client: Result<reqwest::Client, String>,
+    settings: ClientSettings,
+}
+
+impl AsyncHttpClient {
+    /// Creates a client with the `settings`.
+    #[must_use]
+    pub fn new(settings: ClientSettings) -> Self {
+        let client = http::build_client!(reqwest::Client::builder(), &settings);
+        Self { client, settings }
+    }
+}
+
+impl Default for AsyncHttpClient {
+    /// Creates a client with the process wide [`http::settings`].
+    fn default() -> Self {
+        Self::new(http::settings())
+    }
+}
+
+impl AsyncClient for AsyncHttpClient {
+    async fn send(&self, request: &Request) -> Result<Response, Error> {
+        let client = self
+            .client
+            .as_ref()
+            .map_err(|message| Error::new(ErrorKind::Network, message.clone()))?;
+        let host = http::host_of(&request.url);
+        let mut attempt = 0;
+
+        loop {
+            tokio::time::sleep(http::reserve(&host, self.settings.rate_limit_of(&host))).await;
+            let result = client.send(request).await;
+            match http::retry_wait(&self.settings, attempt, &result) {
+                Some(wait) => {
+                    debug!(
+                        "Retrying the request of '{}' in {}ms",
+                        request.url,
+                        wait.as_millis()
+                    );
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                }
+                None => return result,
+            }
+        }
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.settings.email.as_deref()
+    }
+}
+
+async fn get_text<C: AsyncClient>(client: &C, url: &str) -> Result<String, Error> {
+    let response = client.send(&Request::get(url)).await?;
+    response.into_text(url)
+}
+
+async fn get_json<C: AsyncClient, T: DeserializeOwned>(client: &C, url: &str) -> Result<T, Error> {
+    let text = get_text(client, url).await?;
+    serde_json::from_str(&text).map_err(|e| Error::wrap(ErrorKind::Deserialize, e))
+}
+
+/// Search bibliographic entries by `doi` using the API of the registration agency of the `doi`.
+///
+/// # Errors
+///
+/// An `Err` is returned when no entry is found for the `doi`.
+/// An `Err` is returned when the response from the API cannot be parsed into a valid
+/// [Entry](crate::ast::Entry).
+pub async fn entries_by_doi<C: AsyncClient>(
+    client: &C,
+    doi: &str,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    trace!("Search entries by doi of '{doi}'");
+    let agency = doi::agency_of(get_json(client, &doi::ra_url(doi)).await, doi)?;
+
+    match doi::Api::of(agency.as_ref()) {
+        doi::Api::DataCite => {
+            let model = get_json(client, &data_cite::doi_url(doi)).await?;
+            Ok(data_cite::entries_from(model))
+        }
+        doi::Api::CrossRef => {
+            entries_by_url::<C, BibTex>(client, &cross_ref::doi_url(doi, client.email())).await
+        }
+    }
+}
+
+/// Search bibliographic entries by `isbn` using the default API.
+///
+/// # Errors
+///
+/// An `Err` is returned when no entry is found for the `isbn`.
+/// An `Err` is returned when the response from the API cannot be parsed into a valid
+/// [Entry](crate::ast::Entry).
+pub async fn entries_by_isbn<C: AsyncClient>(
+    client: &C,
+    isbn: &str,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    trace!("Search entries by ISBN of '{isbn}'");
+    let isbn = isbn.replace('-', "");
+    let model = get_json(client, &google_books::isbn_url(&isbn)).await?;
+    google_books::book_from(model, isbn).and_then(google_books::entries_from_book)
+}
+
+/// Search bibliographic entries by `IETF RFC number`.
+///
+/// # Errors
+///
+/// An `Err` is returned when no entry is found for the RFC number.
+/// An `Err` is returned when the response from the API cannot be parsed into a valid
+/// [Entry](crate::ast::Entry).
+pub async fn entries_by_rfc<C: AsyncClient>(
+    client: &C,
+    number: usize,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    trace!("Search entries by IETF RFC number '{number}'");
+    entries_by_url::<C, BibTex>(client, &ietf::rfc_url(number)).await
+}
+
+/// Search bibliographic entries at a given `url` when the expected text format matches the `F:
+/// Format` used when calling this function.
+///
+/// # Errors
+///
+/// An `Err` is returned when no entry is found at the `url`.
+/// An `Err` is returned when the response from the API cannot be parsed into a valid
+/// [Entry](crate::ast::Entry).
+pub async fn entries_by_url<C: AsyncClient, F: Format>(
+    client: &C,
+    url: &str,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    trace!("Search entries at url of '{url}'");
+    get_text(client, url)
+        .await
+        .and_then(format_api::parse_entries::<F>)
+}
+
+/// Search the [`EntryStub`]s of the first page of entries that have a title of `title`.
+///
+/// # Errors
+///
+/// An `Err` is returned when no entries are found with the `title`.
+pub async fn entry_stubs_by_title<C: AsyncClient>(
+    client: &C,
+    title: &str,
+) -> Result<Vec<EntryStub>, Error> {
+    entry_stubs_by_query(client, &TitleQuery::new(title)).await
+}
+
+/// Search the [`EntryStub`]s of the page of entries that match the `query`.
+///
+/// # Errors
+///
+/// An `Err` is returned when no entries match the `query`.
+pub async fn entry_stubs_by_query<C: AsyncClient>(
+    client: &C,
+    query: &TitleQuery,
+) -> Result<Vec<EntryStub>, Error> {
+    trace!("Search entries that have a title of '{}'", query.title);
+    let query_result = get_json(client, &cross_ref::query_url(query, client.email())?).await?;
+    cross_ref::stubs_from(query_result, query)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::*;
+
+    /// A client that responds to every request with the same response, recording the urls.
+    struct StaticClient {
+        status: u16,
+        body: &'static str,
+        urls: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl StaticClient {
+        const fn new(status: u16, body: &'static str) -> Self {
+            Self {
+                status,
+                body,
+                urls: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl AsyncClient for StaticClient {
+        async fn send(&self, request: &Request) -> Result<Response, Error> {
+            self.urls.lock().unwrap().push(request.url.clone());
+            Ok(Response {
+                status: self.status,
+                headers: Vec::new(),
+                body: self.body.to_owned(),
+            })
+        }
+    }
+
+    /// Polls a future that never waits to completion.
+    fn ready<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("The future should not wait"),
+        }
+    }
+
+    #[test]
+    fn futures_can_be_spawned_on_a_multi_threaded_runtime() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let client = AsyncHttpClient::default();
+        assert_send(&entries_by_doi(&client, "10.1000/spawned"));
+        assert_send(&entries_by_isbn(&client, "0735619670"));
+        assert_send(&entry_stubs_by_title(&client, "A title"));
+    }
+
+    #[test]
+    fn async_functions_request_the_same_urls_and_map_statuses() {
+        let client = StaticClient::new(200, "@manual{rfc7230, title={HTTP/1.1},}");
+        let biblio = ready(entries_by_rfc(&client, 7230)).unwrap().unwrap();
+        assert!(biblio.get("rfc7230").is_some());
+
+        let client = StaticClient::new(200, "");
+        let err = ready(entries_by_rfc(&client, 7230)).unwrap_err();
+        assert_eq!(ErrorKind::NoValue, err.kind());
+
+        let client = StaticClient::new(404, "Resource not found.");
+        let err = ready(entries_by_doi(&client, "10.1000/missing")).unwrap_err();
+        assert_eq!(ErrorKind::NotFound, err.kind());
+        // the agency lookup fails as well, so the DOI is looked up using the CrossRef API
+        assert_eq!(
+            vec![
+                doi::ra_url("10.1000/missing"),
+                cross_ref::doi_url("10.1000/missing", None)
+            ],
+            *client.urls.lock().unwrap()
+        );
+
+        let client = StaticClient::new(200, r#"{ "items": [] }"#);
+        let err = ready(entries_by_isbn(&client, "978-0380815937")).unwrap_err();
+        assert_eq!(ErrorKind::NoValue, err.kind());
+        assert_eq!(
+            vec!["https://www.googleapis.com/books/v1/volumes?q=isbn:9780380815937"],
+            *client.urls.lock().unwrap()
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use log::info;
+use serde::Deserialize;
+
+use crate::{
+    ast::{self, Biblio, BiblioResolver, Resolver},
+    Error, ErrorKind,
+};
+
+use reqwest::Url;
+
+use super::{Client, TitleQuery};
+
+const OPEN_LIBRARY_SEARCH_URL: &str = "https://openlibrary.org/search.json";
+
+/// The fields of each book that are requested from the search API.
+const BOOK_FIELDS: &str = "title,author_name,first_publish_year,publisher,isbn";
+
+/// Search for books that match the `query` and build an entry of each of the first `limit`
+/// books.
+///
+/// Books may be missing fields that are required for an entry, so the result may need to be
+/// resolved.
+pub(crate) fn search_books<C: Client>(
+    client: &C,
+    query: &TitleQuery,
+    limit: usize,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    info!("Searching for books matching '{query}' using Open Library API");
+    let mut params = vec![("q", query.title.clone())];
+    if let Some(author) = &query.author {
+        params.push(("author", author.clone()));
+    }
+    if let Some(venue) = &query.venue {
+        params.push(("publisher", venue.clone()));
+    }
+    params.push(("fields", BOOK_FIELDS.to_owned()));
+    params.push(("limit", limit.to_string()));
+    let url = Url::parse_with_params(OPEN_LIBRARY_SEARCH_URL, &params)
+        .map_err(|e| Error::wrap(ErrorKind::Deserialize, e))?;
+
+    let SearchModel { docs } = client.get_json(url.as_str())?;
+
+    let resolvers = docs
+        .into_iter()
+        .take(limit)
+        .map(Resolver::from)
+        .collect::<Vec<_>>();
+
+    if resolvers.is_empty() {
+        Err(Error::new(
+            ErrorKind::NoValue,
+            format!("No books found matching '{query}'"),
+        ))
+    } else {
+        Ok(Biblio::try_resolve(resolvers))
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchModel {
+    #[serde(default)]
+    docs: Vec<Doc>,
+}
+
+/// A book in the search results, where every field but the title may be missing.
+#[derive(Deserialize)]
+struct Doc {
+    title: String,
+    #[serde(default)]
+    author_name: Vec<String>,
+    first_publish_year: Option<u16>,
+    #[serde(default)]
+    publisher: Vec<String>,
+    #[serde(default)]
+    isbn: Vec<String>,
+}
+
+impl From<Doc> for Resolver {
+    fn from(doc: Doc) -> Self {
+        let mut resolver = ast::Entry::resolver(ast::EntryKind::Book);
+        resolver.title(doc.title);
+
+        if !doc.author_name.is_empty() {
+            resolver.author(doc.author_name.join(" and "));
+        }
+        if let Some(year) = doc.first_publish_year {
+            resolver.year(year.to_string());
+        }
+        if let Some(publisher) = doc.publisher.into_iter().next() {
+            resolver.publisher(publisher);
+        }
+        // prefer an ISBN-13 as the ISBN-10 is not issued anymore
+        let isbn = doc
+            .isbn
+            .iter()
+            .find(|isbn| isbn.len() == 13)
+            .or_else(|| doc.isbn.first());
+        if let Some(isbn) = isbn {
+            resolver.set_field("isbn", isbn.as_str());
+        }
+
+        resolver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{assert_url, impl_text_producer, MockClient, TitleQuery},
+        ErrorKind,
+    };
+
+    impl_text_producer! {
+        SearchJsonProducer => Ok(
+            r#"{
+                "numFound": 2,
+                "docs": [
+                    {
+                        "title": "Code Complete",
+                        "author_name": ["Steve McConnell"],
+                        "first_publish_year": 1993,
+                        "publisher": ["Microsoft Press"],
+                        "isbn": ["0735619670", "9780735619678"]
+                    },
+                    { "title": "Code Complete Companion" }
+                ]
+            }"#.to_owned()
+        ),
+        EmptyJsonProducer => Ok(r#"{ "numFound": 0, "docs": [] }"#.to_owned()),
+    }
+
+    #[test]
+    fn search_builds_books_with_the_fields_that_are_found() {
+        let query = TitleQuery::new("code complete").venue("Microsoft Press");
+        let resolver = super::search_books(&MockClient::<SearchJsonProducer>::default(), &query, 5)
+            .unwrap()
+            .expect_err("The second book is missing the required fields");
+        assert_url!("https://openlibrary.org/search.json?q=code+complete&publisher=Microsoft+Press&fields=title%2Cauthor_name%2Cfirst_publish_year%2Cpublisher%2Cisbn&limit=5");
+
+        let books = resolver.iter().collect::<Vec<_>>();
+        assert_eq!(2, books.len());
+        assert_eq!("9780735619678", &**books[0].get_field("isbn").unwrap());
+        assert_eq!("1993", &**books[0].get_field("year").unwrap());
+        assert!(books[1].get_field("author").is_none());
+    }
+
+    #[test]
+    fn no_books_returns_no_value_error() {
+        let err = super::search_books(
+            &MockClient::<EmptyJsonProducer>::default(),
+            &TitleQuery::new("?"),
+            5,
+        )
+        .unwrap_err();
+        assert_eq!(ErrorKind::NoValue, err.kind());
+    }
+}
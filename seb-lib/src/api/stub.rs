@@ -0,0 +1,201 @@
+//! Summaries of the entries found by a title search, used to pick the entry to look up.
+
+use std::fmt;
+
+/// The number of stubs in each page of a [`TitleQuery`] when none is given.
+pub const DEFAULT_ROWS: usize = 20;
+
+/// The summary of an entry found by a title search, which has enough information to tell apart
+/// entries with similar titles and the DOI to look up the entry with.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryStub {
+    /// The DOI of the entry.
+    pub doi: String,
+    /// The title of the entry, which is empty when the entry has no title.
+    pub title: String,
+    /// The names of the authors of the entry, in order.
+    pub authors: Vec<String>,
+    /// The year that the entry was published.
+    pub year: Option<u16>,
+    /// The journal, book or proceedings that contains the entry.
+    pub container: Option<String>,
+    /// The type of the entry given by the API, such as `journal-article`.
+    pub kind: Option<String>,
+}
+
+impl fmt::Display for EntryStub {
+    /// Formats the stub as `title - authors (year), container [kind]`, leaving out what is
+    /// missing and shortening more than two authors to the first author and "et al.".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.title)?;
+        match self.authors.as_slice() {
+            [] => {}
+            [first, second] => write!(f, " - {first} and {second}")?,
+            [first] => write!(f, " - {first}")?,
+            [first, ..] => write!(f, " - {first} et al.")?,
+        }
+        if let Some(year) = self.year {
+            write!(f, " ({year})")?;
+        }
+        if let Some(container) = &self.container {
+            write!(f, ", {container}")?;
+        }
+        if let Some(kind) = &self.kind {
+            write!(f, " [{kind}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// A search for entries by title, with optional filters and the page of results to return.
+///
+/// The same query is used by [`Registry::search`](crate::provider::Registry::search), where the
+/// providers may also find the words of the title in other fields of the entries and the page is
+/// ignored.
+///
+/// ```
+/// use seb::TitleQuery;
+///
+/// let query = TitleQuery::new("The Art of Computer Programming")
+///     .author("Knuth")
+///     .years(Some(1968), Some(1973));
+/// let next = query.next_page();
+/// assert_eq!(query.rows, next.offset);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TitleQuery {
+    /// The title, or part of the title, to search for.
+    pub title: String,
+    /// The name of an author of the entries.
+    pub author: Option<String>,
+    /// The journal, proceedings or publisher of the entries.
+    pub venue: Option<String>,
+    /// The first year that the entries may be published in.
+    pub from_year: Option<u16>,
+    /// The last year that the entries may be published in.
+    pub until_year: Option<u16>,
+    /// The type of the entries given by the API, such as `journal-article`.
+    pub kind: Option<String>,
+    /// The number of stubs in the page.
+    pub rows: usize,
+    /// The number of stubs before the page.
+    pub offset: usize,
+}
+
+impl TitleQuery {
+    /// Creates a query for the first page of entries with the `title`.
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        Self {
+            title: title.into(),
+            author: None,
+            venue: None,
+            from_year: None,
+            until_year: None,
+            kind: None,
+            rows: DEFAULT_ROWS,
+            offset: 0,
+        }
+    }
+
+    /// Only finds entries with an author matching the `author`.
+    #[must_use]
+    pub fn author<S: Into<String>>(mut self, author: S) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Only finds entries published in the `venue`.
+    #[must_use]
+    pub fn venue<S: Into<String>>(mut self, venue: S) -> Self {
+        self.venue = Some(venue.into());
+        self
+    }
+
+    /// Only finds entries published from the year `from` until the year `until`, where either
+    /// bound may be open.
+    #[must_use]
+    pub const fn years(mut self, from: Option<u16>, until: Option<u16>) -> Self {
+        self.from_year = from;
+        self.until_year = until;
+        self
+    }
+
+    /// Only finds entries of the `kind`, such as `journal-article`.
+    #[must_use]
+    pub fn kind<S: Into<String>>(mut self, kind: S) -> Self {
+        self.kind = Some(kind.into());
+        self
+    }
+
+    /// Sets the number of stubs in each page.
+    #[must_use]
+    pub const fn rows(mut self, rows: usize) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    /// The same query for the page after this one.
+    #[must_use]
+    pub fn next_page(&self) -> Self {
+        Self {
+            offset: self.offset + self.rows,
+            ..self.clone()
+        }
+    }
+
+    /// Returns `true` when the query has no title, author or venue to search for.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        [Some(&self.title), self.author.as_ref(), self.venue.as_ref()]
+            .into_iter()
+            .flatten()
+            .all(|text| text.trim().is_empty())
+    }
+
+    /// Returns `true` when the `year` is in the range of years of the query.
+    pub(crate) fn includes_year(&self, year: u16) -> bool {
+        self.from_year.is_none_or(|from| from <= year)
+            && self.until_year.is_none_or(|until| year <= until)
+    }
+}
+
+impl fmt::Display for TitleQuery {
+    /// Formats the query as `title by author in venue`, leaving out what is missing.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.title)?;
+        if let Some(author) = &self.author {
+            write!(f, " by {author}")?;
+        }
+        if let Some(venue) = &self.venue {
+            write!(f, " in {venue}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stubs_display_the_information_they_have() {
+        let mut stub = EntryStub {
+            doi: "10.1000/1".to_owned(),
+            title: "BibTeX".to_owned(),
+            ..EntryStub::default()
+        };
+        assert_eq!("BibTeX", stub.to_string());
+
+        stub.authors = vec!["Oren Patashnik".to_owned(), "Leslie Lamport".to_owned()];
+        stub.year = Some(1988);
+        stub.container = Some("TUGboat".to_owned());
+        stub.kind = Some("journal-article".to_owned());
+        assert_eq!(
+            "BibTeX - Oren Patashnik and Leslie Lamport (1988), TUGboat [journal-article]",
+            stub.to_string()
+        );
+
+        stub.authors.push("Donald Knuth".to_owned());
+        assert!(stub.to_string().contains(" - Oren Patashnik et al. (1988)"));
+    }
+}
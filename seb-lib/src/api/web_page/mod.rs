@@ -0,0 +1,805 @@
+//! Entries of web pages built from the bibliographic metadata embedded in the HTML.
+//!
+//! The metadata is read from, in order of preference, Highwire Press `citation_*` meta tags,
+//! JSON-LD, `COinS`, Dublin Core and `OpenGraph` meta tags, and lastly the title and author of the
+//! page itself. Each field is taken from the first source that has it.
+
+mod entities;
+
+use std::time::SystemTime;
+
+use log::{debug, info};
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::{
+    ast::{self, Biblio, BiblioResolver, Resolver},
+    dedupe::{doi_of, normalize_doi},
+    format::{page_range, parse_month},
+    Error, ErrorKind,
+};
+
+use super::{Client, Request};
+use entities::ENTITIES;
+
+/// The JSON-LD types of pages that describe a creative work.
+const JSON_LD_TYPES: [&str; 8] = [
+    "ScholarlyArticle",
+    "Article",
+    "NewsArticle",
+    "BlogPosting",
+    "TechArticle",
+    "Report",
+    "Book",
+    "WebPage",
+];
+
+/// Gets the page at the `url` and builds an entry from the metadata of the page, with the
+/// `urldate` of today.
+pub(crate) fn get_entry_by_page<C: Client>(
+    url: &str,
+) -> Result<Result<Biblio, BiblioResolver>, Error> {
+    let parsed = Url::parse(url).map_err(|e| {
+        Error::wrap_with(
+            ErrorKind::Deserialize,
+            e,
+            format!("'{url}' is not a valid url"),
+        )
+    })?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(Error::new(
+            ErrorKind::Deserialize,
+            format!("'{url}' is not the url of a web page"),
+        ));
+    }
+
+    info!("Reading the metadata of the web page '{url}'");
+    let client = C::default();
+    let request = Request::get(url).header("Accept", "text/html,application/xhtml+xml");
+    let html = client.send(&request)?.error_for_status(url)?.body;
+
+    let resolver = resolver_from_html(&html, url, &today())?;
+    Ok(Biblio::try_resolve(vec![resolver]))
+}
+
+/// Builds an `article` resolver from the metadata of a page of a journal, or else an `online`
+/// resolver, which may be missing required fields.
+pub(crate) fn resolver_from_html(html: &str, url: &str, urldate: &str) -> Result<Resolver, Error> {
+    let tags = Tag::parse_all(html);
+    let meta = tags
+        .iter()
+        .filter(|tag| tag.name == "meta")
+        .filter_map(|tag| {
+            let name = tag.attr("name").or_else(|| tag.attr("property"))?;
+            Some((name.to_ascii_lowercase(), tag.attr("content")?.trim()))
+        })
+        .filter(|(_, content)| !content.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut metadata = Metadata::highwire(&meta);
+    metadata.merge(Metadata::json_ld(html, &tags));
+    metadata.merge(Metadata::coins(&tags));
+    metadata.merge(Metadata::dublin_core(&meta));
+    metadata.merge(Metadata::open_graph(&meta));
+    metadata.merge(Metadata::page(html, &tags, &meta));
+
+    let Some(title) = metadata.title.take() else {
+        return Err(Error::new(
+            ErrorKind::NoValue,
+            format!("No title found in the metadata of '{url}'"),
+        ));
+    };
+    debug!("Found the metadata of '{title}'");
+    Ok(metadata.into_resolver(title, url, urldate))
+}
+
+/// The bibliographic metadata of a page, where each field is from the first source that has it.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Metadata {
+    title: Option<String>,
+    authors: Vec<String>,
+    date: Option<String>,
+    journal: Option<String>,
+    publisher: Option<String>,
+    site: Option<String>,
+    volume: Option<String>,
+    number: Option<String>,
+    first_page: Option<String>,
+    last_page: Option<String>,
+    doi: Option<String>,
+}
+
+impl Metadata {
+    /// Fills the fields that are missing with those of the `other` metadata.
+    fn merge(&mut self, other: Self) {
+        fn fill(field: &mut Option<String>, other: Option<String>) {
+            if field.is_none() {
+                *field = other;
+            }
+        }
+
+        fill(&mut self.title, other.title);
+        if self.authors.is_empty() {
+            self.authors = other.authors;
+        }
+        fill(&mut self.date, other.date);
+        fill(&mut self.journal, other.journal);
+        fill(&mut self.publisher, other.publisher);
+        fill(&mut self.site, other.site);
+        fill(&mut self.volume, other.volume);
+        fill(&mut self.number, other.number);
+        fill(&mut self.first_page, other.first_page);
+        fill(&mut self.last_page, other.last_page);
+        fill(&mut self.doi, other.doi);
+    }
+
+    /// The metadata of the Highwire Press `citation_*` meta tags used by Google Scholar.
+    fn highwire(meta: &[(String, &str)]) -> Self {
+        let first = |name: &str| first_content(meta, &[name]);
+        Self {
+            title: first("citation_title"),
+            authors: all_content(meta, "citation_author"),
+            date: first_content(
+                meta,
+                &[
+                    "citation_publication_date",
+                    "citation_date",
+                    "citation_online_date",
+                ],
+            ),
+            journal: first("citation_journal_title"),
+            publisher: first("citation_publisher"),
+            volume: first("citation_volume"),
+            number: first("citation_issue"),
+            first_page: first("citation_firstpage"),
+            last_page: first("citation_lastpage"),
+            doi: first("citation_doi").map(|doi| normalize_doi(&doi)),
+            ..Self::default()
+        }
+    }
+
+    /// The metadata of the Dublin Core `DC.*` or `DCTERMS.*` meta tags.
+    fn dublin_core(meta: &[(String, &str)]) -> Self {
+        let dc = meta
+            .iter()
+            .filter_map(|(name, content)| {
+                let name = name
+                    .strip_prefix("dc.")
+                    .or_else(|| name.strip_prefix("dcterms."))?;
+                Some((name.to_owned(), *content))
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            title: first_content(&dc, &["title"]),
+            authors: all_content(&dc, "creator"),
+            date: first_content(&dc, &["date", "issued", "created"]),
+            publisher: first_content(&dc, &["publisher"]),
+            doi: dc
+                .iter()
+                .filter(|(name, _)| name == "identifier")
+                .find_map(|(_, id)| doi_of(id)),
+            ..Self::default()
+        }
+    }
+
+    /// The metadata of the `OpenGraph` meta tags used for previews of links.
+    fn open_graph(meta: &[(String, &str)]) -> Self {
+        Self {
+            title: first_content(meta, &["og:title"]),
+            // the author of an article is often the url of a profile page
+            authors: all_content(meta, "article:author")
+                .into_iter()
+                .filter(|author| !author.starts_with("http"))
+                .collect(),
+            date: first_content(meta, &["article:published_time"]),
+            site: first_content(meta, &["og:site_name"]),
+            ..Self::default()
+        }
+    }
+
+    /// The metadata of the first JSON-LD object of a creative work.
+    fn json_ld(html: &str, tags: &[Tag<'_>]) -> Self {
+        let work = tags
+            .iter()
+            .filter(|tag| {
+                tag.name == "script"
+                    && tag
+                        .attr("type")
+                        .is_some_and(|kind| kind.eq_ignore_ascii_case("application/ld+json"))
+            })
+            .filter_map(|tag| serde_json::from_str::<Value>(tag.text(html, "script")).ok())
+            .find_map(|value| creative_work(&value).cloned());
+        let Some(work) = work else {
+            return Self::default();
+        };
+
+        let text = |value: Option<&Value>| match value? {
+            Value::String(text) => Some(decode_entities(text.trim())),
+            Value::Number(number) => Some(number.to_string()),
+            Value::Object(object) => object
+                .get("name")
+                .and_then(Value::as_str)
+                .map(|name| decode_entities(name.trim())),
+            _ => None,
+        };
+        let authors = match work.get("author") {
+            Some(Value::Array(authors)) => authors.iter().filter_map(|a| text(Some(a))).collect(),
+            author => text(author).into_iter().collect(),
+        };
+        // an article is part of an issue, which is part of a volume of the periodical
+        let parts = std::iter::successors(work.get("isPartOf"), |part| part.get("isPartOf"))
+            .take(3)
+            .collect::<Vec<_>>();
+        let part = |name: &str| parts.iter().find_map(|part| part.get(name));
+        let journal = parts
+            .iter()
+            .copied()
+            .find(|part| has_type(part, "Periodical"));
+        let doi = ["identifier", "sameAs", "@id"]
+            .iter()
+            .filter_map(|name| work.get(*name))
+            .flat_map(|value| match value {
+                Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            })
+            .filter_map(Value::as_str)
+            .find_map(doi_of);
+
+        Self {
+            title: text(work.get("headline")).or_else(|| text(work.get("name"))),
+            authors,
+            date: text(work.get("datePublished")).or_else(|| text(work.get("dateCreated"))),
+            journal: text(journal),
+            publisher: text(work.get("publisher")),
+            volume: text(part("volumeNumber")),
+            number: text(part("issueNumber")),
+            first_page: text(work.get("pageStart")),
+            last_page: text(work.get("pageEnd")),
+            doi,
+            ..Self::default()
+        }
+    }
+
+    /// The metadata of the first `COinS` span, whose title is an `OpenURL` context object.
+    fn coins(tags: &[Tag<'_>]) -> Self {
+        let Some(context) = tags
+            .iter()
+            .filter(|tag| {
+                tag.attr("class")
+                    .is_some_and(|class| class.split_whitespace().any(|c| c == "Z3988"))
+            })
+            .find_map(|tag| tag.attr("title"))
+        else {
+            return Self::default();
+        };
+        let context = decode_entities(context);
+        let Ok(url) = Url::parse(&format!("http://localhost/?{context}")) else {
+            return Self::default();
+        };
+        let pairs = url
+            .query_pairs()
+            .map(|(name, value)| (name.into_owned(), value.trim().to_owned()))
+            .filter(|(_, value)| !value.is_empty())
+            .collect::<Vec<_>>();
+        let first = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                pairs
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.clone())
+            })
+        };
+
+        let mut authors = pairs
+            .iter()
+            .filter(|(name, _)| name == "rft.au")
+            .map(|(_, author)| author.clone())
+            .collect::<Vec<_>>();
+        if authors.is_empty() {
+            if let Some(last) = first(&["rft.aulast"]) {
+                authors.push(match first(&["rft.aufirst"]) {
+                    Some(given) => format!("{given} {last}"),
+                    None => last,
+                });
+            }
+        }
+
+        Self {
+            title: first(&["rft.atitle", "rft.btitle", "rft.title"]),
+            authors,
+            date: first(&["rft.date"]),
+            journal: first(&["rft.jtitle"]),
+            publisher: first(&["rft.pub"]),
+            volume: first(&["rft.volume"]),
+            number: first(&["rft.issue"]),
+            first_page: first(&["rft.spage"]),
+            last_page: first(&["rft.epage"]),
+            doi: pairs
+                .iter()
+                .filter(|(name, _)| name == "rft_id")
+                .find_map(|(_, id)| doi_of(id)),
+            ..Self::default()
+        }
+    }
+
+    /// The metadata of the page itself, its `title` element and `author` meta tag.
+    fn page(html: &str, tags: &[Tag<'_>], meta: &[(String, &str)]) -> Self {
+        Self {
+            title: tags
+                .iter()
+                .find(|tag| tag.name == "title")
+                .map(|tag| decode_entities(tag.text(html, "title").trim()))
+                .filter(|title| !title.is_empty()),
+            authors: all_content(meta, "author"),
+            ..Self::default()
+        }
+    }
+
+    fn into_resolver(self, title: String, url: &str, urldate: &str) -> Resolver {
+        let mut resolver = match &self.journal {
+            Some(_) => ast::Entry::resolver(ast::EntryKind::Article),
+            None => ast::Entry::resolver(ast::EntryKind::Other("online".into())),
+        };
+        let is_article = self.journal.is_some();
+        resolver.set_field("title", title);
+
+        if !self.authors.is_empty() {
+            resolver.set_field("author", self.authors.join(" and "));
+        }
+        if let Some(date) = &self.date {
+            let (year, month) = year_and_month(date);
+            if let Some(year) = year {
+                resolver.set_field("year", year);
+            }
+            if let Some(month) = month {
+                resolver.set_field("month", month);
+            }
+        }
+        let pages = page_range(self.first_page, self.last_page);
+        // an online entry has no publisher, so the publisher or site is the organization
+        let (publisher, organization) = if is_article {
+            (self.publisher, None)
+        } else {
+            (None, self.site.or(self.publisher))
+        };
+
+        let fields = [
+            ("journal", self.journal),
+            ("volume", self.volume),
+            ("number", self.number),
+            ("pages", pages),
+            ("publisher", publisher),
+            ("organization", organization),
+            ("doi", self.doi),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                resolver.set_field(name, value);
+            }
+        }
+        resolver.set_field("url", url);
+        resolver.set_field("urldate", urldate);
+
+        resolver
+    }
+}
+
+/// The decoded content of the first meta tag with one of the `names`.
+fn first_content(meta: &[(String, &str)], names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| {
+        meta.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, content)| decode_entities(content))
+    })
+}
+
+/// The decoded content of every meta tag with the `name`, in order.
+fn all_content(meta: &[(String, &str)], name: &str) -> Vec<String> {
+    meta.iter()
+        .filter(|(n, _)| n == name)
+        .map(|(_, content)| decode_entities(content))
+        .collect()
+}
+
+/// Returns `true` when the `@type` of the JSON-LD `value` is, or includes, the `kind`.
+fn has_type(value: &Value, kind: &str) -> bool {
+    match value.get("@type") {
+        Some(Value::String(t)) => t == kind,
+        Some(Value::Array(types)) => types.iter().any(|t| t.as_str() == Some(kind)),
+        _ => false,
+    }
+}
+
+/// The JSON-LD object of a creative work in the `value`, preferring the most specific type of
+/// work when there is more than one.
+fn creative_work(value: &Value) -> Option<&Value> {
+    let mut nodes = Vec::new();
+    collect_nodes(value, &mut nodes);
+    JSON_LD_TYPES
+        .iter()
+        .find_map(|kind| nodes.iter().copied().find(|node| has_type(node, kind)))
+}
+
+/// Collects the objects in the `value`, including those in arrays and `@graph`s.
+fn collect_nodes<'v>(value: &'v Value, nodes: &mut Vec<&'v Value>) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                collect_nodes(value, nodes);
+            }
+        }
+        Value::Object(object) => {
+            nodes.push(value);
+            if let Some(graph) = object.get("@graph") {
+                collect_nodes(graph, nodes);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The year and two digit month of a date, such as `2019-05-03`, `2019/05` or `May 3, 2019`,
+/// where the month is only found when the date starts with the year.
+fn year_and_month(date: &str) -> (Option<String>, Option<String>) {
+    let numbers = date
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|n| !n.is_empty())
+        .collect::<Vec<_>>();
+    let year = numbers
+        .iter()
+        .find(|n| n.len() == 4)
+        .map(|y| (*y).to_owned());
+    let month = match numbers.as_slice() {
+        [y, m, ..] if y.len() == 4 && date.starts_with(y) => m
+            .parse::<u8>()
+            .ok()
+            .filter(|m| (1..=12).contains(m))
+            .map(|m| format!("{m:02}")),
+        _ => None,
+    };
+    (year, month)
+}
+
+/// Replaces the character references of HTML, such as `&amp;`, `&eacute;` and `&#39;`, with the
+/// characters, leaving any `&` that does not start a reference as it is.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        match decode_reference(rest, &mut decoded) {
+            Some(len) => rest = &rest[len..],
+            None => decoded.push('&'),
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Decodes the character reference at the start of the `text`, which follows a `&`, and returns
+/// the length of the reference.
+///
+/// A named reference is the longest name in [`ENTITIES`], so that the legacy references such as
+/// `&copy` are decoded without a `;`.
+fn decode_reference(text: &str, decoded: &mut String) -> Option<usize> {
+    if let Some(number) = text.strip_prefix('#') {
+        let (digits, radix) = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => (hex, 16),
+            None => (number, 10),
+        };
+        let len = digits
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(digits.len());
+        let code = u32::from_str_radix(&digits[..len], radix).ok()?;
+        decoded.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+
+        let end = text.len() - digits.len() + len;
+        return Some(if text[end..].starts_with(';') {
+            end + 1
+        } else {
+            end
+        });
+    }
+
+    let len = text
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(text.len());
+    let with_semicolon = text[len..].starts_with(';').then(|| &text[..=len]);
+    let (name, characters) = with_semicolon
+        .into_iter()
+        .chain((1..=len).rev().map(|len| &text[..len]))
+        .find_map(|name| {
+            ENTITIES
+                .binary_search_by(|(entity, _)| entity.cmp(&name))
+                .ok()
+                .map(|index| (name, ENTITIES[index].1))
+        })?;
+    decoded.push_str(characters);
+    Some(name.len())
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+fn today() -> String {
+    iso_date(SystemTime::now())
+}
+
+/// The date of the `time` in UTC as `YYYY-MM-DD`, from its HTTP date such as
+/// `Sun, 18 Oct 2026 15:12:00 GMT`.
+fn iso_date(time: SystemTime) -> String {
+    let date = httpdate::fmt_http_date(time);
+    let mut parts = date.split_whitespace().skip(1);
+    let day = parts.next().unwrap_or_default();
+    let month = parts.next().and_then(parse_month).unwrap_or_default();
+    let year = parts.next().unwrap_or_default();
+    format!("{year}-{month:02}-{day}")
+}
+
+/// A start tag of a HTML document, with the lowercase name and the attributes of the tag.
+#[derive(Debug)]
+struct Tag<'html> {
+    name: String,
+    attrs: Vec<(String, &'html str)>,
+    // The byte offset of the end of the tag in the document.
+    end: usize,
+}
+
+impl<'html> Tag<'html> {
+    /// The start tags of the `html`, skipping comments, end tags, declarations and the text of
+    /// scripts and styles.
+    ///
+    /// This is not a full HTML parser but it reads the tags of well-formed pages, which is all
+    /// that is needed to find the metadata in the head of a page.
+    fn parse_all(html: &'html str) -> Vec<Self> {
+        let mut tags = Vec::new();
+        let mut at = 0;
+        while let Some(start) = html[at..].find('<').map(|i| at + i) {
+            let rest = &html[start + 1..];
+            if rest.starts_with("!--") {
+                at = html[start..]
+                    .find("-->")
+                    .map_or(html.len(), |end| start + end + 3);
+            } else if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                let tag = Self::parse(html, start + 1);
+                at = tag.end;
+                // the text of these elements is not markup, such as a `<` in a script
+                if matches!(tag.name.as_str(), "script" | "style" | "textarea" | "title") {
+                    at += tag.text(html, &tag.name).len();
+                }
+                tags.push(tag);
+            } else {
+                at = start + 1;
+            }
+        }
+        tags
+    }
+
+    /// Parses the tag whose name starts at `at`.
+    fn parse(html: &'html str, mut at: usize) -> Self {
+        let bytes = html.as_bytes();
+        let is_name_end = |b: u8| b.is_ascii_whitespace() || matches!(b, b'>' | b'/' | b'=');
+        let name_end = |from: usize| {
+            (from..bytes.len())
+                .find(|&i| is_name_end(bytes[i]))
+                .unwrap_or(bytes.len())
+        };
+        let skip_whitespace = |from: usize| {
+            (from..bytes.len())
+                .find(|&i| !bytes[i].is_ascii_whitespace())
+                .unwrap_or(bytes.len())
+        };
+
+        let end = name_end(at);
+        let name = html[at..end].to_ascii_lowercase();
+        at = end;
+
+        let mut attrs = Vec::new();
+        loop {
+            at = skip_whitespace(at);
+            match bytes.get(at) {
+                None => break,
+                Some(b'>') => {
+                    at += 1;
+                    break;
+                }
+                Some(b'/' | b'=') => {
+                    at += 1;
+                    continue;
+                }
+                Some(_) => {}
+            }
+
+            let end = name_end(at);
+            let attr = html[at..end].to_ascii_lowercase();
+            at = skip_whitespace(end);
+            let mut value = "";
+            if bytes.get(at) == Some(&b'=') {
+                at = skip_whitespace(at + 1);
+                if let Some(&quote @ (b'"' | b'\'')) = bytes.get(at) {
+                    let end = (at + 1..bytes.len())
+                        .find(|&i| bytes[i] == quote)
+                        .unwrap_or(bytes.len());
+                    value = &html[at + 1..end];
+                    at = (end + 1).min(bytes.len());
+                } else {
+                    let end = (at..bytes.len())
+                        .find(|&i| bytes[i].is_ascii_whitespace() || bytes[i] == b'>')
+                        .unwrap_or(bytes.len());
+                    value = &html[at..end];
+                    at = end;
+                }
+            }
+            attrs.push((attr, value));
+        }
+
+        Self {
+            name,
+            attrs,
+            end: at,
+        }
+    }
+
+    /// The raw value of the attribute with the lowercase `name`.
+    fn attr(&self, name: &str) -> Option<&'html str> {
+        self.attrs
+            .iter()
+            .find(|(attr, _)| attr == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// The raw text after the tag until the end tag `</name>`.
+    fn text(&self, html: &'html str, name: &str) -> &'html str {
+        let rest = &html[self.end..];
+        let closing = format!("</{name}");
+        let end = rest
+            .as_bytes()
+            .windows(closing.len())
+            .position(|window| window.eq_ignore_ascii_case(closing.as_bytes()))
+            .unwrap_or(rest.len());
+        &rest[..end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::FieldQuery;
+
+    const URLDATE: &str = "2026-10-18";
+
+    fn fields(entry: &dyn FieldQuery, names: &[&str]) -> Vec<Option<String>> {
+        names
+            .iter()
+            .map(|name| entry.get_field(name).map(|value| value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn highwire_tags_build_an_article() {
+        let html = r#"<!DOCTYPE html>
+            <html><head>
+            <title>Ownership types | Journal</title>
+            <!-- <meta name="citation_title" content="Commented out"> -->
+            <meta name="citation_title" content="Ownership Types &amp; Borrowing">
+            <meta name="citation_author" content="Clarke, David">
+            <meta name=citation_author content="Noble, James">
+            <META NAME="citation_publication_date" CONTENT="1998/10/01">
+            <meta name="citation_journal_title" content="ACM SIGPLAN Notices">
+            <meta name="citation_volume" content="33"><meta name="citation_issue" content="10">
+            <meta name="citation_firstpage" content="48"><meta name="citation_lastpage" content="64"/>
+            <meta name="citation_doi" content="10.1145/286942.286947">
+            <meta property="og:site_name" content="ACM Digital Library">
+            </head><body></body></html>"#;
+
+        let resolver = resolver_from_html(html, "https://dl.acm.org/1", URLDATE).unwrap();
+        let article = resolver
+            .resolve()
+            .expect("All the fields of an article are found");
+
+        assert_eq!(ast::EntryKind::Article, article.kind());
+        assert_eq!(
+            vec![
+                Some("Ownership Types & Borrowing".to_owned()),
+                Some("Clarke, David and Noble, James".to_owned()),
+                Some("1998".to_owned()),
+                Some("10".to_owned()),
+                Some("48--64".to_owned()),
+                Some("10.1145/286942.286947".to_owned()),
+                Some(URLDATE.to_owned()),
+            ],
+            fields(
+                &article,
+                &["title", "author", "year", "month", "pages", "doi", "urldate"]
+            )
+        );
+    }
+
+    #[test]
+    fn json_ld_coins_and_open_graph_fill_the_missing_fields() {
+        let html = r#"<html><head>
+            <meta property="og:title" content="A post">
+            <meta property="og:site_name" content="The Blog">
+            <meta property="article:author" content="https://example.org/authors/jane">
+            <script type="application/ld+json">
+            {"@context": "https://schema.org", "@graph": [
+                {"@type": "WebSite", "name": "The Blog"},
+                {"@type": "BlogPosting", "headline": "Fearless concurrency",
+                 "author": [{"@type": "Person", "name": "Jane Doe"}, "John Roe"],
+                 "datePublished": "2021-03-04T10:00:00Z"}
+            ]}
+            </script>
+            </head><body>
+            <span class="Z3988" title="ctx_ver=Z39.88-2004&amp;rft.btitle=Ignored&amp;rft_id=info%3Adoi%2F10.1000%2FBLOG"></span>
+            </body></html>"#;
+
+        let resolver = resolver_from_html(html, "https://blog.example.org/1", URLDATE).unwrap();
+        assert_eq!(
+            vec![
+                Some("Fearless concurrency".to_owned()),
+                Some("Jane Doe and John Roe".to_owned()),
+                Some("2021".to_owned()),
+                Some("03".to_owned()),
+                Some("The Blog".to_owned()),
+                Some("10.1000/blog".to_owned()),
+                Some("https://blog.example.org/1".to_owned()),
+            ],
+            fields(
+                &resolver,
+                &[
+                    "title",
+                    "author",
+                    "year",
+                    "month",
+                    "organization",
+                    "doi",
+                    "url"
+                ]
+            )
+        );
+        let entry = resolver
+            .resolve()
+            .expect("An online entry only needs a title");
+        assert_eq!(ast::EntryKind::Other("online".into()), entry.kind());
+
+        let err = resolver_from_html("<p>No metadata</p>", "https://example.org", URLDATE);
+        assert_eq!(ErrorKind::NoValue, err.unwrap_err().kind());
+    }
+
+    #[test]
+    fn text_of_scripts_is_not_read_as_tags() {
+        let html = r#"<script>if (a <b) { html = '<meta name="citation_title" content="Fake">'; }</script>
+            <style>p::before { content: "<meta>"; }</style>
+            <meta name="citation_title" content="Real">"#;
+        let tags = Tag::parse_all(html);
+
+        let names = tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(vec!["script", "style", "meta"], names);
+        assert_eq!(Some("Real"), tags[2].attr("content"));
+    }
+
+    #[test]
+    fn dates_and_entities_are_decoded() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let date = |days: u64| iso_date(UNIX_EPOCH + Duration::from_secs(days * 86_400));
+        assert_eq!("1970-01-01", date(0));
+        assert_eq!("2000-02-29", date(11_016));
+        assert_eq!("2026-10-18", date(20_744));
+
+        assert_eq!(
+            (Some("2019".to_owned()), None),
+            year_and_month("May 3, 2019")
+        );
+        assert_eq!(
+            "Gödel's <proof> & more &unknown; &",
+            decode_entities("G&#246;del&#x27;s &lt;proof&gt; &amp; more &unknown; &")
+        );
+        assert_eq!(
+            "Café — “Ωmega” … © 2020 ≂̸",
+            decode_entities(
+                "Caf&eacute; &mdash; &ldquo;&Omega;mega&rdquo; &hellip; &copy 2020 &NotEqualTilde;"
+            )
+        );
+    }
+}
@@ -0,0 +1,455 @@
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+};
+
+use super::Biblio;
+use crate::{
+    ast::{Entry, FieldQuery},
+    dedupe::{normalize_doi, normalize_isbn},
+};
+
+/// How to resolve an incoming entry that has the same cite key as an existing entry but
+/// different content.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictPolicy {
+    /// Keep the existing entry and skip the incoming entry.
+    KeepOurs,
+    /// Replace the existing entry with the incoming entry.
+    KeepTheirs,
+    /// Keep both entries by adding the incoming entry with a new cite key.
+    Rename,
+}
+
+/// A summary of the changes made by [`Biblio::merge`].
+#[derive(Debug, Default, PartialEq)]
+pub struct MergeReport {
+    /// Cite keys of the incoming entries that were added.
+    pub added: Vec<String>,
+    /// Cite keys of the incoming entries that were skipped as they are already in the
+    /// bibliography, either as the same entry or as an entry with the same DOI or ISBN.
+    pub skipped: Vec<String>,
+    /// Cite keys of the incoming entries that conflicted with an existing entry and the policy
+    /// used to resolve each conflict.
+    pub conflicts: Vec<(String, ConflictPolicy)>,
+    /// The old and new cite keys of the incoming entries that were renamed due to a conflict.
+    pub renamed: Vec<(String, String)>,
+    /// Cite keys of the existing entries that were removed as the incoming bibliography removed
+    /// them, which only [`Biblio::merge_changes`] does.
+    pub removed: Vec<String>,
+}
+
+impl Biblio {
+    /// Merges the entries of another [`Biblio`] into this one.
+    ///
+    /// Incoming entries with the same DOI or ISBN as an existing entry are skipped, as are
+    /// entries that are identical to an existing entry. An incoming entry with the same cite key
+    /// as an existing entry, ignoring case as `BibTeX` does, but with different content is
+    /// resolved using the `policy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Biblio, ConflictPolicy, Entry, EntryKind};
+    ///
+    /// let entry = |title: &str| {
+    ///     let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+    ///     resolver.title(title);
+    ///     resolver.resolve().unwrap()
+    /// };
+    ///
+    /// let mut ours = Biblio::new(vec![entry("Ours")]);
+    /// let theirs = Biblio::new(vec![entry("Theirs")]);
+    ///
+    /// let report = ours.merge(theirs, ConflictPolicy::Rename);
+    ///
+    /// assert_eq!(vec![("cite".to_owned(), "cite-2".to_owned())], report.renamed);
+    /// assert!(ours.get("cite-2").is_some());
+    /// ```
+    pub fn merge(&mut self, other: Self, policy: ConflictPolicy) -> MergeReport {
+        self.merge_with(other, |_, _| policy)
+    }
+
+    /// Merges the entries of another [`Biblio`] into this one, calling `resolve` with the existing
+    /// and incoming entry to choose how each conflict is resolved.
+    ///
+    /// See [`Biblio::merge`] for which entries are skipped and which are conflicts.
+    pub fn merge_with<R>(&mut self, other: Self, mut resolve: R) -> MergeReport
+    where
+        R: FnMut(&Entry, &Entry) -> ConflictPolicy,
+    {
+        self.try_merge_with(other, |ours, theirs| {
+            Ok::<_, Infallible>(resolve(ours, theirs))
+        })
+        .unwrap_or_else(|never| match never {})
+    }
+
+    /// Merges the entries of another [`Biblio`] into this one in the same way as
+    /// [`Biblio::merge_with`], where `resolve` may fail to choose how a conflict is resolved.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of `resolve`, in which case this [`Biblio`] is left unchanged.
+    pub fn try_merge_with<R, E>(&mut self, other: Self, mut resolve: R) -> Result<MergeReport, E>
+    where
+        R: FnMut(&Entry, &Entry) -> Result<ConflictPolicy, E>,
+    {
+        let mut report = MergeReport::default();
+        let mut ids = self.entries().flat_map(identifiers).collect::<HashSet<_>>();
+        // the changes made so far, which are undone when `resolve` fails
+        let dirty = self.dirty;
+        let mut inserted = Vec::new();
+        let mut replaced = Vec::<Entry>::new();
+
+        let mut incoming = other.into_entries();
+        incoming.sort_by(|a, b| a.cite().cmp(b.cite()));
+
+        for mut entry in incoming {
+            let cite = entry.cite().to_owned();
+            let entry_ids = identifiers(&entry);
+
+            if entry_ids.iter().any(|id| ids.contains(id)) {
+                report.skipped.push(cite);
+                continue;
+            }
+
+            if let Some(existing) = self.get_ignore_case(&cite) {
+                if existing == &entry {
+                    report.skipped.push(cite);
+                    continue;
+                }
+
+                let policy = resolve(existing, &entry);
+                let existing_cite = existing.cite().to_owned();
+                let policy = match policy {
+                    Ok(policy) => policy,
+                    Err(e) => {
+                        for cite in inserted {
+                            self.entries.remove(&cite);
+                        }
+                        for entry in replaced {
+                            self.entries.insert(entry.cite().to_owned(), entry);
+                        }
+                        self.dirty = dirty;
+                        return Err(e);
+                    }
+                };
+                report.conflicts.push((cite.clone(), policy));
+                match policy {
+                    ConflictPolicy::KeepOurs => continue,
+                    ConflictPolicy::KeepTheirs => {
+                        // the cite key of the existing entry may differ in case
+                        replaced.extend(self.take(&existing_cite));
+                    }
+                    ConflictPolicy::Rename => {
+                        let new_cite = self.free_cite(&cite);
+                        entry.set_cite(new_cite.clone());
+                        report.renamed.push((cite, new_cite));
+                    }
+                }
+            } else {
+                report.added.push(cite);
+            }
+
+            ids.extend(entry_ids);
+            inserted.push(entry.cite().to_owned());
+            self.insert(entry);
+        }
+
+        Ok(report)
+    }
+
+    /// Merges the changes made to another [`Biblio`] into this one, where both bibliographies
+    /// were the `base` bibliography before they were changed.
+    ///
+    /// This is a three-way merge of the entries by cite key. An entry that only changed in the
+    /// other bibliography is added, replaced or removed as it was there, and an entry that only
+    /// changed in this bibliography is kept. An entry that changed differently in both is a
+    /// conflict that is resolved by calling `resolve` with the existing and incoming entry, except
+    /// when one side removed the entry and the other changed it, where the changed entry is kept
+    /// so that no changes are lost.
+    ///
+    /// In the [`MergeReport`] the cite keys of the incoming entries that were added or replaced an
+    /// existing entry are `added`, using the new cite key of renamed entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Biblio, ConflictPolicy, Entry, EntryKind};
+    ///
+    /// let entry = |cite: &str, title: &str| {
+    ///     let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, cite);
+    ///     resolver.title(title);
+    ///     resolver.resolve().unwrap()
+    /// };
+    ///
+    /// let base = Biblio::new(vec![entry("a", "A"), entry("b", "B")]);
+    /// let mut ours = Biblio::new(vec![entry("a", "Ours"), entry("b", "B")]);
+    /// let theirs = Biblio::new(vec![entry("a", "A"), entry("c", "C")]);
+    ///
+    /// let report = ours.merge_changes(base, theirs, |_, _| ConflictPolicy::KeepOurs);
+    ///
+    /// assert_eq!(vec!["c"], report.added);
+    /// assert_eq!(vec!["b"], report.removed);
+    /// assert!(ours.get("a").is_some() && ours.get("b").is_none());
+    /// ```
+    pub fn merge_changes<R>(&mut self, base: Self, other: Self, mut resolve: R) -> MergeReport
+    where
+        R: FnMut(&Entry, &Entry) -> ConflictPolicy,
+    {
+        let by_cite = |biblio: Self| {
+            biblio
+                .into_entries()
+                .into_iter()
+                .map(|e| (e.cite().to_owned(), e))
+                .collect::<HashMap<_, _>>()
+        };
+        let mut base = by_cite(base);
+        let mut theirs = by_cite(other);
+
+        let mut cites = base
+            .keys()
+            .chain(theirs.keys())
+            .chain(self.entries.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        cites.sort();
+        cites.dedup();
+
+        let mut report = MergeReport::default();
+        for cite in cites {
+            let base = base.remove(&cite);
+            let theirs = theirs.remove(&cite);
+            let ours = self.entries.get(&cite);
+
+            // either both made the same change or only this bibliography changed the entry
+            if ours == theirs.as_ref() || theirs == base {
+                continue;
+            }
+
+            // only the other bibliography changed the entry
+            if ours == base.as_ref() {
+                if let Some(entry) = theirs {
+                    self.insert(entry);
+                    report.added.push(cite);
+                } else {
+                    self.dirty = true;
+                    self.entries.remove(&cite);
+                    report.removed.push(cite);
+                }
+                continue;
+            }
+
+            match (ours, theirs) {
+                (Some(ours), Some(mut entry)) => {
+                    let policy = resolve(ours, &entry);
+                    report.conflicts.push((cite.clone(), policy));
+                    match policy {
+                        ConflictPolicy::KeepOurs => continue,
+                        ConflictPolicy::KeepTheirs => report.added.push(cite),
+                        ConflictPolicy::Rename => {
+                            let new_cite = self.free_cite(&cite);
+                            entry.set_cite(new_cite.clone());
+                            report.added.push(new_cite.clone());
+                            report.renamed.push((cite, new_cite));
+                        }
+                    }
+                    self.insert(entry);
+                }
+                // this bibliography removed the entry that the other changed
+                (None, Some(entry)) => {
+                    self.insert(entry);
+                    report
+                        .conflicts
+                        .push((cite.clone(), ConflictPolicy::KeepTheirs));
+                    report.added.push(cite);
+                }
+                // the other bibliography removed the entry that this one changed
+                (Some(_), None) => report.conflicts.push((cite, ConflictPolicy::KeepOurs)),
+                (None, None) => unreachable!("both sides are the same when neither has the entry"),
+            }
+        }
+
+        report
+    }
+
+    /// The entry with the cite key, which is matched case-insensitively.
+    fn get_ignore_case(&self, cite: &str) -> Option<&Entry> {
+        self.get(cite).or_else(|| {
+            self.entries()
+                .find(|entry| entry.cite().eq_ignore_ascii_case(cite))
+        })
+    }
+
+    /// Finds an unused cite key by adding a numbered suffix to the `cite`.
+    fn free_cite(&self, cite: &str) -> String {
+        let mut n = 2;
+        loop {
+            let candidate = format!("{cite}-{n}");
+            if self.get_ignore_case(&candidate).is_none() {
+                return candidate;
+            }
+            n += 1;
+        }
+    }
+}
+
+/// The normalized DOI and ISBN of an entry, prefixed so that they can share a set.
+fn identifiers(entry: &Entry) -> Vec<String> {
+    let doi = entry
+        .get_field("doi")
+        .map(|doi| format!("doi:{}", normalize_doi(doi)));
+    let isbn = entry
+        .get_field("isbn")
+        .and_then(|isbn| normalize_isbn(isbn))
+        .map(|isbn| format!("isbn:{isbn}"));
+
+    doi.into_iter().chain(isbn).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::manual_entry;
+
+    fn biblio() -> Biblio {
+        Biblio::new(vec![
+            manual_entry("a", &[("doi", "10.1/a")]),
+            manual_entry("b", &[("isbn", "0-7356-1967-0")]),
+            manual_entry("c", &[("note", "ours")]),
+        ])
+    }
+
+    fn incoming() -> Biblio {
+        Biblio::new(vec![
+            manual_entry("x", &[("doi", "https://doi.org/10.1/A")]),
+            manual_entry("y", &[("isbn", "9780735619678")]),
+            manual_entry("c", &[("note", "theirs")]),
+            manual_entry("a", &[("doi", "10.1/a")]),
+            manual_entry("d", &[]),
+        ])
+    }
+
+    #[test]
+    fn merge_skips_duplicates_and_keeps_ours() {
+        let mut biblio = biblio();
+        let report = biblio.merge(incoming(), ConflictPolicy::KeepOurs);
+
+        assert_eq!(vec!["d"], report.added);
+        assert_eq!(vec!["a", "x", "y"], report.skipped);
+        assert_eq!(
+            vec![("c".to_owned(), ConflictPolicy::KeepOurs)],
+            report.conflicts
+        );
+        assert_eq!(
+            Some("ours"),
+            biblio
+                .get("c")
+                .and_then(|e| e.get_field("note"))
+                .map(|v| &**v)
+        );
+    }
+
+    #[test]
+    fn merge_changes_of_both_sides() {
+        let base = Biblio::new(vec![
+            manual_entry("kept", &[]),
+            manual_entry("ours", &[]),
+            manual_entry("theirs", &[]),
+            manual_entry("removed", &[]),
+            manual_entry("both", &[]),
+            manual_entry("edited-removed", &[]),
+        ]);
+        let mut ours = Biblio::new(vec![
+            manual_entry("kept", &[]),
+            manual_entry("ours", &[("note", "ours")]),
+            manual_entry("theirs", &[]),
+            manual_entry("removed", &[]),
+            manual_entry("both", &[("note", "ours")]),
+            manual_entry("edited-removed", &[("note", "ours")]),
+            manual_entry("new-ours", &[]),
+        ]);
+        let theirs = Biblio::new(vec![
+            manual_entry("kept", &[]),
+            manual_entry("ours", &[]),
+            manual_entry("theirs", &[("note", "theirs")]),
+            manual_entry("both", &[("note", "theirs")]),
+            manual_entry("new-theirs", &[]),
+        ]);
+
+        let report = ours.merge_changes(base, theirs, |_, _| ConflictPolicy::Rename);
+        let note = |cite: &str| {
+            ours.get(cite)
+                .and_then(|e| e.get_field("note"))
+                .map(|v| v.to_string())
+        };
+
+        assert_eq!(vec!["both-2", "new-theirs", "theirs"], report.added);
+        assert_eq!(vec!["removed"], report.removed);
+        assert_eq!(
+            vec![
+                ("both".to_owned(), ConflictPolicy::Rename),
+                ("edited-removed".to_owned(), ConflictPolicy::KeepOurs)
+            ],
+            report.conflicts
+        );
+        assert_eq!(Some("ours".to_owned()), note("ours"));
+        assert_eq!(Some("theirs".to_owned()), note("theirs"));
+        assert_eq!(Some("ours".to_owned()), note("both"));
+        assert_eq!(Some("theirs".to_owned()), note("both-2"));
+        assert_eq!(Some("ours".to_owned()), note("edited-removed"));
+        assert!(ours.get("new-ours").is_some() && ours.get("new-theirs").is_some());
+        assert!(ours.get("removed").is_none());
+    }
+
+    #[test]
+    fn merge_keeps_theirs_or_renames() {
+        let mut theirs = biblio();
+        theirs.merge(incoming(), ConflictPolicy::KeepTheirs);
+        assert_eq!(
+            Some("theirs"),
+            theirs
+                .get("c")
+                .and_then(|e| e.get_field("note"))
+                .map(|v| &**v)
+        );
+
+        let mut renamed = biblio();
+        let report = renamed.merge(incoming(), ConflictPolicy::Rename);
+        assert_eq!(vec![("c".to_owned(), "c-2".to_owned())], report.renamed);
+        assert_eq!(
+            Some("theirs"),
+            renamed
+                .get("c-2")
+                .and_then(|e| e.get_field("note"))
+                .map(|v| &**v)
+        );
+    }
+
+    #[test]
+    fn cite_keys_that_differ_in_case_conflict() {
+        let mut biblio = Biblio::new(vec![manual_entry("Knuth84", &[("note", "ours")])]);
+        let incoming = Biblio::new(vec![manual_entry("knuth84", &[("note", "theirs")])]);
+
+        let report = biblio.merge(incoming, ConflictPolicy::KeepTheirs);
+
+        assert_eq!(
+            vec![("knuth84".to_owned(), ConflictPolicy::KeepTheirs)],
+            report.conflicts
+        );
+        assert_eq!(1, biblio.entries().count());
+        assert!(biblio.get("knuth84").is_some());
+    }
+
+    #[test]
+    fn failed_resolve_leaves_the_biblio_unchanged() {
+        let mut biblio = biblio();
+        biblio.dirty();
+
+        let result = biblio.try_merge_with(incoming(), |_, _| Err("cancelled"));
+
+        assert_eq!(Err("cancelled"), result);
+        assert_eq!(self::biblio(), biblio);
+        assert!(!biblio.dirty());
+    }
+}
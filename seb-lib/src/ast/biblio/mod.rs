@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 
+mod merge;
 mod resolver;
 
+pub use merge::{ConflictPolicy, MergeReport};
 pub use resolver::BiblioResolver;
 
 use super::{Entry, FieldQuery, QuotedString, Resolver};
 
 /// An intermediate representation of a bibliography which is not tied to a specific end format.
+///
+/// With the `serde` feature a [`Biblio`] is serialized as an array of the [`Entry`]s ordered by
+/// cite key, the `dirty` flag is not serialized.
 #[derive(Debug, Default, PartialEq)]
 #[cfg_attr(test, derive(Clone))]
 pub struct Biblio {
@@ -48,7 +53,7 @@ impl Biblio {
     /// The `dirty` flag will return true when this instance has been edited since it was created.
     /// The default value of the `dirty` flag is `false`, therefore calling this function will
     /// always reset the `dirty` flag to `false`.
-    pub fn dirty(&mut self) -> bool {
+    pub const fn dirty(&mut self) -> bool {
         let dirty = self.dirty;
         self.dirty = false;
         dirty
@@ -73,16 +78,36 @@ impl Biblio {
         removed
     }
 
+    /// Removes the entry with exactly the cite key and returns it, unlike [`Biblio::remove`] which
+    /// removes every entry with the cite key ignoring case.
+    pub fn take(&mut self, cite: &str) -> Option<Entry> {
+        let entry = self.entries.remove(cite);
+        self.dirty |= entry.is_some();
+        entry
+    }
+
     /// Return a reference to a slice of entries.
     pub fn entries(&self) -> impl Iterator<Item = &Entry> {
         self.entries.values()
     }
 
+    /// Calls the closure with each entry, which returns `true` when it modified the entry.
+    ///
+    /// The [`Biblio`] is only marked as dirty when an entry was modified.
+    pub fn modify_entries<F>(&mut self, mut modify: F)
+    where
+        F: FnMut(&mut Entry) -> bool,
+    {
+        for entry in self.entries.values_mut() {
+            self.dirty |= modify(entry);
+        }
+    }
+
     /// Creates entries from a value.
     #[must_use]
     #[allow(clippy::missing_const_for_fn)] // drop is not const
     pub fn into_entries(self) -> Vec<Entry> {
-        self.entries.into_iter().map(|(_, v)| v).collect()
+        self.entries.into_values().collect()
     }
 
     /// Tests if any field in this [`Biblio`] matches a predicate.
@@ -105,7 +130,7 @@ impl Biblio {
     {
         self.entries
             .values()
-            .any(|e| e.get_field(key).map(&predicate).unwrap_or_default())
+            .any(|e| e.get_field(key).is_some_and(&predicate))
     }
 
     /// Returns the reference to the `Entry` corresponding to the cite key value.
@@ -115,6 +140,22 @@ impl Biblio {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Biblio {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entries = self.entries.values().collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.cite().cmp(b.cite()));
+        serializer.collect_seq(entries)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Biblio {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self::new)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -161,6 +202,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dirty_flag_is_only_set_when_entries_are_modified() {
+        let mut biblio = Biblio::new(vec![crate::ast::manual_entry("cite", &[])]);
+        biblio.dirty();
+
+        biblio.modify_entries(|_| false);
+        assert!(!biblio.dirty(), "No entry was modified");
+
+        biblio.modify_entries(|entry| {
+            entry.set_field("note", "a note");
+            true
+        });
+        assert!(biblio.dirty(), "An entry was modified");
+    }
+
     #[test]
     fn remove_entry_in_single_biblio() {
         let mut biblio = Biblio::new(vec![Entry::Manual(Manual {
@@ -177,6 +233,44 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        // the custom kind is kept verbatim
+        let mut resolver = crate::ast::Other::resolver_with_cite("Dataset".to_owned(), "data");
+        resolver.title(QuotedString::from_quoted("The {ABC} data", ['{', '}']));
+        resolver.set_field("url", "https://example.com");
+        let other = resolver.resolve().unwrap();
+
+        let biblio = Biblio::new(vec![
+            Entry::Manual(Manual {
+                cite: "manual".to_owned(),
+                title: "Title".into(),
+                optional: HashMap::default(),
+            }),
+            other,
+        ]);
+
+        let json = serde_json::to_value(&biblio).unwrap();
+        assert_eq!(
+            serde_json::json!([
+                {
+                    "cite": "data",
+                    "kind": "Dataset",
+                    "fields": {
+                        "title": { "value": "The ABC data", "markers": [4, 7] },
+                        "url": "https://example.com"
+                    }
+                },
+                { "cite": "manual", "kind": "manual", "fields": { "title": "Title" } }
+            ]),
+            json
+        );
+
+        let res: Biblio = serde_json::from_value(json).unwrap();
+        assert_eq!(biblio, res);
+    }
+
     #[test]
     fn false_on_duplicate_field() {
         let square_quote = |c: char| matches!(c, '{' | '}');
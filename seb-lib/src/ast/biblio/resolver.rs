@@ -1,3 +1,6 @@
+#[cfg(feature = "file")]
+use std::borrow::Cow;
+
 use crate::ast::{Biblio, Entry, FieldQuery, Resolver};
 
 /// A [`Biblio`] resolver used for managing a set of entry resolvers until they all succeed in order
@@ -45,6 +48,59 @@ impl BiblioResolver {
         self.resolvers.iter_mut()
     }
 
+    /// Returns the resolvers that failed to resolve, which can be used to report the missing
+    /// fields of each entry.
+    pub fn resolvers(&self) -> impl Iterator<Item = &Resolver> {
+        self.resolvers.iter()
+    }
+
+    /// Returns the cite keys of both the resolved entries and the resolvers.
+    #[cfg(feature = "file")]
+    pub(crate) fn cites(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.entries
+            .iter()
+            .map(|entry| Cow::Borrowed(entry.cite()))
+            .chain(self.resolvers.iter().map(Resolver::cite))
+    }
+
+    /// Combines the `results`, which is a [`Biblio`] when every result is resolved.
+    ///
+    /// Returns `None` when there are no results.
+    pub(crate) fn combine<I>(results: I) -> Option<Result<Biblio, Self>>
+    where
+        I: IntoIterator<Item = Result<Biblio, Self>>,
+    {
+        results
+            .into_iter()
+            .reduce(|combined, result| match (combined, result) {
+                (Ok(biblio), Ok(other)) => {
+                    let mut entries = biblio.into_entries();
+                    entries.extend(other.into_entries());
+                    Ok(Biblio::new(entries))
+                }
+                (Ok(biblio), Err(mut resolver)) => {
+                    resolver.extend(Ok(biblio));
+                    Err(resolver)
+                }
+                (Err(mut resolver), result) => {
+                    resolver.extend(result);
+                    Err(resolver)
+                }
+            })
+    }
+
+    /// Adds the entries of a [`Biblio`] or the entries and resolvers of another
+    /// [`BiblioResolver`].
+    pub(crate) fn extend(&mut self, other: Result<Biblio, Self>) {
+        match other {
+            Ok(biblio) => self.entries.extend(biblio.into_entries()),
+            Err(other) => {
+                self.entries.extend(other.entries);
+                self.resolvers.extend(other.resolvers);
+            }
+        }
+    }
+
     /// Removes either the entry or resolver based on the index.
     ///
     /// The [`BiblioResolver`] can contain both resolvd entries or resolvers and does so in this
@@ -82,10 +138,12 @@ where
     let mut left = B::default();
     let mut right = R::default();
 
-    iter.fold((), |_, res| match res {
-        Err(r) => right.extend([r]),
-        l => left.extend(l),
-    });
+    for res in iter {
+        match res {
+            Err(r) => right.extend([r]),
+            l => left.extend(l),
+        }
+    }
 
     (left, right)
 }
@@ -93,7 +151,7 @@ where
 impl std::fmt::Display for BiblioResolver {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for unresolved in &self.resolvers {
-            writeln!(f, "{}", unresolved)?;
+            writeln!(f, "{unresolved}")?;
         }
 
         write!(
@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+
+use crate::ast::{QuotedString, Resolver};
+
+use super::Entry;
+
+/// A single field level change between two versions of an [`Entry`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldChange {
+    /// The field is only present in the new version of the entry.
+    Added {
+        /// Name of the field.
+        name: String,
+        /// Value of the field in the new version of the entry.
+        value: QuotedString,
+    },
+    /// The field is only present in the old version of the entry.
+    Removed {
+        /// Name of the field.
+        name: String,
+        /// Value of the field in the old version of the entry.
+        value: QuotedString,
+    },
+    /// The field is present in both versions of the entry but the values are different.
+    Modified {
+        /// Name of the field.
+        name: String,
+        /// Value of the field in the old version of the entry.
+        old: QuotedString,
+        /// Value of the field in the new version of the entry.
+        new: QuotedString,
+    },
+}
+
+impl FieldChange {
+    /// Returns the name of the field that has changed.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Added { name, .. } | Self::Removed { name, .. } | Self::Modified { name, .. } => {
+                name
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added { name, value } => write!(f, "+ {name}: {}", &**value),
+            Self::Removed { name, value } => write!(f, "- {name}: {}", &**value),
+            Self::Modified { name, old, new } => {
+                write!(f, "~ {name}: {} -> {}", &**old, &**new)
+            }
+        }
+    }
+}
+
+/// The field level difference between two versions of an [`Entry`].
+///
+/// Field names are compared case-insensitively and values are compared by their textual content
+/// so that a difference in quoting alone is not considered a change.
+///
+/// # Examples
+///
+/// ```
+/// use seb::ast::{Entry, EntryKind, FieldChange};
+///
+/// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+/// resolver.title("Old title");
+/// let old = resolver.resolve().unwrap();
+///
+/// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+/// resolver.title("New title");
+/// resolver.set_field("version", "2");
+/// let new = resolver.resolve().unwrap();
+///
+/// let diff = old.diff(&new);
+/// let names = diff.changes().iter().map(FieldChange::name).collect::<Vec<_>>();
+///
+/// assert_eq!(vec!["title", "version"], names);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EntryDiff {
+    changes: Vec<FieldChange>,
+}
+
+impl EntryDiff {
+    /// Creates the field level difference of going from the `old` entry to the `new` entry.
+    ///
+    /// The changes are ordered by the field name.
+    #[must_use]
+    pub fn new(old: &Entry, new: &Entry) -> Self {
+        let normalize = |entry: &Entry| {
+            entry
+                .fields()
+                .into_iter()
+                .map(|field| (field.name.to_lowercase(), field.value.into_owned()))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let mut old = normalize(old);
+        let new = normalize(new);
+
+        let mut changes = Vec::new();
+
+        for (name, new) in new {
+            match old.remove(&name) {
+                None => changes.push(FieldChange::Added { name, value: new }),
+                Some(old) if *old != *new => changes.push(FieldChange::Modified { name, old, new }),
+                Some(_) => {}
+            }
+        }
+
+        changes.extend(
+            old.into_iter()
+                .map(|(name, value)| FieldChange::Removed { name, value }),
+        );
+        changes.sort_by(|a, b| a.name().cmp(b.name()));
+
+        Self { changes }
+    }
+
+    /// Returns `true` when there are no changes between the two entries.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the changes between the two entries.
+    #[must_use]
+    pub fn changes(&self) -> &[FieldChange] {
+        &self.changes
+    }
+
+    /// Returns the changes between the two entries by value.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)] // drop is not const
+    pub fn into_changes(self) -> Vec<FieldChange> {
+        self.changes
+    }
+
+    /// Retains only the changes specified by the predicate.
+    pub fn retain<P>(&mut self, predicate: P)
+    where
+        P: FnMut(&FieldChange) -> bool,
+    {
+        self.changes.retain(predicate);
+    }
+
+    /// Applies the changes to a [`Resolver`].
+    ///
+    /// Added and modified fields are set on the resolver and removed fields are removed from it,
+    /// see [`Resolver::remove_field`] for how this effects the required fields.
+    pub fn apply(&self, resolver: &mut Resolver) {
+        for change in &self.changes {
+            match change {
+                FieldChange::Added { name, value }
+                | FieldChange::Modified {
+                    name, new: value, ..
+                } => {
+                    resolver.set_field(name, value.clone());
+                }
+                FieldChange::Removed { name, .. } => {
+                    resolver.remove_field(name);
+                }
+            }
+        }
+    }
+}
+
+impl FromIterator<FieldChange> for EntryDiff {
+    fn from_iter<T: IntoIterator<Item = FieldChange>>(iter: T) -> Self {
+        Self {
+            changes: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for EntryDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Entry {
+    /// Returns the field level difference of going from this entry to the `other` entry.
+    ///
+    /// This is equivalent to calling [`EntryDiff::new`] with this entry as the old entry.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> EntryDiff {
+        EntryDiff::new(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::ast::{manual_entry, Book, Entry, EntryKind, FieldQuery};
+
+    use super::*;
+
+    #[test]
+    fn identical_entries_have_empty_diff() {
+        let entry = manual_entry("cite", &[("doi", "10.1000/1")]);
+
+        assert!(entry.diff(&entry.clone()).is_empty());
+    }
+
+    #[test]
+    fn diff_contains_added_removed_and_modified_fields() {
+        let old = manual_entry(
+            "cite",
+            &[("doi", "10.1000/1"), ("note", "mine"), ("pages", "1-2")],
+        );
+        let new = manual_entry(
+            "cite",
+            &[("doi", "10.1000/1"), ("pages", "1-10"), ("volume", "3")],
+        );
+
+        let diff = old.diff(&new);
+
+        assert_eq!(
+            &[
+                FieldChange::Removed {
+                    name: "note".to_owned(),
+                    value: "mine".into()
+                },
+                FieldChange::Modified {
+                    name: "pages".to_owned(),
+                    old: "1-2".into(),
+                    new: "1-10".into()
+                },
+                FieldChange::Added {
+                    name: "volume".to_owned(),
+                    value: "3".into()
+                },
+            ][..],
+            diff.changes()
+        );
+    }
+
+    #[test]
+    fn quoting_and_field_name_case_are_not_changes() {
+        let old = manual_entry("cite", &[("DOI", "10.1000/1")]);
+        let mut new = manual_entry("cite", &[("doi", "10.1000/1")]);
+        if let Entry::Manual(manual) = &mut new {
+            manual.title = QuotedString::quote("cite".to_owned());
+        }
+
+        assert!(old.diff(&new).is_empty());
+    }
+
+    #[test]
+    fn applying_diff_to_resolver_of_old_entry_builds_new_entry() {
+        let old = Entry::Book(Book {
+            cite: "cite".to_owned(),
+            author: "Me".into(),
+            title: "Title".into(),
+            publisher: "Publisher".into(),
+            year: "2020".into(),
+            optional: HashMap::from([("note".to_owned(), "remove me".into())]),
+        });
+        let new = Entry::Book(Book {
+            cite: "cite".to_owned(),
+            author: "Me".into(),
+            title: "Title".into(),
+            publisher: "Publisher".into(),
+            year: "2021".into(),
+            optional: HashMap::from([("edition".to_owned(), "2".into())]),
+        });
+
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Book, "cite");
+        resolver.set_fields_from_entry(&old);
+        old.diff(&new).apply(&mut resolver);
+
+        let entry = resolver.resolve().expect("Required fields are unchanged");
+
+        assert_eq!(new, entry);
+        assert_eq!(None, entry.get_field("note"));
+    }
+}
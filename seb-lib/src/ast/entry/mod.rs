@@ -2,8 +2,10 @@ use std::{borrow::Cow, collections::HashMap};
 
 use super::{Field, QuotedString};
 
+mod diff;
 mod resolver;
 
+pub use diff::*;
 pub use resolver::*;
 
 /// Trait for querying data structures with fields.
@@ -27,6 +29,23 @@ macro_rules! entry_impl {
     ),* $(,)?) => {
         /// An intermediate representation of a bibliography entry which is not tied to a specific end
         /// format.
+        ///
+        /// # Serialization
+        ///
+        /// With the `serde` feature an [`Entry`] is serialized as an object with the `cite` key,
+        /// the `kind` as it is serialized for an [`EntryKind`] and the `fields` as an object of
+        /// the field names to the [`QuotedString`] values, ordered by name:
+        ///
+        /// ```json
+        /// {
+        ///   "cite": "knuth1984",
+        ///   "kind": "book",
+        ///   "fields": { "author": "Donald E. Knuth", "title": "The TeXbook", "...": "..." }
+        /// }
+        /// ```
+        ///
+        /// An entry is only deserialized when the fields contain the required fields of the
+        /// kind.
         #[derive(Debug, PartialEq)]
         #[cfg_attr(test, derive(Clone))]
         pub enum Entry {
@@ -39,6 +58,10 @@ macro_rules! entry_impl {
         }
 
         /// Types of bibliographic entries
+        ///
+        /// With the `serde` feature an [`EntryKind`] is serialized as a string of the names used
+        /// by the `From<&str>` implementation, such as `book chapter`, and a custom kind is
+        /// serialized verbatim.
         #[derive(Debug, Clone, PartialEq)]
         pub enum EntryKind<'entry> {
             $(
@@ -59,6 +82,26 @@ macro_rules! entry_impl {
                     Self::Other(_) => &["title"],
                 }
             }
+
+            /// The name of the entry kind used when serializing, such as `book chapter`, or the
+            /// custom kind of [`EntryKind::Other`] verbatim.
+            #[cfg(feature = "serde")]
+            fn name(&self) -> &str {
+                match self {
+                    $(Self::$target => $display,)*
+                    Self::Other(kind) => kind,
+                }
+            }
+
+            /// The entry kind of a serialized name, unlike [`From<&str>`] the name of a custom
+            /// kind is kept verbatim rather than lowercased.
+            #[cfg(feature = "serde")]
+            fn from_name(name: String) -> EntryKind<'static> {
+                match name.as_str() {
+                    $($display => EntryKind::$target,)*
+                    _ => EntryKind::Other(name.into()),
+                }
+            }
         }
 
         impl From<&str> for EntryKind<'static> {
@@ -135,6 +178,22 @@ macro_rules! entry_impl {
                 }
             }
 
+            /// Sets the value of a field and returns the previous value of the field if it was
+            /// set.
+            ///
+            /// The `name` of the field is transformed into lowercase before setting the field,
+            /// required fields of the entry are replaced and any other field is set as an
+            /// optional field.
+            pub fn set_field<I>(&mut self, name: &str, value: I) -> Option<QuotedString>
+            where
+                I: Into<QuotedString>,
+            {
+                match self {
+                    $(Self::$target(data) => data.set_field(name, value.into()),)*
+                    Self::Other(data) => data.set_field(name, value.into()),
+                }
+            }
+
             /// Creates a new [`Resolver`] for this type to ensure that the required fields
             /// are set before the entry type can be built.
             ///
@@ -214,6 +273,14 @@ macro_rules! entry_impl {
                         fields
                     }
 
+                    pub(super) fn set_field(&mut self, name: &str, value: QuotedString) -> Option<QuotedString> {
+                        let normal_name = name.to_lowercase();
+                        match normal_name.as_str() {
+                            $(stringify!($req) => Some(std::mem::replace(&mut self.$req, value)),)+
+                            _ => self.optional.insert(normal_name, value),
+                        }
+                    }
+
                     /// Creates a new [`Resolver`] for this type to ensure that the required fields
                     /// are set before the entry type can be built.
                     ///
@@ -313,6 +380,74 @@ macro_rules! entry_impl {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for EntryKind<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EntryKind<'static> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(EntryKind::from_name)
+    }
+}
+
+/// The fields of an entry or resolver ordered by name, so that they are serialized in a stable
+/// order.
+#[cfg(feature = "serde")]
+fn sorted_fields<'a>(
+    fields: impl IntoIterator<Item = (&'a str, &'a QuotedString)>,
+) -> std::collections::BTreeMap<&'a str, &'a QuotedString> {
+    fields.into_iter().collect()
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Entry {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let fields = self.fields();
+        let mut state = serializer.serialize_struct("Entry", 3)?;
+        state.serialize_field("cite", self.cite())?;
+        state.serialize_field("kind", &self.kind())?;
+        state.serialize_field(
+            "fields",
+            &sorted_fields(fields.iter().map(|field| (&*field.name, &*field.value))),
+        )?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Entry {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Repr {
+            cite: String,
+            kind: EntryKind<'static>,
+            fields: HashMap<String, QuotedString>,
+        }
+
+        let Repr { cite, kind, fields } = Repr::deserialize(deserializer)?;
+        let mut resolver = Entry::resolver_with_cite(kind, cite);
+        for (name, value) in fields {
+            // the names are kept verbatim in the same way as `Resolver::set_fields_from_entry`
+            resolver.set_normalized_field(name, value);
+        }
+
+        resolver.resolve().map_err(|resolver| {
+            serde::de::Error::custom(format!(
+                "the {} entry '{}' is missing the required fields: {}",
+                resolver.kind().name(),
+                resolver.cite(),
+                resolver.required_fields().collect::<Vec<_>>().join(", ")
+            ))
+        })
+    }
+}
+
 /// Any other resource not supported by other entry variants.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Other {
@@ -353,6 +488,15 @@ impl Other {
         }
     }
 
+    fn set_field(&mut self, name: &str, value: QuotedString) -> Option<QuotedString> {
+        let normal_name = name.to_lowercase();
+        if normal_name == "title" {
+            Some(std::mem::replace(&mut self.title, value))
+        } else {
+            self.optional.insert(normal_name, value)
+        }
+    }
+
     fn resolve(mut resolver: Resolver) -> Entry {
         Entry::Other(Other {
             cite: resolver.cite().to_string(),
@@ -491,7 +635,7 @@ entry_impl! {
             year
         ),
     "phd thesis" => phd_thesis:
-        /// A thesis for a PhD level degree.
+        /// A thesis for a `PhD` level degree.
         PhdThesis(
             /// Authors of the thesis.
             author,
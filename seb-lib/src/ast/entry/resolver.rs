@@ -28,8 +28,14 @@ use super::{Entry, EntryKind};
 /// assert_eq!("My manual", &**entry.title());
 /// ```
 ///
+/// # Serialization
+///
+/// With the `serde` feature a [`Resolver`] is serialized as an object with the `cite` key, or
+/// `null` when the cite key is generated, the `kind` and `fields` as they are serialized for an
+/// [`Entry`] and the `required` fields that are still to be set.
 #[derive(Debug)]
 #[cfg_attr(test, derive(Clone, PartialEq))]
+#[cfg_attr(test, allow(unpredictable_function_pointer_comparisons))]
 pub struct Resolver {
     pub(super) target: EntryKind<'static>,
     pub(super) cite: Option<String>,
@@ -95,6 +101,7 @@ impl Resolver {
     /// # Errors
     /// Returns `Err(Self)` when the required fields have not been set to make a valid [`Entry`],
     /// returning `Self` allows for the user to retry.
+    #[allow(clippy::result_large_err)] // Self is returned so the resolve can be retried
     pub fn resolve(self) -> Result<Entry, Self> {
         if self.req.is_empty() {
             Ok((self.entry_resolve)(self))
@@ -123,7 +130,7 @@ impl Resolver {
         self.req.iter().map(std::ops::Deref::deref)
     }
 
-    fn entry<'a>(&'a mut self, name: Cow<'static, str>) -> ResolverEntry<'a> {
+    const fn entry(&mut self, name: Cow<'static, str>) -> ResolverEntry<'_> {
         ResolverEntry {
             key: Some(name),
             resolver: self,
@@ -183,11 +190,52 @@ impl Resolver {
     ///
     /// Checks whether this field is a required field and will remove that name from the required
     /// set.
-    fn set_normalized_field(&mut self, name: String, value: QuotedString) {
+    pub(super) fn set_normalized_field(&mut self, name: String, value: QuotedString) {
         self.req.retain(|r| *r != name.as_str());
         self.fields.insert(name, value);
     }
 
+    /// Removes a field value by field name and returns the value if the field was set.
+    ///
+    /// The `name` of the field is matched case-insensitively. When the field removed is one of the
+    /// required fields of the entry kind then it becomes a required field again and will need to
+    /// be set before the resolver will succeed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::ast::{Entry, EntryKind};
+    ///
+    /// let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+    /// resolver.title("My manual");
+    /// assert_eq!(None, resolver.required_fields().next());
+    ///
+    /// assert_eq!(Some("My manual"), resolver.remove_field("TITLE").as_deref());
+    /// assert_eq!(Some("title"), resolver.required_fields().next());
+    /// ```
+    pub fn remove_field(&mut self, name: &str) -> Option<QuotedString> {
+        let key = self
+            .fields
+            .keys()
+            .find(|key| key.eq_ignore_ascii_case(name))?
+            .clone();
+        let value = self.fields.remove(&key);
+
+        let normal_name = key.to_lowercase();
+        if let Some(required) = self
+            .target
+            .required_fields()
+            .iter()
+            .find(|required| **required == normal_name)
+        {
+            if !self.req.iter().any(|req| req == required) {
+                self.req.push(Cow::Borrowed(required));
+            }
+        }
+
+        value
+    }
+
     /// Add additional required fields in order for this instance to be resolved.
     ///
     /// The fields array is checked against the existing required fields and any duplicates
@@ -240,6 +288,54 @@ impl std::fmt::Display for Resolver {
 
 impl std::error::Error for Resolver {}
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Resolver {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Resolver", 4)?;
+        state.serialize_field("cite", &self.cite)?;
+        state.serialize_field("kind", &self.target)?;
+        state.serialize_field("required", &self.req)?;
+        state.serialize_field(
+            "fields",
+            &super::sorted_fields(self.fields.iter().map(|(k, v)| (k.as_str(), v))),
+        )?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Resolver {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Repr {
+            cite: Option<String>,
+            kind: EntryKind<'static>,
+            #[serde(default)]
+            required: Vec<String>,
+            #[serde(default)]
+            fields: HashMap<String, QuotedString>,
+        }
+
+        let Repr {
+            cite,
+            kind,
+            required,
+            fields,
+        } = Repr::deserialize(deserializer)?;
+        let mut resolver = match cite {
+            Some(cite) => Entry::resolver_with_cite(kind, cite),
+            None => Entry::resolver(kind),
+        };
+        for (name, value) in fields {
+            resolver.set_normalized_field(name, value);
+        }
+        resolver.add_required_fields(required);
+        Ok(resolver)
+    }
+}
+
 /// A view into a single required field for a [`Resolver`].
 ///
 /// This entry takes ownership of the required field and if an insert method is not performed on
@@ -266,7 +362,7 @@ impl Drop for ResolverEntry<'_> {
     }
 }
 
-impl<'a> ResolverEntry<'a> {
+impl ResolverEntry<'_> {
     /// Sets the value of the entry.
     #[allow(clippy::missing_panics_doc)] // see key field comment
     pub fn insert(mut self, default: QuotedString) {
@@ -447,4 +543,35 @@ mod tests {
         // We confirm this because using `set_field` normalizes the field name to lowercase.
         assert!(resolver.get_field("tEsT").is_some());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_of_resolver_and_entry_kinds() {
+        use crate::ast::{EntryKind, Resolver};
+
+        let mut resolver = Entry::resolver(EntryKind::BookChapter);
+        resolver.set_field("title", "Title");
+        resolver.add_required_fields(vec!["isbn".to_owned()]);
+
+        let json = serde_json::to_value(&resolver).unwrap();
+        assert_eq!(serde_json::Value::Null, json["cite"]);
+        assert_eq!("book chapter", json["kind"]);
+        assert_eq!("Title", json["fields"]["title"]);
+
+        let res: Resolver = serde_json::from_value(json).unwrap();
+        assert_eq!(resolver.kind(), res.kind());
+        assert_eq!(resolver.get_field("title"), res.get_field("title"));
+        let mut required = res.required_fields().collect::<Vec<_>>();
+        required.sort_unstable();
+        assert_eq!(
+            vec!["author", "chapter", "isbn", "publisher", "year"],
+            required
+        );
+
+        let missing = serde_json::from_str::<Entry>(
+            r#"{"cite": "b", "kind": "book", "fields": {"title": "T"}}"#,
+        )
+        .expect_err("The book is missing required fields");
+        assert!(missing.to_string().contains("author, publisher, year"));
+    }
 }
@@ -10,7 +10,10 @@ pub use entry::*;
 pub use quoted_string::{EscapePattern, QuotedString};
 
 /// An entry field which is essentially a key value pair.
+///
+/// With the `serde` feature a [`Field`] is serialized as an object with the `name` and `value`.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Field<'entry> {
     /// Name of the entry field.
     pub name: Cow<'entry, str>,
@@ -43,3 +46,24 @@ impl<'entry> From<(&'entry str, &'entry QuotedString)> for Field<'entry> {
         }
     }
 }
+
+/// Creates a `manual` entry with the `fields` for tests, the title of the entry is the `cite` key
+/// unless the `fields` have a title.
+///
+/// This is only available with the `test-utils` feature, so that the tests of other crates can
+/// share it.
+///
+/// # Panics
+///
+/// Never panics, as the title is the only required field of a `manual` entry.
+#[cfg(any(test, feature = "test-utils"))]
+#[doc(hidden)]
+#[must_use]
+pub fn manual_entry(cite: &str, fields: &[(&str, &str)]) -> Entry {
+    let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, cite);
+    resolver.title(cite);
+    for (name, value) in fields {
+        resolver.set_field(name, *value);
+    }
+    resolver.resolve().expect("Title is set")
+}
@@ -25,6 +25,16 @@ use std::ops::Deref;
 /// let quoted = QuotedString::quote("foo".to_owned());
 /// assert_eq!("FOO", quoted.map_quoted(str::to_uppercase));
 /// ```
+///
+/// # Serialization
+///
+/// With the `serde` feature a [`QuotedString`] without quoted substrings is serialized as a
+/// string and otherwise as an object with the `value` and the byte offsets of the `markers`,
+/// where each pair of markers is the start and end of a quoted substring:
+///
+/// ```json
+/// { "value": "foo bar baz", "markers": [8, 11] }
+/// ```
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct QuotedString {
     markers: Vec<usize>,
@@ -229,6 +239,49 @@ impl From<String> for QuotedString {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for QuotedString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        if self.markers.is_empty() {
+            return serializer.serialize_str(&self.value);
+        }
+
+        let mut state = serializer.serialize_struct("QuotedString", 2)?;
+        state.serialize_field("value", &self.value)?;
+        state.serialize_field("markers", &self.markers)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for QuotedString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Plain(String),
+            Quoted { value: String, markers: Vec<usize> },
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Plain(value) => Ok(Self::new(value)),
+            Repr::Quoted { value, markers } => {
+                // the markers are used to slice the value so must be in order and on char
+                // boundaries
+                let ordered = markers.windows(2).all(|pair| pair[0] <= pair[1]);
+                if !ordered || !markers.iter().all(|&i| value.is_char_boundary(i)) {
+                    return Err(serde::de::Error::custom(format!(
+                        "the markers {markers:?} are not ordered offsets of the value '{value}'"
+                    )));
+                }
+                Ok(Self { markers, value })
+            }
+        }
+    }
+}
+
 /// A char escape pattern.
 ///
 /// A [`EscapePattern`] expresses that the implementing type can be used as a escape pattern for
@@ -329,6 +382,26 @@ mod tests {
         assert_eq!("HELLO, world", res);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_keeps_markers() {
+        let plain = QuotedString::new("plain".to_owned());
+        let json = serde_json::to_string(&plain).unwrap();
+        assert_eq!(r#""plain""#, json);
+        assert_eq!(plain, serde_json::from_str(&json).unwrap());
+
+        let quoted = QuotedString::from_quoted("foo bar $baz$", '$');
+        let json = serde_json::to_string(&quoted).unwrap();
+        assert_eq!(r#"{"value":"foo bar baz","markers":[8,11]}"#, json);
+
+        let res: QuotedString = serde_json::from_str(&json).unwrap();
+        assert_eq!(quoted, res);
+        assert_eq!("foo bar BAZ", res.map_quoted(str::to_uppercase));
+
+        assert!(serde_json::from_str::<QuotedString>(r#"{"value":"ab","markers":[2,1]}"#).is_err());
+        assert!(serde_json::from_str::<QuotedString>(r#"{"value":"ab","markers":[3]}"#).is_err());
+    }
+
     #[test]
     fn quoted_part_in_parts_from_parts_check() {
         let string = QuotedString::from_parts(vec![
@@ -0,0 +1,490 @@
+//! Detection of duplicate entries in a [`Biblio`].
+//!
+//! Two entries are considered duplicates when either:
+//! - they have the same DOI or ISBN once normalized, so `https://doi.org/10.1000/ABC` is the same
+//!   DOI as `10.1000/abc` and an ISBN-10 is the same as the equivalent ISBN-13.
+//! - they have a similar title and, when both entries have them, the same year and overlapping
+//!   authors.
+//!
+//! Duplicates are grouped into [`Cluster`]s so that more than two entries can be duplicates of
+//! each other.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::{
+    ast::{Biblio, Entry, FieldQuery},
+    search::{normalize, tokenize},
+};
+
+/// The minimum title similarity, between `0.0` and `1.0`, for two entries to be duplicates.
+const TITLE_THRESHOLD: f64 = 0.85;
+
+/// The minimum ratio of shared authors for two entries to be duplicates.
+const AUTHOR_THRESHOLD: f64 = 0.5;
+
+/// Normalizes a DOI so that DOIs can be compared.
+///
+/// DOIs are case-insensitive and are often written as a URL or with a `doi:` prefix.
+///
+/// # Examples
+///
+/// ```
+/// use seb::dedupe::normalize_doi;
+///
+/// assert_eq!("10.1000/abc", normalize_doi(" https://doi.org/10.1000/ABC"));
+/// assert_eq!("10.1000/abc", normalize_doi("doi:10.1000/abc"));
+/// ```
+#[must_use]
+pub fn normalize_doi(doi: &str) -> String {
+    let doi = doi.trim().to_lowercase();
+    let prefixes = [
+        "https://doi.org/",
+        "http://doi.org/",
+        "https://dx.doi.org/",
+        "http://dx.doi.org/",
+        "doi:",
+    ];
+
+    prefixes
+        .iter()
+        .find_map(|prefix| doi.strip_prefix(prefix))
+        .unwrap_or(&doi)
+        .trim()
+        .to_owned()
+}
+
+/// The normalized DOI of an identifier, such as `doi:10.1000/1`, `info:doi/10.1000/1` or a
+/// `doi.org` url, or `None` when the identifier is not a DOI.
+pub(crate) fn doi_of(identifier: &str) -> Option<String> {
+    let identifier = identifier.trim();
+    let doi = normalize_doi(identifier.strip_prefix("info:doi/").unwrap_or(identifier));
+    doi.starts_with("10.").then_some(doi)
+}
+
+/// Normalizes an ISBN to the ISBN-13 digits so that ISBNs can be compared.
+///
+/// Returns `None` when the value is not a valid length for an ISBN-10 or ISBN-13.
+///
+/// # Examples
+///
+/// ```
+/// use seb::dedupe::normalize_isbn;
+///
+/// assert_eq!(Some("9780735619678".to_owned()), normalize_isbn("0-7356-1967-0"));
+/// assert_eq!(Some("9780735619678".to_owned()), normalize_isbn("978-0-7356-1967-8"));
+/// assert_eq!(None, normalize_isbn("123"));
+/// ```
+#[must_use]
+pub fn normalize_isbn(isbn: &str) -> Option<String> {
+    let isbn = isbn
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, 'x' | 'X'))
+        .collect::<String>();
+
+    match isbn.len() {
+        13 => Some(isbn),
+        10 => {
+            // drop the ISBN-10 check digit and calculate the ISBN-13 check digit
+            let digits = format!("978{}", &isbn[..9]);
+            let sum: u32 = digits
+                .chars()
+                .filter_map(|c| c.to_digit(10))
+                .enumerate()
+                .map(|(i, d)| if i % 2 == 0 { d } else { d * 3 })
+                .sum();
+            Some(format!("{digits}{}", (10 - sum % 10) % 10))
+        }
+        _ => None,
+    }
+}
+
+/// The similarity of two titles between `0.0` and `1.0`, where `1.0` is the same title.
+///
+/// The similarity is the Sørensen–Dice coefficient of the character bigrams of the normalized
+/// titles, so case, diacritics and punctuation are ignored.
+#[must_use]
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    dice(&bigrams(a), &bigrams(b))
+}
+
+fn bigrams(title: &str) -> HashSet<(char, char)> {
+    let title = tokenize(title).join(" ");
+    let chars = title.chars().collect::<Vec<_>>();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+#[allow(clippy::cast_precision_loss)] // bigram counts are far below the precision limit
+fn dice(a: &HashSet<(char, char)>, b: &HashSet<(char, char)>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let shared = a.intersection(b).count();
+    (2 * shared) as f64 / (a.len() + b.len()) as f64
+}
+
+/// Returns the normalized family names of the authors in an `author` field.
+///
+/// Authors are separated by ` and ` and each author is either in the form `Family, Given` or
+/// `Given Family`. Only the last word of the family name is kept so that a compound family name
+/// such as `Peyton Jones` matches whichever way the author is written.
+#[must_use]
+pub fn author_family_names(authors: &str) -> BTreeSet<String> {
+    normalize(authors)
+        .split(" and ")
+        .filter_map(|author| {
+            let family = author.split_once(',').map_or(author, |(family, _)| family);
+            family.split_whitespace().last().map(str::to_owned)
+        })
+        .collect()
+}
+
+/// The reason that the entries of a [`Cluster`] are duplicates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchReason {
+    /// The entries have the same DOI.
+    Doi,
+    /// The entries have the same ISBN.
+    Isbn,
+    /// The entries have similar titles, years and authors.
+    Similar,
+}
+
+impl std::fmt::Display for MatchReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Doi => f.write_str("same doi"),
+            Self::Isbn => f.write_str("same isbn"),
+            Self::Similar => f.write_str("similar title, year and authors"),
+        }
+    }
+}
+
+/// A group of entries that are duplicates of each other.
+#[derive(Debug, PartialEq)]
+pub struct Cluster<'biblio> {
+    entries: Vec<&'biblio Entry>,
+    reason: MatchReason,
+}
+
+impl<'biblio> Cluster<'biblio> {
+    /// The duplicate entries ordered by cite key.
+    #[must_use]
+    pub fn entries(&self) -> &[&'biblio Entry] {
+        &self.entries
+    }
+
+    /// The strongest reason that entries in this cluster are duplicates.
+    #[must_use]
+    pub const fn reason(&self) -> MatchReason {
+        self.reason
+    }
+
+    /// The cite keys of the duplicate entries.
+    pub fn cites(&self) -> impl Iterator<Item = &'biblio str> + '_ {
+        self.entries.iter().map(|entry| entry.cite())
+    }
+}
+
+/// The normalized values of an entry used to find duplicates.
+struct Features {
+    doi: Option<String>,
+    isbn: Option<String>,
+    bigrams: HashSet<(char, char)>,
+    year: Option<String>,
+    authors: BTreeSet<String>,
+}
+
+impl Features {
+    fn new(entry: &Entry) -> Self {
+        Self {
+            doi: entry.get_field("doi").map(|doi| normalize_doi(doi)),
+            isbn: entry
+                .get_field("isbn")
+                .and_then(|isbn| normalize_isbn(isbn)),
+            bigrams: bigrams(entry.title()),
+            year: entry.get_field("year").map(|year| year.trim().to_owned()),
+            authors: entry
+                .get_field("author")
+                .map(|authors| author_family_names(authors))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn is_similar(&self, other: &Self) -> bool {
+        if let (Some(a), Some(b)) = (&self.year, &other.year) {
+            if a != b {
+                return false;
+            }
+        }
+
+        if !self.authors.is_empty() && !other.authors.is_empty() {
+            let shared = self.authors.intersection(&other.authors).count();
+            let fewest = self.authors.len().min(other.authors.len());
+            #[allow(clippy::cast_precision_loss)] // author counts are small
+            if (shared as f64 / fewest as f64) < AUTHOR_THRESHOLD {
+                return false;
+            }
+        }
+
+        dice(&self.bigrams, &other.bigrams) >= TITLE_THRESHOLD
+    }
+}
+
+/// A minimal union-find used to group pairs of duplicates into clusters.
+struct Groups {
+    parents: Vec<usize>,
+    reasons: Vec<Option<MatchReason>>,
+}
+
+impl Groups {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            reasons: vec![None; len],
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize, reason: MatchReason) {
+        let a = self.find(a);
+        let b = self.find(b);
+        let reason = match (self.reasons[a], self.reasons[b]) {
+            // keep the strongest reason, which is the first variant of MatchReason
+            (Some(r), _) | (_, Some(r)) if (r as u8) < (reason as u8) => r,
+            _ => reason,
+        };
+        self.parents[b] = a;
+        self.reasons[a] = Some(reason);
+    }
+}
+
+impl Biblio {
+    /// Finds the groups of duplicate entries in this bibliography.
+    ///
+    /// The clusters are ordered by the first cite key in each cluster.
+    #[must_use]
+    pub fn duplicates(&self) -> Vec<Cluster<'_>> {
+        let entries = self.entries().collect::<Vec<_>>();
+        let features = entries.iter().map(|e| Features::new(e)).collect::<Vec<_>>();
+        let mut groups = Groups::new(entries.len());
+
+        // exact matches on the identifiers
+        let mut dois = HashMap::new();
+        let mut isbns = HashMap::new();
+        for (i, feature) in features.iter().enumerate() {
+            if let Some(doi) = &feature.doi {
+                if let Some(j) = dois.insert(doi.as_str(), i) {
+                    groups.union(j, i, MatchReason::Doi);
+                }
+            }
+            if let Some(isbn) = &feature.isbn {
+                if let Some(j) = isbns.insert(isbn.as_str(), i) {
+                    groups.union(j, i, MatchReason::Isbn);
+                }
+            }
+        }
+
+        // fuzzy matches on the title, year and authors
+        for i in 0..features.len() {
+            for j in (i + 1)..features.len() {
+                let (a, b) = (&features[i], &features[j]);
+                // entries with different identifiers of the same kind are never duplicates
+                let different_ids = matches!((&a.doi, &b.doi), (Some(x), Some(y)) if x != y)
+                    || matches!((&a.isbn, &b.isbn), (Some(x), Some(y)) if x != y);
+
+                if !different_ids && groups.find(i) != groups.find(j) && a.is_similar(b) {
+                    groups.union(i, j, MatchReason::Similar);
+                }
+            }
+        }
+
+        let mut clusters: HashMap<usize, Vec<&Entry>> = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let root = groups.find(i);
+            clusters.entry(root).or_default().push(entry);
+        }
+
+        let mut clusters = clusters
+            .into_iter()
+            .filter(|(_, entries)| entries.len() > 1)
+            .map(|(root, mut entries)| {
+                entries.sort_by_key(|entry| entry.cite());
+                Cluster {
+                    entries,
+                    reason: groups.reasons[root].unwrap_or(MatchReason::Similar),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        clusters.sort_by(|a, b| a.entries[0].cite().cmp(b.entries[0].cite()));
+        clusters
+    }
+
+    /// Replaces the duplicate entries with the merged entry.
+    ///
+    /// The entries with exactly the `duplicates` cite keys are removed and the `merged` entry is
+    /// inserted, any `crossref` field in the remaining entries that referenced one of the removed
+    /// entries is rewritten to the cite key of the `merged` entry.
+    pub fn merge_duplicates(&mut self, duplicates: &[&str], merged: Entry) {
+        let cite = merged.cite().to_owned();
+
+        for duplicate in duplicates {
+            self.take(duplicate);
+        }
+        self.insert(merged);
+
+        let is_duplicate = |value: &str| {
+            duplicates
+                .iter()
+                .any(|dup| dup.eq_ignore_ascii_case(value.trim()))
+        };
+
+        self.modify_entries(|entry| {
+            let is_crossref = entry.get_field("crossref").is_some_and(|v| is_duplicate(v));
+            if is_crossref {
+                entry.set_field("crossref", cite.as_str());
+            }
+            is_crossref
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::manual_entry;
+
+    fn cluster_cites(biblio: &Biblio) -> Vec<Vec<&str>> {
+        biblio
+            .duplicates()
+            .iter()
+            .map(|cluster| cluster.cites().collect())
+            .collect()
+    }
+
+    #[test]
+    fn doi_case_and_prefix_differences_are_duplicates() {
+        let biblio = Biblio::new(vec![
+            manual_entry("a", &[("title", "One"), ("doi", "10.1000/ABC")]),
+            manual_entry(
+                "b",
+                &[("title", "Two"), ("doi", "https://doi.org/10.1000/abc")],
+            ),
+            manual_entry("c", &[("title", "Three"), ("doi", "10.1000/xyz")]),
+        ]);
+
+        let clusters = biblio.duplicates();
+
+        assert_eq!(1, clusters.len());
+        assert_eq!(MatchReason::Doi, clusters[0].reason());
+        assert_eq!(vec![vec!["a", "b"]], cluster_cites(&biblio));
+    }
+
+    #[test]
+    fn isbn_10_and_13_are_duplicates() {
+        let biblio = Biblio::new(vec![
+            manual_entry("a", &[("title", "Code Complete"), ("isbn", "0735619670")]),
+            manual_entry(
+                "b",
+                &[("title", "Code complete 2"), ("isbn", "978-0735619678")],
+            ),
+        ]);
+
+        assert_eq!(vec![vec!["a", "b"]], cluster_cites(&biblio));
+    }
+
+    #[test]
+    fn similar_titles_with_same_year_and_authors_are_duplicates() {
+        let biblio = Biblio::new(vec![
+            manual_entry(
+                "a",
+                &[
+                    ("title", "Tackling the Awkward Squad"),
+                    ("author", "Peyton Jones, Simon"),
+                    ("year", "2001"),
+                ],
+            ),
+            manual_entry(
+                "b",
+                &[
+                    ("title", "Tackling the awkward squad."),
+                    ("author", "Simon Peyton Jones and Other Person"),
+                    ("year", "2001"),
+                ],
+            ),
+            manual_entry(
+                "c",
+                &[
+                    ("title", "Tackling the awkward squad"),
+                    ("author", "Peyton Jones, Simon"),
+                    ("year", "2010"),
+                ],
+            ),
+            manual_entry("d", &[("title", "Something else entirely")]),
+        ]);
+
+        assert_eq!(vec![vec!["a", "b"]], cluster_cites(&biblio));
+    }
+
+    #[test]
+    fn different_dois_are_never_duplicates() {
+        let biblio = Biblio::new(vec![
+            manual_entry("a", &[("title", "Same title"), ("doi", "10.1/a")]),
+            manual_entry("b", &[("title", "Same title"), ("doi", "10.1/b")]),
+        ]);
+
+        assert!(biblio.duplicates().is_empty());
+    }
+
+    #[test]
+    fn merge_duplicates_rewrites_crossref() {
+        let mut biblio = Biblio::new(vec![
+            manual_entry("a", &[("title", "Book")]),
+            manual_entry("b", &[("title", "Book")]),
+            manual_entry("chapter", &[("title", "Chapter"), ("crossref", "B")]),
+        ]);
+
+        let merged = manual_entry("a", &[("title", "Book"), ("edition", "2")]);
+        biblio.merge_duplicates(&["a", "b"], merged);
+
+        assert!(biblio.get("b").is_none());
+        assert_eq!(
+            Some("2"),
+            biblio
+                .get("a")
+                .and_then(|e| e.get_field("edition"))
+                .map(|v| &**v)
+        );
+        assert_eq!(
+            Some("a"),
+            biblio
+                .get("chapter")
+                .and_then(|e| e.get_field("crossref"))
+                .map(|v| &**v)
+        );
+    }
+
+    #[test]
+    fn merge_duplicates_only_removes_the_exact_cite_keys() {
+        let mut biblio = Biblio::new(vec![
+            manual_entry("Smith2020", &[("title", "Book")]),
+            manual_entry("Smith2020a", &[("title", "Book")]),
+            manual_entry("smith2020", &[("title", "Another book")]),
+        ]);
+        biblio.dirty();
+
+        let merged = manual_entry("Smith2020a", &[("title", "Book")]);
+        biblio.merge_duplicates(&["Smith2020", "Smith2020a"], merged);
+
+        assert!(biblio.get("Smith2020").is_none());
+        assert!(biblio.get("smith2020").is_some());
+        assert!(biblio.dirty());
+    }
+}
@@ -25,6 +25,14 @@ pub enum ErrorKind {
     Deserialize,
     /// An error when an operation has failed to return a value.
     NoValue,
+    /// An error when a file has changed on disk since it was read.
+    Conflict,
+    /// An error when an API has no resource for the request.
+    NotFound,
+    /// An error when an API refused a request because too many requests were sent.
+    RateLimited,
+    /// An error when a request could not be sent or an API failed to respond.
+    Network,
 }
 
 impl Error {
@@ -75,7 +83,11 @@ impl std::fmt::Display for Error {
             ErrorKind::IO => f.write_str("IO error: ")?,
             ErrorKind::Deserialize => f.write_str("Deserialize error: ")?,
             ErrorKind::NoValue => f.write_str("No value error: ")?,
-        };
+            ErrorKind::Conflict => f.write_str("Conflict error: ")?,
+            ErrorKind::NotFound => f.write_str("Not found error: ")?,
+            ErrorKind::RateLimited => f.write_str("Rate limited error: ")?,
+            ErrorKind::Network => f.write_str("Network error: ")?,
+        }
 
         if let Some(message) = &self.message {
             write!(f, "{message}")?;
@@ -4,17 +4,22 @@
 //! provides types to read and write from those format files.
 
 use std::{
-    fs::{File, OpenOptions},
-    io::{Read, Seek, Write},
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    hash::Hasher,
+    io::{Read, Write},
     marker::PhantomData,
     path::{Path, PathBuf},
 };
 
 use crate::{
+    ast::{Biblio, BiblioResolver, ConflictPolicy, Entry, MergeReport},
     format::{Format, Reader, Writer},
     Error, ErrorKind,
 };
 
+use fnv::FnvHasher;
+use fs2::FileExt;
 use glob::{glob, Paths};
 
 /// A reference to an open file on the filesystem which should have the textual content that
@@ -22,23 +27,161 @@ use glob::{glob, Paths};
 ///
 /// `FormatFile`s are automatically closed when they go out of scope. Errors detected on closing are
 /// ignored by the implementation of `Drop`.
+///
+/// Writing a `FormatFile` never changes the file in place. The new content is written to a
+/// temporary file in the same directory, synced to disk and then renamed over the file, so the file
+/// has either the old or the new content if the process is killed or the disk fills mid-write. The
+/// permissions of the file are kept, and the previous content can be kept in rotating backups, see
+/// [`FormatFile::set_backups`].
+///
+/// A `FormatFile` takes a shared [`FileLock`] of the file while it is read and an exclusive
+/// [`FileLock`] while it checks whether the file changed since it was read and writes it, so that
+/// another process using this crate cannot write the file in between.
 #[allow(clippy::module_name_repetitions)]
 pub struct FormatFile<F: Format> {
     // Raw file handler.
     file: File,
+    // The path the file was opened with, which is where the file is written to.
+    path: PathBuf,
+    // The number of backups kept when the file is written.
+    backups: usize,
+    // The content of the file when it was last read or written.
+    base: Option<Base>,
     // Generic F in PhantomData so that drop implementation knows that
     // FormatFile is not holding an actual F that needs dropping too.
     _format: PhantomData<F>,
 }
 
 impl<F: Format> FormatFile<F> {
-    fn new(file: File) -> Self {
+    const fn new(file: File, path: PathBuf) -> Self {
         Self {
             file,
+            path,
+            backups: 0,
+            base: None,
             _format: PhantomData,
         }
     }
 
+    /// The path of the file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Sets the number of backups of the previous content that are kept each time the file is
+    /// written, which is none by default.
+    ///
+    /// The most recent backup of `refs.bib` is `refs.bib.bak`, followed by `refs.bib.bak.1`,
+    /// `refs.bib.bak.2` and so on, where the oldest backup is removed once there are `count`
+    /// backups.
+    pub const fn set_backups(&mut self, count: usize) {
+        self.backups = count;
+    }
+
+    /// Writes the [`Biblio`] like [`Writer::write_ast`], but when the file changed since it was
+    /// read the changes are merged into the [`Biblio`] first rather than refusing to write.
+    ///
+    /// The content of the file when it was read is the base of a three-way merge using
+    /// [`Biblio::merge_changes`], where `resolve` is called with our entry and their entry to
+    /// resolve each conflict.
+    ///
+    /// # Errors
+    /// This function will return an error if the file cannot be read or written, or if the file
+    /// when it was read or its current content cannot be parsed or has entries that are missing
+    /// required fields.
+    pub fn write_ast_merged<R>(
+        &mut self,
+        mut biblio: Biblio,
+        resolve: R,
+    ) -> Result<MergeReport, Error>
+    where
+        R: FnMut(&Entry, &Entry) -> ConflictPolicy,
+    {
+        let lock = FileLock::exclusive(&self.path)?;
+        let report = match (self.changed_since_read(&lock)?, &self.base) {
+            (Some(current), Some(base)) => {
+                log::info!(
+                    "Merging the changes made to the '{}' file since it was read",
+                    self.path.display()
+                );
+                let base = self.parse_for_merge(base.text.clone())?;
+                let theirs = self.parse_for_merge(current)?;
+                biblio.merge_changes(base, theirs, resolve)
+            }
+            _ => MergeReport::default(),
+        };
+
+        self.replace(F::compose(&biblio).raw().into_bytes())?;
+        Ok(report)
+    }
+
+    /// The current content of the locked file if it is different to the content that was read,
+    /// where a file that was never read is unchanged.
+    ///
+    /// The content is always compared by its hash rather than by the modified time of the file,
+    /// which may not change when the file is written twice within the resolution of the clock.
+    fn changed_since_read(&self, lock: &FileLock) -> Result<Option<String>, Error> {
+        let Some(base) = &self.base else {
+            return Ok(None);
+        };
+
+        // a file that was removed is locked as an empty file, which is a change to the file
+        let current = read_file_to_string(&lock.file)?;
+
+        if base.matches(&current) {
+            Ok(None)
+        } else {
+            log::debug!("{} changed since it was read", self.path.display());
+            Ok(Some(current))
+        }
+    }
+
+    fn parse_for_merge(&self, text: String) -> Result<Biblio, Error> {
+        F::new(text).parse()?.map_err(|resolver| {
+            Error::wrap_with(
+                ErrorKind::Conflict,
+                resolver.to_string(),
+                format!(
+                    "Cannot merge the changes made to the '{}' file as it has entries that are \
+                    missing required fields",
+                    self.path.display()
+                ),
+            )
+        })
+    }
+
+    /// Replaces the content of the file with the bytes, see [`replace_file`].
+    fn replace(&mut self, bytes: Vec<u8>) -> Result<(), Error> {
+        // write through symbolic links so that the link is not replaced by the file
+        let path = fs::canonicalize(&self.path).unwrap_or_else(|_| self.path.clone());
+        let wrap_write_error = |e| {
+            Error::wrap_with(
+                ErrorKind::IO,
+                e,
+                format!("Cannot write the '{}' file", self.path.display()),
+            )
+        };
+
+        rotate_backups(&path, self.backups).map_err(|e| {
+            Error::wrap_with(
+                ErrorKind::IO,
+                e,
+                format!("Cannot back up the '{}' file", self.path.display()),
+            )
+        })?;
+        replace_file(&path, &bytes).map_err(wrap_write_error)?;
+
+        // the open file is the replaced file so open the new file for any following reads
+        self.file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .map_err(wrap_write_error)?;
+        self.base = String::from_utf8(bytes).ok().map(Base::of);
+        Ok(())
+    }
+
     /// Attempts to open a format file in read and write mode.
     ///
     /// # Errors
@@ -135,17 +278,50 @@ impl<F: Format> Reader for FormatFile<F> {
     type Format = F;
 
     fn read(&mut self) -> Result<Self::Format, Error> {
+        let _lock = FileLock::shared(&self.path)?;
+
         // Read the file contents into a string value then wrap that
         // string with the associated Format type.
         //
         // Any IO error is wrapped by the crate Error type
-        read_file_to_string(&mut self.file)
-            .map(F::new)
-            .map_err(|e| Error::wrap(ErrorKind::IO, e))
+        let content = read_file_to_string(&self.file).map_err(|e| Error::wrap(ErrorKind::IO, e))?;
+        self.base = Some(Base::of(content.clone()));
+        Ok(F::new(content))
     }
 }
 
-fn read_file_to_string(file: &mut File) -> Result<String, Error> {
+/// The content of a file when it was read, which is used to find whether the file changed and as
+/// the base of a three-way merge when it did.
+struct Base {
+    len: usize,
+    hash: u64,
+    text: String,
+}
+
+impl Base {
+    fn of(text: String) -> Self {
+        Self {
+            len: text.len(),
+            hash: hash_text(&text),
+            text,
+        }
+    }
+
+    /// Whether the text has the same size and hash as the base.
+    fn matches(&self, text: &str) -> bool {
+        self.len == text.len() && self.hash == hash_text(text)
+    }
+}
+
+/// The FNV-1a hash of the text, which unlike the standard library hasher is the same across Rust
+/// releases.
+fn hash_text(text: &str) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(text.as_bytes());
+    hasher.finish()
+}
+
+fn read_file_to_string(mut file: &File) -> Result<String, Error> {
     // Wraps an IO error when trying to access a file contents or metadata.
     #[inline]
     fn wrap_file_access_error(e: std::io::Error) -> Error {
@@ -182,29 +358,590 @@ impl<F: Format> Writer for FormatFile<F> {
     type Format = F;
 
     fn write(&mut self, format: F) -> Result<(), Error> {
-        fn overrwrite_file_from_start(file: &mut File, bytes: &[u8]) -> std::io::Result<()> {
-            // Rewind the cursor back to the start of the file to write over the contents and set
-            // the length of the file to be equal to bytes so that existing data is removed
-            log::trace!("rewind file cursor to start and write bytes: {bytes:?}");
-            file.rewind()?;
-            file.set_len(bytes.len() as u64)?;
-            file.write_all(bytes)
-        }
-
         // Get raw contents of Format string as bytes
         let bytes = format.raw().into_bytes();
-        overrwrite_file_from_start(&mut self.file, &bytes)
-            .map_err(|e| Error::wrap(ErrorKind::IO, e))
+
+        let lock = FileLock::exclusive(&self.path)?;
+        if self.changed_since_read(&lock)?.is_some() {
+            return Err(Error::new(
+                ErrorKind::Conflict,
+                format!(
+                    "The '{}' file changed since it was read, so it was not overwritten\n\
+                    hint: consider enabling interactive mode (-i / --interact) to merge the \
+                    changes.",
+                    self.path.display()
+                ),
+            ));
+        }
+        self.replace(bytes)
+    }
+}
+
+/// An advisory lock of a file, which is held until the lock is dropped.
+///
+/// A shared lock can be held by many processes at once to read the file, while an exclusive lock
+/// is held by a single process to write the file. The lock is only respected by processes that
+/// also take it.
+#[allow(clippy::module_name_repetitions)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Takes a shared lock of the file at the path, waiting until any other process holding an
+    /// exclusive lock of the file drops it.
+    ///
+    /// # Errors
+    /// This function will return an error if the file cannot be opened or locked.
+    pub fn shared<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::acquire(path.as_ref(), false)
+    }
+
+    /// Takes an exclusive lock of the file at the path, waiting until any other process holding
+    /// a lock of the file drops it.
+    ///
+    /// A file that does not exist is created empty so that it can be locked.
+    ///
+    /// # Errors
+    /// This function will return an error if the file cannot be created or locked.
+    pub fn exclusive<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::acquire(path.as_ref(), true)
+    }
+
+    fn acquire(path: &Path, exclusive: bool) -> Result<Self, Error> {
+        // lock through symbolic links so that every link to the file shares the lock
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let wrap_lock_error = |e| {
+            Error::wrap_with(
+                ErrorKind::IO,
+                e,
+                format!("Cannot lock the '{}' file", path.display()),
+            )
+        };
+
+        loop {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(exclusive)
+                .create(exclusive)
+                .truncate(false)
+                .open(&path)
+                .map_err(wrap_lock_error)?;
+            let locked = if exclusive {
+                FileExt::try_lock_exclusive(&file)
+            } else {
+                FileExt::try_lock_shared(&file)
+            };
+            if locked.is_err() {
+                log::info!(
+                    "Waiting for another process to unlock the '{}' file",
+                    path.display()
+                );
+                if exclusive {
+                    FileExt::lock_exclusive(&file)
+                } else {
+                    FileExt::lock_shared(&file)
+                }
+                .map_err(wrap_lock_error)?;
+            }
+
+            // a file written while waiting for the lock is a new file, which has to be locked
+            if is_same_file(&file, &path) {
+                return Ok(Self { file, path });
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Err(e) = FileExt::unlock(&self.file) {
+            log::warn!("Cannot unlock the '{}' file: {e}", self.path.display());
+        }
     }
 }
 
+/// Whether the open file is the file at the path, rather than a file that was replaced.
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(open), Ok(current)) => open.dev() == current.dev() && open.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+/// The identity of a file is not available on this platform, so the open file is taken to be the
+/// file at the path.
+#[cfg(not(unix))]
+const fn is_same_file(_file: &File, _path: &Path) -> bool {
+    true
+}
+
+/// Atomically replaces the content of the file by writing the bytes to a temporary file in the
+/// same directory, syncing it to disk and renaming it over the file with the same permissions.
+///
+/// A file that does not exist is created, and the file is never left partially written.
+///
+/// # Errors
+///
+/// An `Err` is returned when the temporary file cannot be written or renamed over the file.
+pub fn replace_file(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut temp = tempfile::Builder::new()
+        .prefix(&format!(".{name}."))
+        .suffix(".tmp")
+        .tempfile_in(dir)?;
+    log::trace!("writing {} bytes to {}", bytes.len(), temp.path().display());
+    temp.write_all(bytes)?;
+    temp.as_file().sync_all()?;
+
+    match fs::metadata(path) {
+        Ok(metadata) => temp.as_file().set_permissions(metadata.permissions())?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    temp.persist(path).map_err(|e| e.error)?;
+    sync_dir(dir)
+}
+
+/// Syncs the directory so that a rename in the directory is on disk.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+/// Directories cannot be opened to sync them on this platform, where a rename is on disk once it
+/// returns.
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// The path of a backup of the file, where `0` is the most recent backup.
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    if index > 0 {
+        name.push(format!(".{index}"));
+    }
+    PathBuf::from(name)
+}
+
+/// Copies the current content of the file to the most recent backup, moving each existing backup
+/// along by one and removing the oldest so there are at most `count` backups.
+fn rotate_backups(path: &Path, count: usize) -> std::io::Result<()> {
+    // nothing worth keeping in a file that is missing or empty
+    if count == 0 || fs::metadata(path).map_or(true, |metadata| metadata.len() == 0) {
+        return Ok(());
+    }
+
+    match fs::remove_file(backup_path(path, count - 1)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    for index in (1..count).rev() {
+        match fs::rename(backup_path(path, index - 1), backup_path(path, index)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+
+    log::trace!(
+        "backing up {} to {}",
+        path.display(),
+        backup_path(path, 0).display()
+    );
+    fs::copy(path, backup_path(path, 0)).map(|_| ())
+}
+
+/// A set of format files that are read and written as one [`Biblio`], such as the `papers.bib`,
+/// `books.bib` and `software.bib` files of a larger project.
+///
+/// The set remembers which file each entry was read from so that the entry is written back to the
+/// same file. Entries that were not read from any of the files, such as new entries or entries
+/// with a changed cite key, are written to the target file, which is the first file unless it is
+/// changed with [`FormatFileSet::set_target`]. Files are only written when their entries change.
+///
+/// Cite keys are unique across the whole set, ignoring case, so reading two files that both
+/// contain the same cite key is an error.
+#[allow(clippy::module_name_repetitions)]
+pub struct FormatFileSet<F: Format> {
+    files: Vec<SetFile<F>>,
+    // the index of the file that each lowercase cite key was read from or written to
+    sources: HashMap<String, usize>,
+    target: usize,
+}
+
+struct SetFile<F: Format> {
+    file: FormatFile<F>,
+    // the sorted composed entries of the file when it was last read or written, used to skip
+    // writing files whose entries have not changed
+    composed: Vec<String>,
+}
+
+impl<F: Format> FormatFileSet<F> {
+    fn new(files: Vec<FormatFile<F>>) -> Result<Self, Error> {
+        if files.is_empty() {
+            return Err(Error::new(
+                ErrorKind::IO,
+                format!("No .{} files given for the set of files", F::ext()),
+            ));
+        }
+
+        Ok(Self {
+            files: files
+                .into_iter()
+                .map(|file| SetFile {
+                    file,
+                    composed: Vec::new(),
+                })
+                .collect(),
+            sources: HashMap::new(),
+            target: 0,
+        })
+    }
+
+    /// Attempts to open each of the format files in read and write mode.
+    ///
+    /// # Errors
+    /// This function will return an error if no paths are given, any of the paths do not already
+    /// exist or the user lacks permissions to open one of the files.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seb::{
+    ///     file::FormatFileSet,
+    ///     format::BibTex
+    /// };
+    ///
+    /// fn main() -> Result<(), seb::Error> {
+    ///     let mut files = FormatFileSet::<BibTex>::open(["papers.bib", "books.bib"])?;
+    ///     Ok(())
+    /// }
+    ///
+    /// ```
+    pub fn open<P, I>(paths: I) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = P>,
+    {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let path = path.as_ref().with_extension(F::ext());
+                open_file_for_read_and_write(&path)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Self::new(files)
+    }
+
+    /// Opens each of the format files in read and write mode, creating the files that do not
+    /// exist.
+    ///
+    /// # Errors
+    /// This function will return an error if no paths are given or the user lacks permissions to
+    /// open or create one of the files.
+    pub fn open_or_create<P, I>(paths: I) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = P>,
+    {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let path = path.as_ref().with_extension(F::ext());
+                if path.exists() {
+                    open_file_for_read_and_write(&path)
+                } else {
+                    create_file_for_read_and_write(&path)
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        Self::new(files)
+    }
+
+    /// Attempts to open every format file in the directory, in the order of their paths.
+    ///
+    /// # Errors
+    /// This function will return an error if:
+    /// - The path is not a directory
+    /// - No file can be found in the directory
+    /// - User lacks permissions to open one of the files
+    pub fn find<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let paths = format_file_paths_in_directory::<F, _>(path)?;
+        Self::open(paths)
+    }
+
+    /// The paths of the files in the set.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|set_file| set_file.file.path())
+    }
+
+    /// Sets the number of backups kept of each file when it is written, see
+    /// [`FormatFile::set_backups`].
+    pub fn set_backups(&mut self, count: usize) {
+        for set_file in &mut self.files {
+            set_file.file.set_backups(count);
+        }
+    }
+
+    /// The number of files in the set, which is never zero.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether the set has no files, which is never the case as a set is opened with at least one
+    /// file.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The path of the file that new entries are written to.
+    #[must_use]
+    pub fn target(&self) -> &Path {
+        self.files[self.target].file.path()
+    }
+
+    /// Sets the file that new entries are written to, which must be one of the files in the set.
+    ///
+    /// # Errors
+    /// This function will return an error if the path is not one of the files in the set.
+    pub fn set_target<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let index = self.position(path).ok_or_else(|| {
+            let paths = self
+                .paths()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Error::new(
+                ErrorKind::NoValue,
+                format!(
+                    "The target '{}' is not one of the bibliography files: {paths}",
+                    path.display()
+                ),
+            )
+        })?;
+
+        self.target = index;
+        Ok(())
+    }
+
+    /// The path of the file that the entry with the cite key was read from or last written to.
+    #[must_use]
+    pub fn source(&self, cite: &str) -> Option<&Path> {
+        self.sources
+            .get(&cite.to_lowercase())
+            .map(|&index| self.files[index].file.path())
+    }
+
+    /// Sets the file that the entry with the cite key is written to, such as when an entry that
+    /// was removed from a file is restored, and returns whether the path is one of the files.
+    pub fn set_source<P: AsRef<Path>>(&mut self, cite: &str, path: P) -> bool {
+        match self.position(path.as_ref()) {
+            Some(index) => {
+                self.sources.insert(cite.to_lowercase(), index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The index of the file with the path, comparing the canonical paths when they exist.
+    fn position(&self, path: &Path) -> Option<usize> {
+        let path = path.with_extension(F::ext());
+        let canonical = path.canonicalize().ok();
+
+        self.files.iter().position(|set_file| {
+            set_file.file.path == path
+                || canonical.is_some() && set_file.file.path.canonicalize().ok() == canonical
+        })
+    }
+
+    /// Reads every file of the set into a single [`Biblio`].
+    ///
+    /// # Errors
+    /// This function will return an error if one of the files cannot be read or parsed, or if the
+    /// same cite key is used in more than one file.
+    pub fn read_ast(&mut self) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        let paths = self.paths().map(Path::to_path_buf).collect::<Vec<_>>();
+        let mut sources: HashMap<String, usize> = HashMap::new();
+        let mut combined: Result<Vec<Entry>, BiblioResolver> = Ok(Vec::new());
+
+        for (index, set_file) in self.files.iter_mut().enumerate() {
+            let parsed = set_file.file.read_ast()?;
+
+            let cites: Vec<String> = match &parsed {
+                Ok(biblio) => biblio.entries().map(|e| e.cite().to_lowercase()).collect(),
+                Err(resolver) => resolver.cites().map(|c| c.to_lowercase()).collect(),
+            };
+            for cite in cites {
+                match sources.get(&cite) {
+                    Some(&other) if other != index => {
+                        return Err(Error::new(
+                            ErrorKind::Deserialize,
+                            format!(
+                                "The cite key '{cite}' is used in both the '{}' and '{}' files",
+                                paths[other].display(),
+                                set_file.file.path.display()
+                            ),
+                        ));
+                    }
+                    _ => {
+                        sources.insert(cite, index);
+                    }
+                }
+            }
+
+            set_file.composed = match &parsed {
+                Ok(biblio) => composed_entries::<F, _>(biblio.entries()),
+                // a file with unresolved entries is always written once they are resolved
+                Err(_) => Vec::new(),
+            };
+
+            combined = match (combined, parsed) {
+                (Ok(mut entries), Ok(biblio)) => {
+                    entries.extend(biblio.into_entries());
+                    Ok(entries)
+                }
+                (Ok(entries), Err(mut resolver)) => {
+                    resolver.extend(Ok(Biblio::new(entries)));
+                    Err(resolver)
+                }
+                (Err(mut resolver), parsed) => {
+                    resolver.extend(parsed);
+                    Err(resolver)
+                }
+            };
+        }
+
+        self.sources = sources;
+        Ok(combined.map(Biblio::new))
+    }
+
+    /// Writes each entry of the [`Biblio`] to the file it was read from, or to the target file
+    /// when it was not read from any of the files.
+    ///
+    /// # Errors
+    /// This function will return an error if one of the changed files cannot be written, or has
+    /// changed since it was read, see [`FormatFile`].
+    pub fn write_ast(&mut self, biblio: Biblio) -> Result<(), Error> {
+        self.write_groups(biblio, |file, biblio| {
+            file.write_ast(biblio).map(|()| MergeReport::default())
+        })
+        .map(|_| ())
+    }
+
+    /// Writes each entry of the [`Biblio`] like [`FormatFileSet::write_ast`], but merges the
+    /// changes made to each file since it was read, see [`FormatFile::write_ast_merged`].
+    ///
+    /// # Errors
+    /// This function will return an error if one of the changed files cannot be written, or its
+    /// changes cannot be merged.
+    pub fn write_ast_merged<R>(
+        &mut self,
+        biblio: Biblio,
+        mut resolve: R,
+    ) -> Result<MergeReport, Error>
+    where
+        R: FnMut(&Entry, &Entry) -> ConflictPolicy,
+    {
+        self.write_groups(biblio, |file, biblio| {
+            file.write_ast_merged(biblio, &mut resolve)
+        })
+    }
+
+    fn write_groups<W>(&mut self, biblio: Biblio, mut write: W) -> Result<MergeReport, Error>
+    where
+        W: FnMut(&mut FormatFile<F>, Biblio) -> Result<MergeReport, Error>,
+    {
+        let mut groups: Vec<Vec<Entry>> = self.files.iter().map(|_| Vec::new()).collect();
+        for entry in biblio.into_entries() {
+            let index = self
+                .sources
+                .get(&entry.cite().to_lowercase())
+                .copied()
+                .unwrap_or(self.target);
+            groups[index].push(entry);
+        }
+
+        let mut sources = HashMap::new();
+        let mut merged = MergeReport::default();
+        for ((index, set_file), entries) in self.files.iter_mut().enumerate().zip(groups) {
+            sources.extend(entries.iter().map(|e| (e.cite().to_lowercase(), index)));
+
+            let composed = composed_entries::<F, _>(entries.iter());
+            if composed == set_file.composed {
+                log::trace!("{} is unchanged", set_file.file.path.display());
+                continue;
+            }
+
+            log::trace!(
+                "Writing {} entries to {}",
+                entries.len(),
+                set_file.file.path.display()
+            );
+            let report = write(&mut set_file.file, Biblio::new(entries))?;
+
+            for cite in &report.removed {
+                sources.remove(&cite.to_lowercase());
+            }
+            sources.extend(report.added.iter().map(|cite| (cite.to_lowercase(), index)));
+            // the merged entries are not known so the file is always written again
+            set_file.composed = if report == MergeReport::default() {
+                composed
+            } else {
+                Vec::new()
+            };
+
+            merged.added.extend(report.added);
+            merged.skipped.extend(report.skipped);
+            merged.conflicts.extend(report.conflicts);
+            merged.renamed.extend(report.renamed);
+            merged.removed.extend(report.removed);
+        }
+
+        self.sources = sources;
+        Ok(merged)
+    }
+}
+
+// The entries composed to the format and sorted so that the entries of two files can be compared
+// regardless of their order.
+fn composed_entries<'e, F, I>(entries: I) -> Vec<String>
+where
+    F: Format,
+    I: Iterator<Item = &'e Entry>,
+{
+    let mut composed = entries.map(F::compose_entry).collect::<Vec<_>>();
+    composed.sort_unstable();
+    composed
+}
+
 #[inline]
 fn open_file_for_read_and_write<F: Format>(path: &Path) -> Result<FormatFile<F>, Error> {
     OpenOptions::new()
         .read(true)
         .write(true)
         .open(path)
-        .map(FormatFile::<F>::new)
+        .map(|file| FormatFile::<F>::new(file, path.to_path_buf()))
         .map_err(|e| {
             Error::wrap_with(
                 ErrorKind::IO,
@@ -224,7 +961,7 @@ fn create_file_for_read_and_write<F: Format>(path: &Path) -> Result<FormatFile<F
         .read(true)
         .write(true)
         .open(path)
-        .map(FormatFile::<F>::new)
+        .map(|file| FormatFile::<F>::new(file, path.to_path_buf()))
         .map_err(|e| {
             Error::wrap_with(
                 ErrorKind::IO,
@@ -269,7 +1006,7 @@ impl Iterator for GlobIter {
     }
 }
 
-fn find_format_file_in_directory<F, P>(dir: P) -> Result<FormatFile<F>, Error>
+fn format_file_paths_in_directory<F, P>(dir: P) -> Result<Vec<PathBuf>, Error>
 where
     F: Format,
     P: AsRef<Path>,
@@ -282,55 +1019,62 @@ where
     }
 
     let pattern = format!("{}/*.{}", path.to_string_lossy(), F::ext());
-    let mut iter = GlobIter::try_glob(&pattern)?;
+    let paths = GlobIter::try_glob(&pattern)?.collect::<Result<Vec<_>, Error>>()?;
 
-    let dir = path
-        // we want the actual file path and not relative "."
-        .canonicalize()
-        // shouldn't error as we've had access to this file path already but just to becareful
-        .map_err(|e| Error::wrap(ErrorKind::IO, e))?;
+    if paths.is_empty() {
+        let dir = path
+            // we want the actual file path and not relative "."
+            .canonicalize()
+            // shouldn't error as we've had access to this file path already but just to becareful
+            .map_err(|e| Error::wrap(ErrorKind::IO, e))?;
 
-    let found_file = iter.next().ok_or_else(|| {
-        Error::new(
+        return Err(Error::new(
             ErrorKind::IO,
             format!(
                 "No .{} file found in the '{}' directory",
                 F::ext(),
                 dir.display()
             ),
-        )
-    })??;
-
-    // we check if more than one file is found and if so then we need to return
-    // an error early with all the files found.
-
-    // map through the remaining items and convert them to file name strings
-    // and collect.
-    let extra_files = iter
-        .map(|res| {
-            let path = res?;
-            Ok::<_, Error>(path.display().to_string())
-        })
-        .collect::<Result<Vec<String>, Error>>()?;
+        ));
+    }
 
-    // if the list of files names is not empty then we need to start building up the error message
-    // for too many files found
-    if !extra_files.is_empty() {
-        // create a list of extra files in a single String.
-        let extra_files = extra_files.join("\n");
+    Ok(paths)
+}
+
+fn find_format_file_in_directory<F, P>(dir: P) -> Result<FormatFile<F>, Error>
+where
+    F: Format,
+    P: AsRef<Path>,
+{
+    let path = dir.as_ref();
+    let mut paths = format_file_paths_in_directory::<F, _>(path)?;
+
+    // if more than one file is found then we need to return an error early with all the files
+    // found.
+    if paths.len() > 1 {
+        let dir = path
+            .canonicalize()
+            .map_err(|e| Error::wrap(ErrorKind::IO, e))?;
+
+        // create a list of the files in a single String.
+        let files = paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
 
         let msg = format!(
             "More than one .{} file found in the '{}' directory!\nThe following files were found:\n\
-            {}\n{}",
+            {}",
             F::ext(),
             dir.display(),
-            found_file.display(),
-            extra_files,
+            files,
         );
 
         return Err(Error::new(ErrorKind::IO, msg));
     }
 
+    let found_file = paths.remove(0);
     open_file_for_read_and_write(found_file.as_path())
 }
 
@@ -338,10 +1082,10 @@ where
 mod tests {
 
     use super::*;
-    use crate::format::BibTex;
+    use crate::{ast::manual_entry, format::BibTex};
 
     use assert_fs::{
-        fixture::{FileTouch, PathChild},
+        fixture::{FileTouch, FileWriteStr, PathChild},
         NamedTempFile, TempDir,
     };
 
@@ -406,6 +1150,187 @@ mod tests {
         find_format_file_in_directory::<BibTex, _>(TempDir::path(&dir)).unwrap();
     }
 
+    #[test]
+    fn write_replaces_file_and_rotates_backups() {
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        let child = dir.child("refs.bib");
+        child.write_str("first").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(child.path(), fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        let mut file = FormatFile::<BibTex>::open(child.path()).unwrap();
+        file.set_backups(2);
+        for content in ["second", "third", "fourth"] {
+            file.write(BibTex::new(content.to_owned())).unwrap();
+        }
+
+        let read = |name: &str| fs::read_to_string(dir.child(name).path()).unwrap();
+        assert_eq!("fourth", read("refs.bib"));
+        assert_eq!("fourth", file.read().unwrap().raw());
+        assert_eq!("third", read("refs.bib.bak"));
+        assert_eq!("second", read("refs.bib.bak.1"));
+        assert!(!dir.child("refs.bib.bak.2").path().exists());
+
+        // no temporary files are left behind
+        assert_eq!(3, fs::read_dir(TempDir::path(&dir)).unwrap().count());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(child.path()).unwrap().permissions().mode();
+            assert_eq!(0o640, mode & 0o777);
+        }
+    }
+
+    #[test]
+    fn write_refuses_or_merges_changes_made_since_read() {
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        let child = dir.child("refs.bib");
+        child
+            .write_str("@manual{a,\n  title = {A}\n}\n@manual{b,\n  title = {B}\n}\n")
+            .unwrap();
+
+        let mut file = FormatFile::<BibTex>::open(child.path()).unwrap();
+        let mut biblio = file.read_ast().unwrap().unwrap();
+        biblio.insert(manual_entry("ours", &[]));
+
+        // an editor changes the file after it was read
+        child
+            .write_str("@manual{a,\n  title = {A}\n}\n@manual{theirs,\n  title = {Theirs}\n}\n")
+            .unwrap();
+
+        let err = file
+            .write(BibTex::compose(&Biblio::new(vec![manual_entry(
+                "lost",
+                &[],
+            )])))
+            .unwrap_err();
+        assert_eq!(ErrorKind::Conflict, err.kind());
+
+        let report = file
+            .write_ast_merged(biblio, |_, _| ConflictPolicy::KeepOurs)
+            .unwrap();
+        assert_eq!(vec!["theirs"], report.added);
+        assert_eq!(vec!["b"], report.removed);
+
+        let mut cites = BibTex::new(fs::read_to_string(child.path()).unwrap())
+            .parse()
+            .unwrap()
+            .unwrap()
+            .into_entries()
+            .into_iter()
+            .map(|e| e.cite().to_owned())
+            .collect::<Vec<_>>();
+        cites.sort();
+        assert_eq!(vec!["a", "ours", "theirs"], cites);
+
+        // the file is unchanged since it was written so it can be written again
+        file.write(BibTex::new(String::new())).unwrap();
+    }
+
+    #[test]
+    fn change_of_the_same_size_is_found_without_the_modified_time() {
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        let child = dir.child("refs.bib");
+        child.write_str("@manual{a,\n  title = {A}\n}\n").unwrap();
+
+        let mut file = FormatFile::<BibTex>::open(child.path()).unwrap();
+        file.read().unwrap();
+        let modified = fs::metadata(child.path()).unwrap().modified().unwrap();
+
+        // the file is changed within the same tick of a coarse clock
+        child.write_str("@manual{b,\n  title = {B}\n}\n").unwrap();
+        File::options()
+            .write(true)
+            .open(child.path())
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        let err = file.write(BibTex::new(String::new())).unwrap_err();
+        assert_eq!(ErrorKind::Conflict, err.kind());
+    }
+
+    #[test]
+    fn reads_share_the_lock_and_writes_wait_for_it() {
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        let child = dir.child("refs.bib");
+        child.write_str("").unwrap();
+
+        let lock = FileLock::shared(child.path()).unwrap();
+        // reading does not wait for another reader
+        let mut file = FormatFile::<BibTex>::open(child.path()).unwrap();
+        file.read().unwrap();
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let writer = std::thread::spawn(move || {
+            file.write(BibTex::new("@manual{a,\n  title = {A}\n}\n".to_owned()))
+                .unwrap();
+            sender.send(()).unwrap();
+        });
+
+        let wait = std::time::Duration::from_millis(200);
+        assert!(receiver.recv_timeout(wait).is_err());
+        drop(lock);
+        assert!(receiver.recv_timeout(wait * 10).is_ok());
+        writer.join().unwrap();
+
+        // the file itself is locked, so no lock file is left next to it
+        assert_eq!(1, fs::read_dir(TempDir::path(&dir)).unwrap().count());
+    }
+
+    #[test]
+    fn set_reads_every_file_and_writes_new_entries_to_target() {
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        let books = "@manual{book,\n  title = {Book}\n}\n";
+        dir.child("books.bib").write_str(books).unwrap();
+        dir.child("papers.bib")
+            .write_str("@manual{paper,\n  title = {Paper}\n}\n")
+            .unwrap();
+
+        let mut files = FormatFileSet::<BibTex>::find(TempDir::path(&dir)).unwrap();
+        assert_eq!(2, files.len());
+        files.set_target(dir.child("papers").path()).unwrap();
+
+        let mut biblio = files.read_ast().unwrap().unwrap();
+        assert_eq!(2, biblio.entries().count());
+        assert_eq!(Some(dir.child("books.bib").path()), files.source("BOOK"));
+
+        biblio.insert(manual_entry("new", &[]));
+        files.write_ast(biblio).unwrap();
+
+        let papers = std::fs::read_to_string(dir.child("papers.bib").path()).unwrap();
+        assert!(papers.contains("{paper,") && papers.contains("{new,"));
+        // the books file is not written as its entries have not changed
+        assert_eq!(
+            books,
+            std::fs::read_to_string(dir.child("books.bib").path()).unwrap()
+        );
+        assert_eq!(Some(dir.child("papers.bib").path()), files.source("new"));
+    }
+
+    #[test]
+    fn set_rejects_cite_keys_used_in_more_than_one_file() {
+        let dir = TempDir::new().expect("Cannot create temp directory for test");
+        dir.child("one.bib")
+            .write_str("@manual{same,\n  title = {One}\n}\n")
+            .unwrap();
+        dir.child("two.bib")
+            .write_str("@manual{Same,\n  title = {Two}\n}\n")
+            .unwrap();
+
+        let mut files = FormatFileSet::<BibTex>::find(TempDir::path(&dir)).unwrap();
+        let err = files.read_ast().map(|_| ()).unwrap_err();
+        assert!(err.to_string().contains("'same' is used in both"));
+
+        assert!(files.set_target("elsewhere.bib").is_err());
+        assert!(FormatFileSet::<BibTex>::open(Vec::<PathBuf>::new()).is_err());
+    }
+
     #[test]
     fn read_bib_file_as_bibliograph() {
         // bibtex1 only contains a single bibtex entry so only check equality for one entry
@@ -423,7 +1348,8 @@ mod tests {
         let file = std::fs::File::open("../seb-lib/tests/data/bibtex1.bib")
             .expect("Cannot open ../seb-lib/tests/data/bibtex1.bib file for test");
 
-        let mut file: FormatFile<BibTex> = FormatFile::new(file);
+        let mut file: FormatFile<BibTex> =
+            FormatFile::new(file, "../seb-lib/tests/data/bibtex1.bib".into());
 
         let biblio = file.read_ast().unwrap().unwrap();
         let res = biblio.entries().next().unwrap();
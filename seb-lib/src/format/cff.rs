@@ -0,0 +1,612 @@
+use serde::{Deserialize, Deserializer};
+use serde_yaml_ng::{Mapping, Value};
+
+use crate::{
+    ast::{self, Biblio, BiblioResolver, EntryKind, FieldQuery, Resolver},
+    dedupe::doi_of,
+    Error, ErrorKind,
+};
+
+use super::{
+    software::{self, Person, DATASET, SOFTWARE},
+    Format,
+};
+
+/// A type wrapper around [`String`] to represent a `CITATION.cff` file of the Citation File
+/// Format.
+///
+/// Each YAML document of the file is parsed into a single [`Entry`][E]. When the document has a
+/// `preferred-citation` then the entry is the preferred citation, as it is the work that the
+/// authors ask to be cited instead of the software, otherwise the entry is a `software` entry, or
+/// a `dataset` entry for a `dataset` type. The authors are joined into the `author` field and
+/// any ORCID iDs of the authors are kept in the `orcid` field.
+///
+/// An entry is composed as a document that describes the entry itself when it is a `software` or
+/// `dataset` entry, and as the `preferred-citation` of a document otherwise.
+///
+/// [E]: ast::Entry
+#[derive(Debug)]
+pub struct CitationCff(String);
+
+/// The version of the Citation File Format of composed files.
+const CFF_VERSION: &str = "1.2.0";
+
+/// The types of the Citation File Format and the entry kinds they are parsed into, the first type
+/// of a kind is the type that the kind is composed to.
+const TYPES: &[(&str, EntryKind<'static>)] = &[
+    ("article", EntryKind::Article),
+    ("magazine-article", EntryKind::Article),
+    ("newspaper-article", EntryKind::Article),
+    ("book", EntryKind::Book),
+    ("pamphlet", EntryKind::Booklet),
+    ("conference-paper", EntryKind::InProceedings),
+    ("proceedings", EntryKind::Proceedings),
+    ("manual", EntryKind::Manual),
+    ("report", EntryKind::TechReport),
+    ("thesis", EntryKind::PhdThesis),
+    ("thesis", EntryKind::MasterThesis),
+    ("unpublished", EntryKind::Unpublished),
+    ("software", SOFTWARE),
+    ("software-code", SOFTWARE),
+    ("software-container", SOFTWARE),
+    ("software-executable", SOFTWARE),
+    ("data", DATASET),
+    ("dataset", DATASET),
+];
+
+/// The fields of an entry that are composed to the keys of a document with the same name.
+const FIELD_KEYS: &[(&str, &str)] = &[
+    ("version", "version"),
+    ("repository", "repository-code"),
+    ("url", "url"),
+    ("license", "license"),
+    ("abstract", "abstract"),
+    ("journal", "journal"),
+    ("volume", "volume"),
+    ("number", "issue"),
+    ("booktitle", "collection-title"),
+    ("isbn", "isbn"),
+    ("edition", "edition"),
+];
+
+impl Format for CitationCff {
+    fn new(val: String) -> Self {
+        Self(val)
+    }
+
+    fn parse(self) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        let mut resolvers = Vec::new();
+        for document in serde_yaml_ng::Deserializer::from_str(&self.0) {
+            let file = CffFile::deserialize(document)
+                .map_err(|e| Error::wrap(ErrorKind::Deserialize, e))?;
+            resolvers.push(match file.preferred_citation {
+                Some(citation) => citation.into_resolver(EntryKind::Other("misc".into())),
+                None => file.work.into_resolver(SOFTWARE),
+            });
+        }
+
+        if resolvers.is_empty() {
+            Err(Error::new(
+                ErrorKind::NoValue,
+                "The citation file does not describe any work",
+            ))
+        } else {
+            Ok(Biblio::try_resolve(resolvers))
+        }
+    }
+
+    fn compose(biblio: &Biblio) -> Self {
+        let mut entries = biblio.entries().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.cite());
+
+        let cff = entries
+            .into_iter()
+            .map(Self::compose_entry)
+            .collect::<Vec<_>>()
+            .join("---\n");
+
+        Self(cff)
+    }
+
+    fn compose_entry(entry: &ast::Entry) -> String {
+        let kind = entry.kind();
+        let reference = reference_yaml(entry);
+
+        let mut document = Mapping::new();
+        insert(&mut document, "cff-version", Some(CFF_VERSION));
+        insert(
+            &mut document,
+            "message",
+            Some("If you use this work, please cite it using the metadata from this file."),
+        );
+        if kind == SOFTWARE || kind == DATASET {
+            document.extend(reference);
+        } else {
+            // the document describes the work itself, so only the required keys are repeated
+            for key in ["title", "authors"] {
+                if let Some(value) = reference.get(key) {
+                    document.insert(key.into(), value.clone());
+                }
+            }
+            document.insert("preferred-citation".into(), Value::Mapping(reference));
+        }
+
+        serde_yaml_ng::to_string(&document).unwrap_or_default()
+    }
+
+    fn unsupported_fields(entry: &ast::Entry) -> Vec<String> {
+        let is_supported = |name: &str| {
+            FIELD_KEYS.iter().any(|(field, _)| *field == name)
+                || matches!(
+                    name,
+                    "title"
+                        | "author"
+                        | "orcid"
+                        | "year"
+                        | "month"
+                        | "day"
+                        | "doi"
+                        | "keywords"
+                        | "pages"
+                        | "publisher"
+                        | "institution"
+                        | "school"
+                )
+        };
+
+        entry
+            .fields()
+            .into_iter()
+            .map(|field| field.name.to_lowercase())
+            .filter(|name| !is_supported(name))
+            .collect()
+    }
+
+    fn raw(self) -> String {
+        self.0
+    }
+
+    fn name() -> &'static str {
+        "Citation File Format"
+    }
+
+    fn ext() -> &'static str {
+        "cff"
+    }
+}
+
+/// Inserts the `value` of the `key` into the `mapping` when there is a value.
+fn insert<V: Into<Value>>(mapping: &mut Mapping, key: &str, value: Option<V>) {
+    if let Some(value) = value {
+        mapping.insert(key.into(), value.into());
+    }
+}
+
+/// The number of the `text`, such as a year, or the `text` when it is not a number.
+fn number_or_text(text: String) -> Value {
+    text.parse::<u64>()
+        .map_or_else(|_| text.into(), Value::from)
+}
+
+/// The keys of a reference of the Citation File Format that describe the `entry`.
+fn reference_yaml(entry: &ast::Entry) -> Mapping {
+    let field = |name| entry.get_field(name).map(|value| (**value).to_owned());
+    let kind = entry.kind();
+    let mut reference = Mapping::new();
+
+    insert(
+        &mut reference,
+        "type",
+        TYPES
+            .iter()
+            .find(|(_, k)| *k == kind)
+            .map_or(Some("generic"), |(name, _)| Some(*name)),
+    );
+    insert(&mut reference, "title", Some(&**entry.title()));
+    let authors = software::authors_of(entry)
+        .into_iter()
+        .map(|person| {
+            let mut author = Mapping::new();
+            insert(&mut author, "family-names", person.family);
+            insert(&mut author, "given-names", person.given);
+            insert(&mut author, "name-particle", person.particle);
+            insert(&mut author, "name-suffix", person.suffix);
+            insert(&mut author, "name", person.name);
+            insert(
+                &mut author,
+                "orcid",
+                person.orcid.as_deref().map(software::orcid_url),
+            );
+            Value::Mapping(author)
+        })
+        .collect::<Vec<_>>();
+    if !authors.is_empty() {
+        reference.insert("authors".into(), Value::Sequence(authors));
+    }
+
+    insert(&mut reference, "doi", field("doi"));
+    match software::date_of(entry) {
+        Some(date) if kind == SOFTWARE || kind == DATASET => {
+            insert(&mut reference, "date-released", Some(date));
+        }
+        _ => {
+            insert(&mut reference, "year", field("year").map(number_or_text));
+            insert(&mut reference, "month", field("month").map(number_or_text));
+        }
+    }
+    for (name, key) in FIELD_KEYS {
+        insert(&mut reference, key, field(name));
+    }
+    if let Some(keywords) = field("keywords") {
+        let keywords = keywords
+            .split(',')
+            .map(|keyword| Value::from(keyword.trim()))
+            .collect();
+        reference.insert("keywords".into(), Value::Sequence(keywords));
+    }
+    if let Some(pages) = field("pages") {
+        let mut pages = pages.split('-').filter(|page| !page.is_empty());
+        insert(&mut reference, "start", pages.next());
+        insert(&mut reference, "end", pages.next());
+    }
+    for (name, key) in [
+        ("publisher", "publisher"),
+        ("institution", "institution"),
+        ("school", "institution"),
+    ] {
+        if let Some(name) = field(name) {
+            let mut entity = Mapping::new();
+            entity.insert("name".into(), name.into());
+            reference.insert(key.into(), Value::Mapping(entity));
+        }
+    }
+
+    reference
+}
+
+/// A document of a citation file, which describes a work and may have the preferred citation of
+/// the work.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CffFile {
+    #[serde(flatten)]
+    work: Reference,
+    preferred_citation: Option<Reference>,
+}
+
+/// A work or a reference to a work, where every key may be missing.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Reference {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    title: Option<String>,
+    #[serde(default)]
+    authors: Vec<CffPerson>,
+    #[serde(default, deserialize_with = "text")]
+    version: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    date_released: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    date_published: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    year: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    month: Option<String>,
+    doi: Option<String>,
+    #[serde(default)]
+    identifiers: Vec<Identifier>,
+    repository_code: Option<String>,
+    url: Option<String>,
+    #[serde(default, deserialize_with = "texts")]
+    license: Option<String>,
+    #[serde(rename = "abstract")]
+    summary: Option<String>,
+    #[serde(default)]
+    keywords: Vec<String>,
+    journal: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    volume: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    issue: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    start: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    end: Option<String>,
+    publisher: Option<Entity>,
+    institution: Option<Entity>,
+    conference: Option<Entity>,
+    collection_title: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    isbn: Option<String>,
+    #[serde(default, deserialize_with = "text")]
+    edition: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CffPerson {
+    family_names: Option<String>,
+    given_names: Option<String>,
+    name_particle: Option<String>,
+    name_suffix: Option<String>,
+    // an entity, such as an organization, only has a name
+    name: Option<String>,
+    orcid: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Entity {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Identifier {
+    #[serde(rename = "type")]
+    kind: String,
+    value: String,
+}
+
+/// A scalar value, which is a string in the Citation File Format but may have been written as a
+/// number, such as `version: 1.2` or `year: 2021`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Scalar {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+}
+
+impl From<Scalar> for String {
+    fn from(scalar: Scalar) -> Self {
+        match scalar {
+            Scalar::Text(text) => text,
+            Scalar::Integer(integer) => integer.to_string(),
+            Scalar::Float(float) => float.to_string(),
+        }
+    }
+}
+
+fn text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(Option::<Scalar>::deserialize(deserializer)?.map(String::from))
+}
+
+/// A scalar or a list of scalars, such as the licenses of a work, joined by commas.
+fn texts<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Texts {
+        One(Scalar),
+        Many(Vec<Scalar>),
+    }
+
+    Ok(match Option::<Texts>::deserialize(deserializer)? {
+        Some(Texts::One(scalar)) => Some(scalar.into()),
+        Some(Texts::Many(scalars)) if !scalars.is_empty() => Some(
+            scalars
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        _ => None,
+    })
+}
+
+impl From<CffPerson> for Person {
+    fn from(person: CffPerson) -> Self {
+        Self {
+            family: person.family_names,
+            given: person.given_names,
+            particle: person.name_particle,
+            suffix: person.name_suffix,
+            name: person.name,
+            orcid: person.orcid.as_deref().map(software::orcid_id),
+        }
+    }
+}
+
+impl Reference {
+    /// The resolver of the work, with the kind of its type or the `default` kind when the type is
+    /// missing.
+    fn into_resolver(self, default: EntryKind<'static>) -> Resolver {
+        let kind = self.kind.as_deref().map_or(default, |kind| {
+            TYPES
+                .iter()
+                .find(|(name, _)| *name == kind)
+                .map_or_else(|| EntryKind::Other("misc".into()), |(_, kind)| kind.clone())
+        });
+        let institution_field = match kind {
+            EntryKind::PhdThesis | EntryKind::MasterThesis => "school",
+            _ => "institution",
+        };
+        let mut resolver = ast::Entry::resolver(kind);
+
+        if let Some(title) = self.title {
+            resolver.set_field("title", title);
+        }
+        let authors = self
+            .authors
+            .into_iter()
+            .map(Person::from)
+            .collect::<Vec<_>>();
+        software::set_authors(&mut resolver, &authors);
+
+        if let Some(date) = self.date_released.or(self.date_published) {
+            software::set_date(&mut resolver, &date);
+        } else {
+            if let Some(year) = self.year {
+                resolver.set_field("year", year);
+            }
+            if let Some(month) = self.month {
+                resolver.set_field("month", month);
+            }
+        }
+
+        let doi = self.doi.or_else(|| {
+            self.identifiers
+                .into_iter()
+                .find(|identifier| identifier.kind == "doi")
+                .map(|identifier| identifier.value)
+        });
+        let pages = super::page_range(self.start, self.end);
+        let keywords = (!self.keywords.is_empty()).then(|| self.keywords.join(", "));
+
+        let fields = [
+            ("doi", doi.as_deref().and_then(doi_of)),
+            ("version", self.version),
+            ("repository", self.repository_code),
+            ("url", self.url),
+            ("license", self.license),
+            ("abstract", self.summary),
+            ("keywords", keywords),
+            ("journal", self.journal),
+            ("volume", self.volume),
+            ("number", self.issue),
+            ("pages", pages),
+            ("publisher", self.publisher.map(|entity| entity.name)),
+            (
+                institution_field,
+                self.institution.map(|entity| entity.name),
+            ),
+            (
+                "booktitle",
+                self.collection_title
+                    .or_else(|| self.conference.map(|entity| entity.name)),
+            ),
+            ("isbn", self.isbn),
+            ("edition", self.edition),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                resolver.set_field(name, value);
+            }
+        }
+
+        resolver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Entry;
+
+    const CFF: &str = r#"cff-version: 1.2.0
+message: "If you use this software, please cite it as below."
+title: seb
+authors:
+  - family-names: Doe
+    given-names: Jane
+    orcid: "https://orcid.org/0000-0002-1825-0097"
+  - family-names: Rossum
+    name-particle: van
+    given-names: Guido
+  - name: "The Seb Developers"
+version: 0.2
+date-released: 2021-03-04
+identifiers:
+  - type: doi
+    value: 10.5281/zenodo.3233986
+repository-code: "https://github.com/mc1098/seb"
+license: MIT
+keywords:
+  - bibliography
+  - bibtex
+"#;
+
+    #[test]
+    fn parse_software_with_the_orcids_of_the_authors() {
+        let biblio = CitationCff::new(CFF.to_owned())
+            .parse()
+            .unwrap()
+            .expect("Software only requires a title");
+        let entries = biblio.into_entries();
+        assert_eq!(1, entries.len());
+        let entry = &entries[0];
+        let field = |name| entry.get_field(name).map(|value| &**value);
+
+        assert_eq!(SOFTWARE, entry.kind());
+        assert_eq!("seb", &**entry.title());
+        assert_eq!(
+            Some("Doe, Jane and van Rossum, Guido and The Seb Developers"),
+            field("author")
+        );
+        assert_eq!(Some("Doe, Jane: 0000-0002-1825-0097"), field("orcid"));
+        assert_eq!(Some("0.2"), field("version"));
+        assert_eq!(Some("2021"), field("year"));
+        assert_eq!(Some("3"), field("month"));
+        assert_eq!(Some("4"), field("day"));
+        assert_eq!(Some("10.5281/zenodo.3233986"), field("doi"));
+        assert_eq!(Some("https://github.com/mc1098/seb"), field("repository"));
+        assert_eq!(Some("bibliography, bibtex"), field("keywords"));
+    }
+
+    #[test]
+    fn preferred_citation_is_the_parsed_entry() {
+        let cff = format!(
+            "{CFF}preferred-citation:
+  type: article
+  title: Managing bibliographies
+  authors:
+    - family-names: Doe
+      given-names: Jane
+  journal: Journal of Open Source Software
+  volume: 6
+  start: 3000
+  end: 3004
+  year: 2021
+"
+        );
+        let biblio = CitationCff::new(cff).parse().unwrap().unwrap();
+        let entries = biblio.into_entries();
+        let entry = &entries[0];
+        let field = |name| entry.get_field(name).map(|value| &**value);
+
+        assert_eq!(EntryKind::Article, entry.kind());
+        assert_eq!("Managing bibliographies", &**entry.title());
+        assert_eq!(Some("Journal of Open Source Software"), field("journal"));
+        assert_eq!(Some("3000--3004"), field("pages"));
+        assert_eq!(Some("2021"), field("year"));
+        assert_eq!(None, field("version"));
+    }
+
+    #[test]
+    fn compose_and_parse_round_trip() {
+        let biblio = CitationCff::new(CFF.to_owned()).parse().unwrap().unwrap();
+
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Article, "doe2021");
+        resolver.set_field("title", "Managing bibliographies");
+        resolver.set_field("author", "Doe, Jane");
+        resolver.set_field("journal", "Journal of Open Source Software");
+        resolver.set_field("year", "2021");
+        resolver.set_field("pages", "3000--3004");
+        let article = resolver.resolve().unwrap();
+
+        for entry in biblio.entries().chain([&article]) {
+            let composed = CitationCff::compose_entry(entry);
+            let reparsed = CitationCff::new(composed).parse().unwrap().unwrap();
+            let reparsed = reparsed.into_entries().remove(0);
+
+            assert_eq!(entry.kind(), reparsed.kind());
+            let mut fields = entry.fields();
+            let mut reparsed_fields = reparsed.fields();
+            fields.sort_by(|a, b| a.name.cmp(&b.name));
+            reparsed_fields.sort_by(|a, b| a.name.cmp(&b.name));
+            assert_eq!(fields, reparsed_fields);
+        }
+    }
+
+    #[test]
+    fn unsupported_fields_are_reported() {
+        let mut resolver = Entry::resolver_with_cite(SOFTWARE, "seb");
+        resolver.set_field("title", "seb");
+        resolver.set_field("version", "0.2");
+        resolver.set_field("note", "A note");
+
+        assert_eq!(
+            vec!["note"],
+            CitationCff::unsupported_fields(&resolver.resolve().unwrap())
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use serde_json::{json, Map, Value};
+
+use crate::{
+    ast::{self, Biblio, BiblioResolver, EntryKind, FieldQuery},
+    dedupe::doi_of,
+    Error, ErrorKind,
+};
+
+use super::{
+    software::{self, Person, DATASET, SOFTWARE},
+    Format,
+};
+
+/// A type wrapper around [`String`] to represent a `codemeta.json` file, the JSON-LD metadata of
+/// software of the `CodeMeta` project.
+///
+/// The file is parsed into a `software` entry, or a `dataset` entry for a `Dataset` type, in the
+/// same way as a [`CitationCff`](super::CitationCff) file. A file may also hold an array of
+/// objects, which are each parsed into an entry.
+///
+/// An entry is composed as an object of the `SoftwareSourceCode` type, or the `Dataset` type for
+/// a `dataset` entry, and any other kind of entry is composed as a `CreativeWork`.
+#[derive(Debug)]
+pub struct CodeMeta(String);
+
+const CONTEXT: &str = "https://w3id.org/codemeta/3.0";
+
+/// The fields of an entry that are composed to the properties with the same value.
+const FIELD_PROPERTIES: &[(&str, &str)] = &[
+    ("version", "version"),
+    ("repository", "codeRepository"),
+    ("url", "url"),
+    ("license", "license"),
+    ("abstract", "description"),
+];
+
+impl Format for CodeMeta {
+    fn new(val: String) -> Self {
+        Self(val)
+    }
+
+    fn parse(self) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        let value = serde_json::from_str::<Value>(&self.0)
+            .map_err(|e| Error::wrap(ErrorKind::Deserialize, e))?;
+        let objects = match value {
+            Value::Array(objects) => objects,
+            object => vec![object],
+        };
+
+        let resolvers = objects
+            .iter()
+            .map(|object| {
+                object.as_object().map(resolver_of).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::Deserialize,
+                        "The CodeMeta metadata is not an object",
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if resolvers.is_empty() {
+            Err(Error::new(
+                ErrorKind::NoValue,
+                "The CodeMeta metadata does not describe any software",
+            ))
+        } else {
+            Ok(Biblio::try_resolve(resolvers))
+        }
+    }
+
+    fn compose(biblio: &Biblio) -> Self {
+        let mut entries = biblio.entries().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.cite());
+
+        let objects = entries
+            .into_iter()
+            .map(object_of)
+            .map(Value::Object)
+            .collect::<Vec<_>>();
+        let value = match <[_; 1]>::try_from(objects) {
+            Ok([object]) => object,
+            Err(objects) => Value::Array(objects),
+        };
+
+        Self(serde_json::to_string_pretty(&value).unwrap_or_default())
+    }
+
+    fn compose_entry(entry: &ast::Entry) -> String {
+        serde_json::to_string_pretty(&object_of(entry)).unwrap_or_default()
+    }
+
+    fn unsupported_fields(entry: &ast::Entry) -> Vec<String> {
+        let is_supported = |name: &str| {
+            FIELD_PROPERTIES.iter().any(|(field, _)| *field == name)
+                || matches!(
+                    name,
+                    "title"
+                        | "author"
+                        | "orcid"
+                        | "year"
+                        | "month"
+                        | "day"
+                        | "doi"
+                        | "keywords"
+                        | "publisher"
+                )
+        };
+
+        entry
+            .fields()
+            .into_iter()
+            .map(|field| field.name.to_lowercase())
+            .filter(|name| !is_supported(name))
+            .collect()
+    }
+
+    fn raw(self) -> String {
+        self.0
+    }
+
+    fn name() -> &'static str {
+        "CodeMeta"
+    }
+
+    fn ext() -> &'static str {
+        "json"
+    }
+}
+
+/// The values of a property that may be a single value or an array of values.
+fn values(value: Option<&Value>) -> Vec<&Value> {
+    match value {
+        Some(Value::Array(values)) => values.iter().collect(),
+        Some(Value::Null) | None => Vec::new(),
+        Some(value) => vec![value],
+    }
+}
+
+/// The text of a property, joining the texts of an array, such as the given names of a person,
+/// with the `separator`.
+fn text(value: Option<&Value>, separator: &str) -> Option<String> {
+    let texts = values(value)
+        .into_iter()
+        .filter_map(|value| match value {
+            Value::String(text) => Some(text.trim().to_owned()),
+            Value::Number(number) => Some(number.to_string()),
+            // an organization, such as a publisher, or a defined term, such as a license
+            Value::Object(object) => object
+                .get("name")
+                .or_else(|| object.get("@id"))
+                .and_then(Value::as_str)
+                .map(str::to_owned),
+            _ => None,
+        })
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>();
+    (!texts.is_empty()).then(|| texts.join(separator))
+}
+
+fn person_of(value: &Value) -> Option<Person> {
+    let Value::Object(object) = value else {
+        return text(Some(value), "").map(|name| Person::from_bibtex_name(&name));
+    };
+
+    let orcid = text(object.get("@id"), "")
+        .into_iter()
+        .chain(text(object.get("identifier"), ""))
+        .find(|id| id.contains("orcid.org/"))
+        .map(|id| software::orcid_id(&id));
+    let family = text(object.get("familyName"), " ");
+    let name = text(object.get("name"), " ");
+    let is_person = object.get("@type").and_then(Value::as_str) != Some("Organization");
+
+    let mut person = match (family, name) {
+        (Some(family), _) => Person {
+            family: Some(family),
+            given: text(object.get("givenName"), " "),
+            ..Person::default()
+        },
+        (None, Some(name)) if is_person => Person::from_bibtex_name(&name),
+        (None, Some(name)) => Person {
+            name: Some(name),
+            ..Person::default()
+        },
+        (None, None) => return None,
+    };
+    person.orcid = orcid;
+    Some(person)
+}
+
+fn resolver_of(object: &Map<String, Value>) -> ast::Resolver {
+    let kind = match object.get("@type").and_then(Value::as_str) {
+        Some("Dataset") => DATASET,
+        Some("CreativeWork") => EntryKind::Other("misc".into()),
+        _ => SOFTWARE,
+    };
+    let mut resolver = ast::Entry::resolver(kind);
+
+    if let Some(title) = text(object.get("name"), " ") {
+        resolver.set_field("title", title);
+    }
+    let authors = values(object.get("author"))
+        .into_iter()
+        .filter_map(person_of)
+        .collect::<Vec<_>>();
+    software::set_authors(&mut resolver, &authors);
+
+    if let Some(date) =
+        text(object.get("datePublished"), "").or_else(|| text(object.get("dateCreated"), ""))
+    {
+        software::set_date(&mut resolver, &date);
+    }
+
+    // the DOI is an identifier, or the `@id` of the software
+    let doi = values(object.get("identifier"))
+        .into_iter()
+        .chain(object.get("@id"))
+        .filter_map(|id| match id {
+            Value::Object(id) => text(id.get("value"), "").or_else(|| text(id.get("@id"), "")),
+            id => text(Some(id), ""),
+        })
+        .find_map(|id| doi_of(&id));
+
+    let fields = [
+        ("doi", doi),
+        (
+            "version",
+            text(object.get("version"), "").or_else(|| text(object.get("softwareVersion"), "")),
+        ),
+        ("repository", text(object.get("codeRepository"), ", ")),
+        ("url", text(object.get("url"), ", ")),
+        ("license", text(object.get("license"), ", ")),
+        ("abstract", text(object.get("description"), " ")),
+        ("keywords", text(object.get("keywords"), ", ")),
+        ("publisher", text(object.get("publisher"), ", ")),
+    ];
+    for (name, value) in fields {
+        if let Some(value) = value {
+            resolver.set_field(name, value);
+        }
+    }
+
+    resolver
+}
+
+fn object_of(entry: &ast::Entry) -> Map<String, Value> {
+    let field = |name| entry.get_field(name).map(|value| (**value).to_owned());
+    let kind = entry.kind();
+    let mut object = Map::new();
+
+    object.insert("@context".to_owned(), CONTEXT.into());
+    let kind = match kind {
+        _ if kind == SOFTWARE => "SoftwareSourceCode",
+        _ if kind == DATASET => "Dataset",
+        _ => "CreativeWork",
+    };
+    object.insert("@type".to_owned(), kind.into());
+    object.insert("name".to_owned(), (**entry.title()).into());
+
+    let authors = software::authors_of(entry)
+        .into_iter()
+        .map(|person| {
+            let mut author = match &person.family {
+                Some(family) => {
+                    let family = person
+                        .particle
+                        .as_ref()
+                        .map_or_else(|| family.clone(), |particle| format!("{particle} {family}"));
+                    let mut author = json!({ "@type": "Person", "familyName": family });
+                    if let Some(given) = &person.given {
+                        author["givenName"] = given.as_str().into();
+                    }
+                    author
+                }
+                None => json!({ "@type": "Organization", "name": person.name }),
+            };
+            if let Some(orcid) = &person.orcid {
+                author["@id"] = software::orcid_url(orcid).into();
+            }
+            author
+        })
+        .collect::<Vec<_>>();
+    if !authors.is_empty() {
+        object.insert("author".to_owned(), authors.into());
+    }
+
+    if let Some(doi) = field("doi") {
+        object.insert(
+            "identifier".to_owned(),
+            format!("https://doi.org/{doi}").into(),
+        );
+    }
+    if let Some(date) = software::date_of(entry) {
+        object.insert("datePublished".to_owned(), date.into());
+    }
+    for (name, property) in FIELD_PROPERTIES {
+        if let Some(value) = field(name) {
+            object.insert((*property).to_owned(), value.into());
+        }
+    }
+    if let Some(keywords) = field("keywords") {
+        let keywords = keywords.split(',').map(str::trim).collect::<Vec<_>>();
+        object.insert("keywords".to_owned(), keywords.into());
+    }
+    if let Some(publisher) = field("publisher") {
+        object.insert(
+            "publisher".to_owned(),
+            json!({ "@type": "Organization", "name": publisher }),
+        );
+    }
+
+    object
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODEMETA: &str = r#"{
+        "@context": "https://w3id.org/codemeta/3.0",
+        "@type": "SoftwareSourceCode",
+        "name": "seb",
+        "author": [
+            {
+                "@type": "Person",
+                "@id": "https://orcid.org/0000-0002-1825-0097",
+                "givenName": "Jane",
+                "familyName": "Doe"
+            },
+            { "@type": "Organization", "name": "The Seb Developers" }
+        ],
+        "version": "0.2.0",
+        "datePublished": "2021-03-04",
+        "identifier": "https://doi.org/10.5281/zenodo.3233986",
+        "codeRepository": "https://github.com/mc1098/seb",
+        "license": "https://spdx.org/licenses/MIT",
+        "keywords": ["bibliography", "bibtex"]
+    }"#;
+
+    #[test]
+    fn parse_software_with_the_orcids_of_the_authors() {
+        let biblio = CodeMeta::new(CODEMETA.to_owned())
+            .parse()
+            .unwrap()
+            .expect("Software only requires a title");
+        let entries = biblio.into_entries();
+        let entry = &entries[0];
+        let field = |name| entry.get_field(name).map(|value| &**value);
+
+        assert_eq!(SOFTWARE, entry.kind());
+        assert_eq!("seb", &**entry.title());
+        assert_eq!(Some("Doe, Jane and The Seb Developers"), field("author"));
+        assert_eq!(Some("Doe, Jane: 0000-0002-1825-0097"), field("orcid"));
+        assert_eq!(Some("0.2.0"), field("version"));
+        assert_eq!(Some("2021"), field("year"));
+        assert_eq!(Some("10.5281/zenodo.3233986"), field("doi"));
+        assert_eq!(Some("https://github.com/mc1098/seb"), field("repository"));
+        assert_eq!(Some("bibliography, bibtex"), field("keywords"));
+    }
+
+    #[test]
+    fn compose_and_parse_round_trip() {
+        let biblio = CodeMeta::new(CODEMETA.to_owned()).parse().unwrap().unwrap();
+        let composed = CodeMeta::compose(&biblio).raw();
+        let reparsed = CodeMeta::new(composed).parse().unwrap().unwrap();
+
+        assert_eq!(biblio, reparsed);
+    }
+}
@@ -4,6 +4,11 @@
 use std::marker::PhantomData;
 
 mod bibtex;
+#[cfg(feature = "cff")]
+mod cff;
+mod codemeta;
+mod ris;
+mod software;
 
 use crate::{
     ast::{Biblio, BiblioResolver, Entry},
@@ -12,6 +17,10 @@ use crate::{
 
 #[cfg(feature = "bibtex")]
 pub use bibtex::BibTex;
+#[cfg(feature = "cff")]
+pub use cff::CitationCff;
+pub use codemeta::CodeMeta;
+pub use ris::Ris;
 
 // TODO: Consider defining Format so that it can wrap T types, where T: std::io::Write +
 // std::io::Read. This would allow Format to trivially uphold the same type bounds as T and would
@@ -46,6 +55,16 @@ pub trait Format {
     /// [`Format`] must correctly represent every valid [`Entry`].
     fn compose_entry(entry: &Entry) -> String;
 
+    /// The names of the fields of an [`Entry`] that this [`Format`] cannot represent.
+    ///
+    /// These fields are dropped when the [`Entry`] is composed to this [`Format`], by default a
+    /// [`Format`] can represent every field.
+    #[must_use]
+    fn unsupported_fields(entry: &Entry) -> Vec<String> {
+        let _ = entry;
+        Vec::new()
+    }
+
     /// The current [`Format`] in a raw [`String`].
     ///
     /// Most [`Format`]s are likely to be type wrappers around [`String`] so this is a method to
@@ -147,7 +166,7 @@ impl<F: Format> Default for FormatString<F> {
 impl<F: Format> FormatString<F> {
     /// Construct a new instance by wrapping an existing [`String`].
     #[must_use]
-    pub fn new(val: String) -> Self {
+    pub const fn new(val: String) -> Self {
         Self {
             inner: val,
             _format: PhantomData,
@@ -177,3 +196,28 @@ impl<F: Format> Writer for FormatString<F> {
         Ok(())
     }
 }
+
+/// The `pages` field of the `first` and `last` page, such as `12--34`, or only the first page when
+/// the last page is missing or the same.
+pub(crate) fn page_range(first: Option<String>, last: Option<String>) -> Option<String> {
+    match (first, last) {
+        (Some(first), Some(last)) if first != last => Some(format!("{first}--{last}")),
+        (first, _) => first,
+    }
+}
+
+/// Parses a month as a number or an English month name, as used in the `month` field.
+pub(crate) fn parse_month(month: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+
+    let month = month.trim().to_lowercase();
+    if let Ok(month) = month.parse() {
+        return (1..=12).contains(&month).then_some(month);
+    }
+    MONTHS
+        .iter()
+        .position(|name| month.starts_with(name))
+        .and_then(|i| u32::try_from(i + 1).ok())
+}
@@ -0,0 +1,427 @@
+use crate::{
+    ast::{self, Biblio, BiblioResolver, EntryKind, FieldQuery},
+    Error, ErrorKind,
+};
+
+use super::Format;
+
+/// A type wrapper around [`String`] to represent a RIS format string.
+///
+/// Each RIS record is a list of `TAG  - value` lines starting with a `TY` tag for the kind of the
+/// record and ending with an `ER` tag. Tags that can be used more than once, such as `AU` for each
+/// author or `KW` for each keyword, are joined into a single field of the [`Entry`][E].
+///
+/// [E]: ast::Entry
+#[derive(Debug)]
+pub struct Ris(String);
+
+/// Tags that map directly to a single field of every kind of entry.
+const TAGS: &[(&str, &str)] = &[
+    ("TI", "title"),
+    ("PY", "year"),
+    ("VL", "volume"),
+    ("IS", "number"),
+    ("CY", "address"),
+    ("DO", "doi"),
+    ("UR", "url"),
+    ("AB", "abstract"),
+    ("N1", "note"),
+    ("ET", "edition"),
+    ("T3", "series"),
+    ("LA", "language"),
+    ("Y2", "urldate"),
+];
+
+/// Tags that are used more than once in a record, with the field they are joined into and the
+/// separator used to join them.
+const LIST_TAGS: &[(&str, &str, &str)] = &[
+    ("AU", "author", " and "),
+    ("A2", "editor", " and "),
+    ("KW", "keywords", ", "),
+];
+
+impl Format for Ris {
+    fn new(val: String) -> Self {
+        Self(val)
+    }
+
+    fn parse(self) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        let records = parse_records(&self.0)?;
+        let resolvers = records.into_iter().map(record_resolver).collect();
+        Ok(Biblio::try_resolve(resolvers))
+    }
+
+    fn compose(biblio: &Biblio) -> Self {
+        let mut entries = biblio.entries().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.cite());
+
+        let ris = entries
+            .into_iter()
+            .map(Self::compose_entry)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Self(ris)
+    }
+
+    fn compose_entry(entry: &ast::Entry) -> String {
+        let mut lines = vec![
+            tag_line("TY", compose_type(entry)),
+            tag_line("ID", entry.cite()),
+        ];
+
+        for (tag, name, separator) in LIST_TAGS {
+            if let Some(value) = entry.get_field(name) {
+                lines.extend(
+                    value
+                        .split(separator)
+                        .map(|item| tag_line(tag, item.trim())),
+                );
+            }
+        }
+
+        for (tag, name) in TAGS.iter().chain(&kind_tags(&entry.kind())) {
+            if let Some(value) = entry.get_field(name) {
+                lines.push(tag_line(tag, value));
+            }
+        }
+
+        if let Some((start, end)) = entry.get_field("pages").map(|pages| split_pages(pages)) {
+            lines.push(tag_line("SP", start));
+            if let Some(end) = end {
+                lines.push(tag_line("EP", end));
+            }
+        }
+
+        if let Some(month) = entry.get_field("month") {
+            let year = entry.get_field("year").map_or("", |year| year);
+            // a month that is not a number or month name, such as `Spring`, is left out
+            match super::parse_month(month) {
+                Some(month) => {
+                    let day = entry.get_field("day").map_or("", |day| day);
+                    lines.push(tag_line("DA", &format!("{year}/{month:02}/{day}/")));
+                }
+                None if !year.is_empty() => lines.push(tag_line("DA", &format!("{year}///"))),
+                None => {}
+            }
+        }
+
+        if let Some(isbn) = entry.get_field("isbn").or_else(|| entry.get_field("issn")) {
+            lines.push(tag_line("SN", isbn));
+        }
+
+        lines.push("ER  - \n".to_owned());
+        lines.join("\n")
+    }
+
+    fn unsupported_fields(entry: &ast::Entry) -> Vec<String> {
+        let kind_tags = kind_tags(&entry.kind());
+        let is_supported = |name: &str| {
+            TAGS.iter().chain(&kind_tags).any(|(_, n)| *n == name)
+                || LIST_TAGS.iter().any(|(_, n, _)| *n == name)
+                || matches!(name, "pages" | "month" | "day" | "isbn")
+                || (name == "issn" && entry.get_field("isbn").is_none())
+        };
+
+        entry
+            .fields()
+            .into_iter()
+            .map(|field| field.name.to_lowercase())
+            .filter(|name| !is_supported(name))
+            .collect()
+    }
+
+    fn raw(self) -> String {
+        self.0
+    }
+
+    fn name() -> &'static str {
+        "RIS"
+    }
+
+    fn ext() -> &'static str {
+        "ris"
+    }
+}
+
+fn tag_line(tag: &str, value: &str) -> String {
+    format!("{tag}  - {value}")
+}
+
+/// The tags whose field depends on the kind of entry, the container (`T2`) is the journal of an
+/// article but the book title of anything else, and the publisher (`PB`) is the school of a
+/// thesis and the institution of a report.
+const fn kind_tags(kind: &EntryKind<'_>) -> [(&'static str, &'static str); 2] {
+    let container = match kind {
+        EntryKind::Article => "journal",
+        _ => "book_title",
+    };
+    let publisher = match kind {
+        EntryKind::MasterThesis | EntryKind::PhdThesis => "school",
+        EntryKind::TechReport => "institution",
+        EntryKind::Manual => "organization",
+        _ => "publisher",
+    };
+
+    [("T2", container), ("PB", publisher)]
+}
+
+const fn compose_type(entry: &ast::Entry) -> &'static str {
+    match entry {
+        ast::Entry::Article(_) => "JOUR",
+        ast::Entry::Book(_) => "BOOK",
+        ast::Entry::Booklet(_) => "PAMP",
+        ast::Entry::BookChapter(_) | ast::Entry::BookPages(_) | ast::Entry::BookSection(_) => {
+            "CHAP"
+        }
+        ast::Entry::InProceedings(_) => "CPAPER",
+        ast::Entry::MasterThesis(_) | ast::Entry::PhdThesis(_) => "THES",
+        ast::Entry::Proceedings(_) => "CONF",
+        ast::Entry::TechReport(_) => "RPRT",
+        ast::Entry::Unpublished(_) => "UNPB",
+        ast::Entry::Manual(_) | ast::Entry::Other(_) => "GEN",
+    }
+}
+
+/// Splits a page range such as `10-20` or `10--20` into the start and end pages.
+fn split_pages(pages: &str) -> (&str, Option<&str>) {
+    match pages.split_once('-') {
+        Some((start, end)) => (start.trim(), Some(end.trim_start_matches('-').trim())),
+        None => (pages.trim(), None),
+    }
+}
+
+/// A single RIS record as a list of tags and values in the order they were found.
+type Record = Vec<(String, String)>;
+
+fn parse_records(ris: &str) -> Result<Vec<Record>, Error> {
+    let mut records = Vec::new();
+    let mut record: Option<Record> = None;
+
+    for (number, line) in ris.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((tag, value)) = split_tag_line(line) else {
+            // a line without a tag continues the value of the previous tag
+            match record.as_mut().and_then(|record| record.last_mut()) {
+                Some((_, previous)) => {
+                    previous.push(' ');
+                    previous.push_str(line.trim());
+                    continue;
+                }
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::Deserialize,
+                        format!("Unable to parse line {} as RIS: '{line}'", number + 1),
+                    ))
+                }
+            }
+        };
+
+        match (tag, record.as_mut()) {
+            ("TY", None) => record = Some(vec![(tag.to_owned(), value.to_owned())]),
+            ("ER", Some(_)) => records.extend(record.take()),
+            (_, Some(record)) => record.push((tag.to_owned(), value.to_owned())),
+            (_, None) => {
+                return Err(Error::new(
+                    ErrorKind::Deserialize,
+                    format!(
+                        "Unable to parse RIS: expected a TY tag on line {} but found {tag}",
+                        number + 1
+                    ),
+                ))
+            }
+        }
+    }
+
+    if record.is_some() {
+        return Err(Error::new(
+            ErrorKind::Deserialize,
+            "Unable to parse RIS: the last record has no ER tag",
+        ));
+    }
+
+    Ok(records)
+}
+
+fn split_tag_line(line: &str) -> Option<(&str, &str)> {
+    let tag = line.get(..2)?;
+    let rest = line.get(2..)?.trim_start_matches(' ').strip_prefix('-')?;
+    tag.chars()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        .then(|| (tag, rest.trim()))
+}
+
+fn parse_kind(record: &Record) -> EntryKind<'static> {
+    let ty = record.first().map_or("GEN", |(_, ty)| ty.as_str());
+    let work_type = record
+        .iter()
+        .find(|(tag, _)| tag == "M3")
+        .map(|(_, value)| value.to_lowercase());
+
+    match ty {
+        "JOUR" | "JFULL" | "MGZN" | "NEWS" | "EJOUR" => EntryKind::Article,
+        "BOOK" | "EBOOK" | "EDBOOK" => EntryKind::Book,
+        "PAMP" => EntryKind::Booklet,
+        "CHAP" | "ECHAP" => EntryKind::BookSection,
+        "CPAPER" => EntryKind::InProceedings,
+        "CONF" => EntryKind::Proceedings,
+        "THES" if work_type.is_some_and(|t| t.contains("master")) => EntryKind::MasterThesis,
+        "THES" => EntryKind::PhdThesis,
+        "RPRT" => EntryKind::TechReport,
+        "UNPB" => EntryKind::Unpublished,
+        _ => EntryKind::Manual,
+    }
+}
+
+fn record_resolver(record: Record) -> ast::Resolver {
+    let kind = parse_kind(&record);
+    let mut resolver = match record.iter().find(|(tag, _)| tag == "ID") {
+        Some((_, cite)) => ast::Entry::resolver_with_cite(kind.clone(), cite.as_str()),
+        None => ast::Entry::resolver(kind.clone()),
+    };
+
+    for (tag, name, separator) in LIST_TAGS {
+        let items = record
+            .iter()
+            .filter(|(t, _)| t == tag)
+            .map(|(_, value)| value.as_str())
+            .collect::<Vec<_>>();
+        if !items.is_empty() {
+            resolver.set_field(name, items.join(separator));
+        }
+    }
+
+    let kind_tags = kind_tags(&kind);
+    let mut start_page = None;
+    let mut end_page = None;
+
+    for (tag, value) in record {
+        let field = TAGS
+            .iter()
+            .chain(&kind_tags)
+            .find(|(t, _)| *t == tag)
+            .map(|(_, name)| *name);
+
+        match (tag.as_str(), field) {
+            (_, Some(name)) => resolver.set_field(name, value),
+            ("T1", _) => resolver.set_field("title", value),
+            ("Y1", _) => resolver.set_field("year", value.split('/').next().unwrap_or_default()),
+            ("JO" | "JF", _) => resolver.set_field("journal", value),
+            ("BT", _) => resolver.set_field("book_title", value),
+            ("SP", _) => start_page = Some(value),
+            ("EP", _) => end_page = Some(value),
+            ("SN", _) => resolver.set_field("isbn", value),
+            ("DA", _) => {
+                let mut parts = value.split('/').map(str::trim);
+                let names = ["year", "month", "day"];
+                for (name, part) in names.iter().zip(&mut parts) {
+                    if !part.is_empty() {
+                        resolver.set_field(name, part.trim_start_matches('0'));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match (start_page, end_page) {
+        (Some(start), Some(end)) => resolver.set_field("pages", format!("{start}-{end}")),
+        (Some(start), None) => resolver.set_field("pages", start),
+        _ => {}
+    }
+
+    resolver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Entry;
+
+    const RIS: &str = "TY  - JOUR
+ID  - knuth1984
+AU  - Knuth, Donald E.
+AU  - Other, Author
+TI  - Literate
+  Programming
+T2  - The Computer Journal
+PY  - 1984
+VL  - 27
+SP  - 97
+EP  - 111
+DO  - 10.1093/comjnl/27.2.97
+ER  -
+";
+
+    #[test]
+    fn parse_journal_record() {
+        let biblio = Ris::new(RIS.to_owned())
+            .parse()
+            .unwrap()
+            .expect("Article has all required fields");
+        let entry = biblio.get("knuth1984").expect("Cite taken from ID tag");
+
+        assert_eq!(EntryKind::Article, entry.kind());
+        assert_eq!("Literate Programming", &**entry.title());
+        assert_eq!(
+            Some("Knuth, Donald E. and Other, Author"),
+            entry.get_field("author").map(|v| &**v)
+        );
+        assert_eq!(
+            Some("The Computer Journal"),
+            entry.get_field("journal").map(|v| &**v)
+        );
+        assert_eq!(Some("97-111"), entry.get_field("pages").map(|v| &**v));
+    }
+
+    #[test]
+    fn compose_and_parse_round_trip() {
+        let biblio = Ris::new(RIS.to_owned()).parse().unwrap().unwrap();
+        let composed = Ris::compose(&biblio).raw();
+        let reparsed = Ris::new(composed).parse().unwrap().unwrap();
+
+        assert_eq!(biblio, reparsed);
+    }
+
+    #[test]
+    fn missing_end_of_record_is_an_error() {
+        let res = Ris::new("TY  - JOUR\nTI  - Title\n".to_owned()).parse();
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn unsupported_fields_are_reported() {
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+        resolver.title("Title");
+        resolver.set_field("organization", "Org");
+        resolver.set_field("howpublished", "Online");
+        let entry = resolver.resolve().unwrap();
+
+        assert_eq!(vec!["howpublished"], Ris::unsupported_fields(&entry));
+    }
+
+    #[test]
+    fn month_names_are_composed_as_numbers() {
+        let date = |month: &str| {
+            let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "cite");
+            resolver.title("Title");
+            resolver.set_field("year", "2020");
+            resolver.set_field("month", month);
+            let entry = resolver.resolve().unwrap();
+            Ris::compose_entry(&entry)
+                .lines()
+                .find(|line| line.starts_with("DA"))
+                .map(str::to_owned)
+        };
+
+        assert_eq!(Some("DA  - 2020/01//".to_owned()), date("jan"));
+        assert_eq!(Some("DA  - 2020/03//".to_owned()), date("March"));
+        assert_eq!(Some("DA  - 2020/11//".to_owned()), date("11"));
+        assert_eq!(Some("DA  - 2020///".to_owned()), date("Spring"));
+    }
+}
@@ -0,0 +1,228 @@
+//! The parts of the mapping between entries and the metadata files of software, such as
+//! `CITATION.cff` and `codemeta.json`, that are shared by the formats of those files.
+
+use crate::{
+    ast::{Entry, EntryKind, FieldQuery, QuotedString, Resolver},
+    render::Name,
+};
+
+const ORCID_URL: &str = "https://orcid.org/";
+
+/// The kind of entry of software.
+pub(super) const SOFTWARE: EntryKind<'static> =
+    EntryKind::Other(std::borrow::Cow::Borrowed("software"));
+/// The kind of entry of a dataset.
+pub(super) const DATASET: EntryKind<'static> =
+    EntryKind::Other(std::borrow::Cow::Borrowed("dataset"));
+
+/// A person or an organization that is an author of a work.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct Person {
+    pub(super) family: Option<String>,
+    pub(super) given: Option<String>,
+    /// The particle of the family name, such as `van` or `de`.
+    pub(super) particle: Option<String>,
+    pub(super) suffix: Option<String>,
+    /// The name of an organization, or of a person whose name is not split into parts.
+    pub(super) name: Option<String>,
+    /// The ORCID iD of the person, without the `https://orcid.org/` prefix.
+    pub(super) orcid: Option<String>,
+}
+
+impl Person {
+    /// The name of the person as it is written in the `author` field, such as
+    /// `van Rossum, Guido`.
+    pub(super) fn bibtex_name(&self) -> Option<String> {
+        let Some(family) = &self.family else {
+            return self.name.clone();
+        };
+
+        let mut name = self
+            .particle
+            .as_ref()
+            .map_or_else(|| family.clone(), |particle| format!("{particle} {family}"));
+        if let Some(suffix) = &self.suffix {
+            name = format!("{name}, {suffix}");
+        }
+        Some(match &self.given {
+            Some(given) => format!("{name}, {given}"),
+            None => name,
+        })
+    }
+
+    /// The person of a name of the `author` field, in either the `First von Last` or the
+    /// `von Last, Jr, First` form. A name in braces is the name of an organization.
+    pub(super) fn from_bibtex_name(name: &str) -> Self {
+        let name = name.trim();
+        if let Some(organization) = name.strip_prefix('{').and_then(|n| n.strip_suffix('}')) {
+            return Self {
+                name: Some(organization.to_owned()),
+                ..Self::default()
+            };
+        }
+
+        // a single word is a name that is not split into parts
+        if !name.contains(',') && name.split_whitespace().nth(1).is_none() {
+            return Self {
+                name: Some(name.to_owned()),
+                ..Self::default()
+            };
+        }
+
+        let Name {
+            family,
+            given,
+            suffix,
+        } = Name::parse(name);
+        Self::split_particle(
+            &family,
+            Some(given).filter(|g| !g.is_empty()),
+            Some(suffix).filter(|s| !s.is_empty()),
+        )
+    }
+
+    /// The person with the `family` name split into the particle and family name.
+    fn split_particle(family: &str, given: Option<String>, suffix: Option<String>) -> Self {
+        let words = family.split(' ').collect::<Vec<_>>();
+        let at = words
+            .iter()
+            .position(|word| !word.starts_with(char::is_lowercase))
+            .unwrap_or(words.len() - 1);
+        Self {
+            family: Some(words[at..].join(" ")),
+            given,
+            particle: (at > 0).then(|| words[..at].join(" ")),
+            suffix,
+            ..Self::default()
+        }
+    }
+}
+
+/// Sets the `author` field to the names of the `authors`, quoting the names of organizations,
+/// and the `orcid` field to the ORCID iDs of the authors that have one, as `name: iD` pairs such
+/// as `Doe, Jane: 0000-0002-1825-0097`.
+pub(super) fn set_authors(resolver: &mut Resolver, authors: &[Person]) {
+    let names = authors
+        .iter()
+        .filter_map(|person| person.bibtex_name().map(|name| (name, person)))
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        return;
+    }
+
+    let orcids = names
+        .iter()
+        .filter_map(|(name, person)| {
+            person
+                .orcid
+                .as_ref()
+                .map(|orcid| format!("{name}: {orcid}"))
+        })
+        .collect::<Vec<_>>();
+    let mut parts = Vec::with_capacity(names.len() * 2);
+    for (name, person) in names {
+        if !parts.is_empty() {
+            parts.push((false, " and ".to_owned()));
+        }
+        parts.push((person.family.is_none(), name));
+    }
+    resolver.set_field("author", QuotedString::from_parts(parts));
+    if !orcids.is_empty() {
+        resolver.set_field("orcid", orcids.join(" and "));
+    }
+}
+
+/// The authors of the `entry`, with the ORCID iDs of its `orcid` field.
+pub(super) fn authors_of(entry: &Entry) -> Vec<Person> {
+    let orcids = entry
+        .get_field("orcid")
+        .map(|orcids| {
+            orcids
+                .split(" and ")
+                .filter_map(|pair| pair.rsplit_once(':'))
+                .map(|(name, orcid)| (name.trim().to_owned(), orcid.trim().to_owned()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let Some(authors) = entry.get_field("author") else {
+        return Vec::new();
+    };
+
+    // the names of organizations are quoted so that they are not split
+    let authors = authors.map_quoted(|name| format!("{{{name}}}"));
+    let mut names = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (at, c) in authors.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ if depth == 0 && authors[at..].starts_with(" and ") => {
+                names.push(&authors[start..at]);
+                start = at + " and ".len();
+            }
+            _ => {}
+        }
+    }
+    names.push(&authors[start..]);
+
+    names
+        .into_iter()
+        .map(|name| {
+            let mut person = Person::from_bibtex_name(name);
+            let name = person.bibtex_name();
+            person.orcid = orcids
+                .iter()
+                .find(|(n, _)| Some(n) == name.as_ref())
+                .map(|(_, orcid)| orcid.clone());
+            person
+        })
+        .collect()
+}
+
+/// The ORCID iD of an `orcid`, which may be the url of the iD.
+pub(super) fn orcid_id(orcid: &str) -> String {
+    let orcid = orcid.trim();
+    orcid
+        .strip_prefix(ORCID_URL)
+        .or_else(|| orcid.strip_prefix("http://orcid.org/"))
+        .unwrap_or(orcid)
+        .to_owned()
+}
+
+/// The url of the ORCID iD.
+pub(super) fn orcid_url(orcid: &str) -> String {
+    format!("{ORCID_URL}{orcid}")
+}
+
+/// Sets the `year`, `month` and `day` fields of a `date`, such as `2021-03-04`.
+pub(super) fn set_date(resolver: &mut Resolver, date: &str) {
+    // any time of the date is ignored
+    let date = date.trim().split('T').next().unwrap_or_default();
+    let names = ["year", "month", "day"];
+    for (name, part) in names.iter().zip(date.splitn(3, '-')) {
+        let part = if *name == "year" {
+            part
+        } else {
+            part.trim_start_matches('0')
+        };
+        if !part.is_empty() {
+            resolver.set_field(name, part);
+        }
+    }
+}
+
+/// The date of the `year`, `month` and `day` fields of the `entry`, such as `2021-03-04`, or
+/// `None` when the entry does not have all three fields.
+pub(super) fn date_of(entry: &Entry) -> Option<String> {
+    let part = |name| {
+        entry
+            .get_field(name)
+            .and_then(|value| value.trim().parse::<u16>().ok())
+    };
+    Some(format!(
+        "{:04}-{:02}-{:02}",
+        part("year")?,
+        part("month")?,
+        part("day")?
+    ))
+}
@@ -8,24 +8,44 @@
 )]
 #![warn(missing_docs, rust_2018_idioms)]
 #![allow(clippy::module_name_repetitions)]
+// lints added to clippy after the tests were written
+#![cfg_attr(
+    test,
+    allow(
+        clippy::uninlined_format_args,
+        clippy::manual_string_new,
+        clippy::needless_borrows_for_generic_args
+    )
+)]
 #![doc = include_str!("../README.md")]
 
-mod api;
+pub mod api;
 pub mod ast;
+pub mod dedupe;
 mod error;
 #[cfg(feature = "file")]
 pub mod file;
 pub mod format;
+pub mod provider;
+pub mod render;
+pub mod search;
 
+pub use api::{EntryStub, TitleQuery};
 use ast::{Biblio, BiblioResolver};
 pub use error::{Error, ErrorKind};
 
+#[cfg(feature = "async")]
+pub use api::nonblocking;
+
 use format::Format;
 use log::trace;
 
-type Client = reqwest::blocking::Client;
+type Client = api::CachedClient<api::HttpClient>;
 
-/// Search bibliographic entries by `doi` using the default API.
+/// Search bibliographic entries by `doi` using the API of the registration agency of the `doi`.
+///
+/// DOIs registered with `DataCite`, such as those of Zenodo and Figshare, are looked up using the
+/// `DataCite` API and any other DOI is looked up using the `CrossRef` API.
 ///
 /// Searching by `doi` should only return a single [Entry][E] but a [`Vec`] is used to provide a
 /// consistent API across all `entries_by_*` functions.
@@ -40,7 +60,7 @@ type Client = reqwest::blocking::Client;
 #[inline]
 pub fn entries_by_doi(doi: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
     trace!("Search entries by doi of '{doi}'");
-    api::cross_ref::get_entries_by_doi::<Client>(doi)
+    api::doi::get_entries_by_doi(&Client::default(), doi)
 }
 
 /// Search bibliographic entries by `isbn` using the default API.
@@ -57,7 +77,7 @@ pub fn entries_by_doi(doi: &str) -> Result<Result<Biblio, BiblioResolver>, Error
 #[inline]
 pub fn entries_by_isbn(isbn: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
     trace!("Search entries by ISBN of '{isbn}'");
-    api::google_books::get_entries_by_isbn::<Client>(isbn)
+    api::google_books::get_entries_by_isbn(&Client::default(), isbn)
 }
 
 /// Search bibliographic entries by `IETF RFC number`.
@@ -94,8 +114,21 @@ pub fn entries_by_url<F: Format>(url: &str) -> Result<Result<Biblio, BiblioResol
     api::format_api::get_entry_by_url::<Client, F>(url)
 }
 
+/// Search the [`EntryStub`]s of the first page of entries that have a title of `title`.
+///
+/// # Errors
+///
+/// An `Err` is returned when no entries are found with the `title`.
+pub fn entry_stubs_by_title(title: &str) -> Result<Vec<EntryStub>, Error> {
+    entry_stubs_by_query(&TitleQuery::new(title))
+}
+
+/// Search the [`EntryStub`]s of the page of entries that match the `query`.
+///
 /// # Errors
-pub fn entry_stubs_by_title(title: &str) -> Result<Vec<(String, String)>, Error> {
-    trace!("Search entries that have a title of '{title}'");
-    api::cross_ref::get_entry_stubs_by_title::<Client>(title)
+///
+/// An `Err` is returned when no entries match the `query`.
+pub fn entry_stubs_by_query(query: &TitleQuery) -> Result<Vec<EntryStub>, Error> {
+    trace!("Search entries that have a title of '{}'", query.title);
+    api::cross_ref::get_entry_stubs(&Client::default(), query)
 }
@@ -0,0 +1,790 @@
+//! Providers of bibliographic entries from online sources.
+//!
+//! A [`Provider`] looks up entries by an identifier, such as a DOI, and may search for entries
+//! that match a query. The [`Registry`] holds the built-in providers and any providers that are
+//! registered by a downstream crate, so that providers can be selected by name.
+//!
+//! [`Registry::search`] sends a [`TitleQuery`] to every provider that can search and merges the
+//! results, so that an entry found by more than one provider is only returned once.
+//!
+//! ```no_run
+//! use seb::provider::{IdKind, Registry};
+//!
+//! let registry = Registry::default().select(&["crossref"])?;
+//! let entries = registry.lookup(IdKind::Doi, "10.1145/3290380")?;
+//! # Ok::<(), seb::Error>(())
+//! ```
+
+use std::{cmp::Ordering, fmt, marker::PhantomData, str::FromStr};
+
+use crate::{
+    api::{self, doi::Agency, Client, TitleQuery},
+    ast::{Biblio, BiblioResolver, Entry, FieldQuery, Resolver},
+    dedupe::{normalize_doi, normalize_isbn},
+    search::tokenize,
+    Error, ErrorKind,
+};
+
+/// The number of results of a search that are looked up by the built-in providers.
+const SEARCH_LIMIT: usize = 5;
+
+/// The kinds of identifiers that entries are looked up by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IdKind {
+    /// A Digital Object Identifier, such as `10.1145/3290380`.
+    Doi,
+    /// An International Standard Book Number, with or without hyphens.
+    Isbn,
+    /// The number of an IETF Request for Comments, such as `7230`.
+    Rfc,
+    /// The url of a web page, such as the landing page of an article.
+    Url,
+}
+
+impl IdKind {
+    /// The name of the kind of identifier, which is the field that holds the identifier in an
+    /// entry for the DOI, ISBN and url.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Doi => "doi",
+            Self::Isbn => "isbn",
+            Self::Rfc => "rfc",
+            Self::Url => "url",
+        }
+    }
+}
+
+impl fmt::Display for IdKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for IdKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Doi, Self::Isbn, Self::Rfc, Self::Url]
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::Deserialize,
+                    format!("'{s}' is not a kind of identifier, use 'doi', 'isbn', 'rfc' or 'url'"),
+                )
+            })
+    }
+}
+
+/// An entry found by [`Registry::search`], which may need missing fields before it can be added.
+#[derive(Debug)]
+pub struct Found {
+    /// The entry, or the resolver of an entry that is missing required fields.
+    pub entry: Result<Entry, Resolver>,
+    /// The names of the providers that found the entry, in the order of the registry.
+    pub providers: Vec<String>,
+    /// How well the entry matches the query, higher is better.
+    pub score: f64,
+}
+
+impl Found {
+    /// The fields of the entry or resolver.
+    #[must_use]
+    pub fn fields(&self) -> &dyn FieldQuery {
+        match &self.entry {
+            Ok(entry) => entry,
+            Err(resolver) => resolver,
+        }
+    }
+
+    /// The normalized DOI, or else ISBN, that identifies the entry across providers.
+    fn id(&self) -> Option<String> {
+        let fields = self.fields();
+        fields
+            .get_field("doi")
+            .map(|doi| normalize_doi(doi))
+            .or_else(|| {
+                fields
+                    .get_field("isbn")
+                    .and_then(|isbn| normalize_isbn(isbn))
+            })
+    }
+
+    /// The year from the leading digits of the `year` field.
+    fn year(&self) -> Option<u16> {
+        let year = self.fields().get_field("year")?;
+        year.trim()
+            .split(|c: char| !c.is_ascii_digit())
+            .next()
+            .and_then(|digits| digits.parse().ok())
+    }
+}
+
+/// A source of bibliographic entries.
+pub trait Provider {
+    /// The unique name of the provider, such as `crossref`.
+    fn name(&self) -> &str;
+
+    /// The kinds of identifiers that the provider can look up.
+    fn id_kinds(&self) -> &[IdKind];
+
+    /// Looks up the entries with the `id` of the `kind`.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when the provider does not support the `kind` of identifier.
+    /// An `Err` is returned when no entry is found for the `id`.
+    fn lookup(&self, kind: IdKind, id: &str) -> Result<Result<Biblio, BiblioResolver>, Error>;
+
+    /// Searches for entries that match the `query`, or returns `None` when the provider cannot
+    /// search, which is the default.
+    ///
+    /// Filters of the `query` that the provider cannot apply may be ignored, as the results of
+    /// [`Registry::search`] are filtered by year and ranked by how well they match the query.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when no entries match the `query`.
+    fn search(&self, query: &TitleQuery) -> Option<Result<Result<Biblio, BiblioResolver>, Error>> {
+        let _ = query;
+        None
+    }
+
+    /// Returns `true` when the provider can look up the `kind` of identifier.
+    fn supports(&self, kind: IdKind) -> bool {
+        self.id_kinds().contains(&kind)
+    }
+}
+
+/// The error of a provider asked to look up a kind of identifier that it does not support.
+fn unsupported(provider: &dyn Provider, kind: IdKind) -> Error {
+    Error::new(
+        ErrorKind::NoValue,
+        format!(
+            "The '{}' provider cannot look up entries by {kind}",
+            provider.name()
+        ),
+    )
+}
+
+/// The [CrossRef](https://www.crossref.org) provider, which looks up entries by DOI.
+///
+/// DOIs registered with `DataCite` are left to the [`DataCite`] provider.
+#[derive(Debug, Default)]
+pub struct CrossRef<C: Client = api::CachedClient>(PhantomData<C>);
+
+impl<C: Client> Provider for CrossRef<C> {
+    fn name(&self) -> &'static str {
+        "crossref"
+    }
+
+    fn id_kinds(&self) -> &[IdKind] {
+        &[IdKind::Doi]
+    }
+
+    fn lookup(&self, kind: IdKind, id: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match kind {
+            IdKind::Doi => {
+                let client = C::default();
+                match api::doi::registration_agency(&client, id)? {
+                    Some(Agency::DataCite) => Err(Error::new(
+                        ErrorKind::NotFound,
+                        format!("The DOI '{id}' is registered with DataCite, not CrossRef"),
+                    )),
+                    _ => api::cross_ref::get_entries_by_doi(&client, id),
+                }
+            }
+            _ => Err(unsupported(self, kind)),
+        }
+    }
+
+    fn search(&self, query: &TitleQuery) -> Option<Result<Result<Biblio, BiblioResolver>, Error>> {
+        Some(api::cross_ref::search_works(
+            &C::default(),
+            query,
+            SEARCH_LIMIT,
+        ))
+    }
+}
+
+/// The [DataCite](https://datacite.org) provider, which looks up the DOIs registered with
+/// `DataCite`, such as the DOIs of Zenodo and Figshare.
+///
+/// Datasets and software are built as `dataset` and `software` entries with the version,
+/// publisher and creators of the DOI.
+#[derive(Debug, Default)]
+pub struct DataCite<C: Client = api::CachedClient>(PhantomData<C>);
+
+impl<C: Client> Provider for DataCite<C> {
+    fn name(&self) -> &'static str {
+        "datacite"
+    }
+
+    fn id_kinds(&self) -> &[IdKind] {
+        &[IdKind::Doi]
+    }
+
+    fn lookup(&self, kind: IdKind, id: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match kind {
+            IdKind::Doi => {
+                let client = C::default();
+                match api::doi::registration_agency(&client, id)? {
+                    Some(agency) if agency != Agency::DataCite => Err(Error::new(
+                        ErrorKind::NotFound,
+                        format!("The DOI '{id}' is registered with {agency}, not DataCite"),
+                    )),
+                    _ => api::data_cite::get_entries_by_doi(&client, id),
+                }
+            }
+            _ => Err(unsupported(self, kind)),
+        }
+    }
+}
+
+/// The [Google Books](https://books.google.com) provider, which looks up books by ISBN.
+#[derive(Debug, Default)]
+pub struct GoogleBooks<C: Client = api::CachedClient>(PhantomData<C>);
+
+impl<C: Client> Provider for GoogleBooks<C> {
+    fn name(&self) -> &'static str {
+        "google-books"
+    }
+
+    fn id_kinds(&self) -> &[IdKind] {
+        &[IdKind::Isbn]
+    }
+
+    fn lookup(&self, kind: IdKind, id: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match kind {
+            IdKind::Isbn => api::google_books::get_entries_by_isbn(&C::default(), id),
+            _ => Err(unsupported(self, kind)),
+        }
+    }
+
+    fn search(&self, query: &TitleQuery) -> Option<Result<Result<Biblio, BiblioResolver>, Error>> {
+        Some(api::google_books::search_books(
+            &C::default(),
+            query,
+            SEARCH_LIMIT,
+        ))
+    }
+}
+
+/// The [IETF Datatracker](https://datatracker.ietf.org) provider, which looks up RFCs by number.
+#[derive(Debug, Default)]
+pub struct Ietf<C: Client = api::CachedClient>(PhantomData<C>);
+
+impl<C: Client> Provider for Ietf<C> {
+    fn name(&self) -> &'static str {
+        "ietf"
+    }
+
+    fn id_kinds(&self) -> &[IdKind] {
+        &[IdKind::Rfc]
+    }
+
+    fn lookup(&self, kind: IdKind, id: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match kind {
+            IdKind::Rfc => {
+                let number = id
+                    .trim()
+                    .trim_start_matches("RFC")
+                    .trim()
+                    .parse()
+                    .map_err(|e| {
+                        Error::wrap_with(
+                            ErrorKind::Deserialize,
+                            e,
+                            format!("'{id}' is not an RFC number"),
+                        )
+                    })?;
+                api::ietf::get_entry_by_rfc::<C>(number)
+            }
+            _ => Err(unsupported(self, kind)),
+        }
+    }
+}
+
+/// The [Open Library](https://openlibrary.org) provider, which searches for books.
+#[derive(Debug, Default)]
+pub struct OpenLibrary<C: Client = api::CachedClient>(PhantomData<C>);
+
+impl<C: Client> Provider for OpenLibrary<C> {
+    fn name(&self) -> &'static str {
+        "open-library"
+    }
+
+    fn id_kinds(&self) -> &[IdKind] {
+        &[]
+    }
+
+    fn lookup(&self, kind: IdKind, _: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        Err(unsupported(self, kind))
+    }
+
+    fn search(&self, query: &TitleQuery) -> Option<Result<Result<Biblio, BiblioResolver>, Error>> {
+        Some(api::open_library::search_books(
+            &C::default(),
+            query,
+            SEARCH_LIMIT,
+        ))
+    }
+}
+
+/// The web page provider, which builds entries from the metadata embedded in the HTML of a page,
+/// such as Highwire Press `citation_*` meta tags, JSON-LD, `COinS`, Dublin Core or `OpenGraph`.
+#[derive(Debug, Default)]
+pub struct WebPage<C: Client = api::CachedClient>(PhantomData<C>);
+
+impl<C: Client> Provider for WebPage<C> {
+    fn name(&self) -> &'static str {
+        "web"
+    }
+
+    fn id_kinds(&self) -> &[IdKind] {
+        &[IdKind::Url]
+    }
+
+    fn lookup(&self, kind: IdKind, id: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        match kind {
+            IdKind::Url => api::web_page::get_entry_by_page::<C>(id.trim()),
+            _ => Err(unsupported(self, kind)),
+        }
+    }
+}
+
+/// The providers of entries, in the order that they are tried.
+pub struct Registry {
+    providers: Vec<Box<dyn Provider>>,
+}
+
+impl Default for Registry {
+    /// Creates a registry of the built-in providers, `crossref`, `datacite`, `google-books`,
+    /// `ietf`, `open-library` and `web`.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(CrossRef::<api::CachedClient>::default());
+        registry.register(DataCite::<api::CachedClient>::default());
+        registry.register(GoogleBooks::<api::CachedClient>::default());
+        registry.register(Ietf::<api::CachedClient>::default());
+        registry.register(OpenLibrary::<api::CachedClient>::default());
+        registry.register(WebPage::<api::CachedClient>::default());
+        registry
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl Registry {
+    /// Creates a registry without any providers.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+
+    /// Adds the `provider` after the other providers, replacing any provider with the same name.
+    pub fn register<P: Provider + 'static>(&mut self, provider: P) {
+        let provider: Box<dyn Provider> = Box::new(provider);
+        match self.position(provider.name()) {
+            Some(index) => self.providers[index] = provider,
+            None => self.providers.push(provider),
+        }
+    }
+
+    /// Returns the provider with the `name`, ignoring case.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&dyn Provider> {
+        self.position(name).map(|index| &*self.providers[index])
+    }
+
+    /// Returns the names of the providers in the order that they are tried.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.providers.iter().map(|provider| provider.name())
+    }
+
+    /// Returns the providers in the order that they are tried.
+    pub fn providers(&self) -> impl Iterator<Item = &dyn Provider> {
+        self.providers.iter().map(|provider| &**provider)
+    }
+
+    /// Keeps only the providers with the `names`, in the order of the `names`.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when there is no provider with one of the `names`.
+    pub fn select<S: AsRef<str>>(mut self, names: &[S]) -> Result<Self, Error> {
+        let mut selected = Vec::with_capacity(names.len());
+        for name in names {
+            let name = name.as_ref();
+            let index = self.position(name).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NoValue,
+                    format!(
+                        "There is no provider named '{name}', the providers are: {}",
+                        self.names().collect::<Vec<_>>().join(", ")
+                    ),
+                )
+            })?;
+            selected.push(index);
+        }
+
+        let mut providers = self.providers.drain(..).map(Some).collect::<Vec<_>>();
+        self.providers = selected
+            .into_iter()
+            .filter_map(|index| providers[index].take())
+            .collect();
+        Ok(self)
+    }
+
+    /// Looks up the entries with the `id` of the `kind` using each provider that supports the
+    /// `kind` in turn, until one of them finds the entries.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when no provider supports the `kind` of identifier.
+    /// The error of the last provider is returned when none of them find the entries.
+    pub fn lookup(&self, kind: IdKind, id: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+        let mut last = None;
+        for provider in self.providers().filter(|provider| provider.supports(kind)) {
+            log::trace!(
+                "Looking up the {kind} '{id}' using the '{}' provider",
+                provider.name()
+            );
+            match provider.lookup(kind, id) {
+                Ok(found) => return Ok(found),
+                Err(e) => {
+                    log::debug!(
+                        "The '{}' provider did not find '{id}': {e}",
+                        provider.name()
+                    );
+                    last = Some(e);
+                }
+            }
+        }
+
+        Err(last.unwrap_or_else(|| {
+            Error::new(
+                ErrorKind::NoValue,
+                format!("None of the providers can look up entries by {kind}"),
+            )
+        }))
+    }
+
+    /// Searches for entries that match the `query` using every provider that can search, see
+    /// [`Provider::search`].
+    ///
+    /// The results are merged so that an entry with the same DOI or ISBN is only returned once,
+    /// keeping the entry of the first provider unless only a later provider has every required
+    /// field. Entries published outside the years of the `query` are dropped and the rest are
+    /// ranked by how well they match the `query` and then by how many providers found them.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when no provider can search.
+    /// The error of the last provider is returned when none of them find any entries.
+    pub fn search(&self, query: &TitleQuery) -> Result<Vec<Found>, Error> {
+        let mut found: Vec<Found> = Vec::new();
+        let mut last = None;
+        for provider in self.providers() {
+            let Some(searched) = provider.search(query) else {
+                continue;
+            };
+            log::trace!(
+                "Searched for '{query}' using the '{}' provider",
+                provider.name()
+            );
+            let results = match searched {
+                Ok(Ok(biblio)) => biblio.into_entries().into_iter().map(Ok).collect(),
+                Ok(Err(mut resolver)) => {
+                    std::iter::from_fn(|| resolver.checked_remove(0)).collect::<Vec<_>>()
+                }
+                Err(e) => {
+                    log::debug!(
+                        "The '{}' provider did not find '{query}': {e}",
+                        provider.name()
+                    );
+                    last = Some(e);
+                    continue;
+                }
+            };
+
+            for entry in results {
+                let result = Found {
+                    entry,
+                    providers: vec![provider.name().to_owned()],
+                    score: 0.0,
+                };
+                if result.year().is_some_and(|year| !query.includes_year(year)) {
+                    continue;
+                }
+                merge(&mut found, result);
+            }
+        }
+
+        if found.is_empty() {
+            return Err(last.unwrap_or_else(|| {
+                Error::new(
+                    ErrorKind::NoValue,
+                    "None of the providers can search for entries",
+                )
+            }));
+        }
+
+        for result in &mut found {
+            result.score = relevance(query, result.fields());
+        }
+        found.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| b.providers.len().cmp(&a.providers.len()))
+        });
+        Ok(found)
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.providers
+            .iter()
+            .position(|provider| provider.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// How well the `fields` of an entry match the `query`, between `0.0` and `1.0`.
+///
+/// Each word of the title counts fully when it is in the title and half when it is only in
+/// another field, and the words of the author and venue count when they are in those fields.
+#[allow(clippy::cast_precision_loss)] // word counts are far below the precision limit
+fn relevance(query: &TitleQuery, fields: &dyn FieldQuery) -> f64 {
+    let words = |names: &[&str]| {
+        names
+            .iter()
+            .filter_map(|name| fields.get_field(name))
+            .flat_map(|value| tokenize(value))
+            .collect::<Vec<_>>()
+    };
+    let title = words(&["title"]);
+    let authors = words(&["author", "editor"]);
+    let venues = words(&[
+        "journal",
+        "booktitle",
+        "publisher",
+        "series",
+        "organization",
+    ]);
+    let contains = |words: &[String], word: &str| words.iter().any(|w| w.starts_with(word));
+
+    let mut total = 0;
+    let mut matched = 0.0;
+    for word in tokenize(&query.title) {
+        total += 1;
+        if contains(&title, &word) {
+            matched += 1.0;
+        } else if contains(&authors, &word) || contains(&venues, &word) {
+            matched += 0.5;
+        }
+    }
+    for (query, words) in [(&query.author, &authors), (&query.venue, &venues)] {
+        for word in query.iter().flat_map(|query| tokenize(query)) {
+            total += 1;
+            if contains(words, &word) {
+                matched += 1.0;
+            }
+        }
+    }
+
+    if total == 0 {
+        0.0
+    } else {
+        matched / f64::from(total)
+    }
+}
+
+/// Adds the `result` to the `found` results, or adds its provider to the result with the same
+/// DOI or ISBN.
+fn merge(found: &mut Vec<Found>, result: Found) {
+    let Some(same) = result.id().and_then(|id| {
+        found
+            .iter_mut()
+            .find(|other| other.id().as_ref() == Some(&id))
+    }) else {
+        found.push(result);
+        return;
+    };
+
+    let Found {
+        entry, providers, ..
+    } = result;
+    if same.entry.is_err() && entry.is_ok() {
+        same.entry = entry;
+    }
+    for provider in providers {
+        if !same.providers.contains(&provider) {
+            same.providers.push(provider);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{assert_url, MockClient};
+    use crate::ast::Entry;
+
+    /// A provider for a repository that finds a single manual by its DOI.
+    struct Repository;
+
+    impl Provider for Repository {
+        fn name(&self) -> &'static str {
+            "repository"
+        }
+
+        fn id_kinds(&self) -> &[IdKind] {
+            &[IdKind::Doi]
+        }
+
+        fn lookup(&self, _: IdKind, id: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+            let mut resolver = Entry::resolver_with_cite(crate::ast::EntryKind::Manual, id);
+            resolver.set_field("title", "A manual");
+            Ok(Biblio::try_resolve(vec![resolver]))
+        }
+    }
+
+    #[test]
+    fn registry_selects_and_orders_providers_by_name() {
+        let mut registry = Registry::default();
+        registry.register(Repository);
+        assert_eq!(
+            vec![
+                "crossref",
+                "datacite",
+                "google-books",
+                "ietf",
+                "open-library",
+                "web",
+                "repository"
+            ],
+            registry.names().collect::<Vec<_>>()
+        );
+
+        let registry = registry.select(&["Repository", "crossref"]).unwrap();
+        assert_eq!(
+            vec!["repository", "crossref"],
+            registry.names().collect::<Vec<_>>()
+        );
+
+        let err = Registry::default().select(&["openlibrary"]).unwrap_err();
+        assert_eq!(ErrorKind::NoValue, err.kind());
+
+        let err = registry.lookup(IdKind::Rfc, "7230").unwrap_err();
+        assert_eq!(ErrorKind::NoValue, err.kind());
+    }
+
+    #[test]
+    fn lookup_tries_providers_that_support_the_kind_in_order() {
+        let mut registry = Registry::new();
+        registry.register(CrossRef::<MockClient>::default());
+        registry.register(Repository);
+
+        let biblio = registry
+            .lookup(IdKind::Doi, "10.1000/report")
+            .unwrap()
+            .unwrap();
+        assert_url!("https://api.crossref.org/works/10.1000/report/transform/application/x-bibtex");
+        assert!(biblio.get("10.1000/report").is_some());
+
+        assert!(Ietf::<MockClient>::default()
+            .lookup(IdKind::Rfc, "RFC 7230")
+            .is_err());
+        assert_url!("https://datatracker.ietf.org/doc/rfc7230/bibtex");
+        assert_eq!(IdKind::Isbn, "ISBN".parse().unwrap());
+    }
+
+    /// A provider that finds the same manuals, with a title, DOI and year, for every search.
+    struct Catalogue(
+        &'static str,
+        &'static [(&'static str, &'static str, &'static str)],
+    );
+
+    impl Provider for Catalogue {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn id_kinds(&self) -> &[IdKind] {
+            &[]
+        }
+
+        fn lookup(&self, kind: IdKind, _: &str) -> Result<Result<Biblio, BiblioResolver>, Error> {
+            Err(unsupported(self, kind))
+        }
+
+        fn search(&self, _: &TitleQuery) -> Option<Result<Result<Biblio, BiblioResolver>, Error>> {
+            let resolvers = self
+                .1
+                .iter()
+                .map(|(title, doi, year)| {
+                    let mut resolver =
+                        Entry::resolver_with_cite(crate::ast::EntryKind::Manual, *doi);
+                    resolver.set_field("title", *title);
+                    resolver.set_field("doi", *doi);
+                    resolver.set_field("year", *year);
+                    resolver
+                })
+                .collect();
+            Some(Ok(Biblio::try_resolve(resolvers)))
+        }
+    }
+
+    #[test]
+    fn search_merges_filters_and_ranks_the_results_of_providers() {
+        let mut registry = Registry::new();
+        registry.register(Repository);
+        registry.register(Catalogue(
+            "first",
+            &[
+                ("Ownership types", "10.1000/A", "2012"),
+                ("Borrow checking in Rust", "10.1000/b", "2019"),
+            ],
+        ));
+        registry.register(Catalogue(
+            "second",
+            &[
+                (
+                    "Borrow checking in Rust",
+                    "https://doi.org/10.1000/B",
+                    "2019",
+                ),
+                ("Rust in the wild", "10.1000/c", "2021"),
+                ("Borrow checking in C++", "10.1000/d", "2001"),
+            ],
+        ));
+
+        let query = TitleQuery::new("rust borrow checking").years(Some(2010), None);
+        let found = registry.search(&query).unwrap();
+        let titles = found
+            .iter()
+            .map(|result| result.fields().get_field("title").unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "Borrow checking in Rust",
+                "Rust in the wild",
+                "Ownership types"
+            ],
+            titles
+        );
+        assert_eq!(vec!["first", "second"], found[0].providers);
+        assert!((found[0].score - 1.0).abs() < f64::EPSILON);
+
+        let err = Registry::new().search(&query).unwrap_err();
+        assert_eq!(ErrorKind::NoValue, err.kind());
+        assert!(TitleQuery::new(" ").is_empty());
+    }
+}
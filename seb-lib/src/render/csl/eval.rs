@@ -0,0 +1,860 @@
+use std::cmp::Ordering;
+
+use super::{
+    item::{DateValue, Item},
+    locale::TermForm,
+    style::{
+        Condition, Date, DatePart, DatePartName, Element, Formatting, Label, Match, Names,
+        NumberForm, Plural, Section, SortKey, TextCase, TextSource,
+    },
+    CslStyle, Locale,
+};
+use crate::render::{escape_html, escape_markdown, Name, Output};
+
+/// The result of rendering an element, with whether it called a variable and whether any of the
+/// variables it called had a value so that empty groups can be suppressed.
+#[derive(Debug, Default)]
+struct Rendered {
+    text: String,
+    called: bool,
+    found: bool,
+}
+
+impl Rendered {
+    fn text(text: String) -> Self {
+        Self {
+            text,
+            ..Self::default()
+        }
+    }
+
+    const fn variable(text: String) -> Self {
+        Self {
+            found: !text.is_empty(),
+            called: true,
+            text,
+        }
+    }
+
+    const fn empty_variable() -> Self {
+        Self {
+            text: String::new(),
+            called: true,
+            found: false,
+        }
+    }
+}
+
+/// Renders the elements of a style section for a single item.
+pub(super) struct Evaluator<'a> {
+    style: &'a CslStyle,
+    locale: &'a Locale,
+    section: &'a Section,
+    output: Output,
+    item: &'a Item,
+    /// The variables that were substituted by a `names` element and are not rendered again.
+    suppressed: Vec<String>,
+    /// The variables rendered so far, used to find the variables to suppress.
+    rendered: Vec<String>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub(super) const fn new(
+        style: &'a CslStyle,
+        locale: &'a Locale,
+        section: &'a Section,
+        output: Output,
+        item: &'a Item,
+    ) -> Self {
+        Self {
+            style,
+            locale,
+            section,
+            output,
+            item,
+            suppressed: Vec::new(),
+            rendered: Vec::new(),
+        }
+    }
+
+    /// Renders the layout of the section, without the affixes of the layout.
+    pub(super) fn layout(&mut self) -> String {
+        let section = self.section;
+        let rendered = self.elements(&section.layout, "");
+        wrap_font(self.output, &section.formatting, rendered.text)
+            .trim()
+            .to_owned()
+    }
+
+    /// The sort keys of the item for the section.
+    pub(super) fn sort_keys(&mut self) -> Vec<String> {
+        let output = std::mem::replace(&mut self.output, Output::Text);
+        let keys = self
+            .section
+            .sort
+            .iter()
+            .map(|key| match key {
+                SortKey::Variable { name, .. } => self.variable_sort_key(name),
+                SortKey::Macro { name, .. } => self.call_macro(name).text.to_lowercase(),
+            })
+            .collect();
+        self.output = output;
+        keys
+    }
+
+    fn variable_sort_key(&self, name: &str) -> String {
+        if let Some(names) = self.item.names(name) {
+            return names
+                .iter()
+                .map(|name| format!("{} {}", name.family, name.given))
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase();
+        }
+        if let Some(date) = self.item.date(name) {
+            return date.sort_key();
+        }
+        match self.item.variable(name) {
+            Some(value) => match value.parse::<u64>() {
+                Ok(number) => format!("{number:020}"),
+                Err(_) => value.to_lowercase(),
+            },
+            None => String::new(),
+        }
+    }
+
+    fn elements(&mut self, elements: &[Element], delimiter: &str) -> Rendered {
+        let mut result = Rendered::default();
+        let mut parts = Vec::new();
+        for element in elements {
+            let rendered = self.element(element);
+            result.called |= rendered.called;
+            result.found |= rendered.found;
+            if !rendered.text.is_empty() {
+                parts.push(rendered.text);
+            }
+        }
+        result.text = parts.join(&self.escape(delimiter));
+        result
+    }
+
+    fn element(&mut self, element: &Element) -> Rendered {
+        match element {
+            Element::Text { source, formatting } => self.text(source, formatting),
+            Element::Date(date) => self.date(date),
+            Element::Number {
+                variable,
+                form,
+                formatting,
+            } => self.number(variable, *form, formatting),
+            Element::Names(names) => self.names(names),
+            Element::Label(label) => self.label(label),
+            Element::Group {
+                children,
+                delimiter,
+                formatting,
+            } => {
+                let rendered = self.elements(children, delimiter);
+                // a group is suppressed when it calls variables but none of them have a value
+                if rendered.text.is_empty() || (rendered.called && !rendered.found) {
+                    return Rendered {
+                        text: String::new(),
+                        ..rendered
+                    };
+                }
+                Rendered {
+                    text: self.affixes(
+                        formatting,
+                        wrap_font(self.output, formatting, rendered.text),
+                    ),
+                    ..rendered
+                }
+            }
+            Element::Choose {
+                branches,
+                otherwise,
+            } => {
+                let branch = branches
+                    .iter()
+                    .find(|(condition, _)| self.test(condition))
+                    .map_or(otherwise, |(_, elements)| elements);
+                self.elements(branch, "")
+            }
+        }
+    }
+
+    fn test(&self, condition: &Condition) -> bool {
+        let item = self.item;
+        let tests = condition
+            .types
+            .iter()
+            .map(|kind| kind == item.kind)
+            .chain(
+                condition
+                    .variables
+                    .iter()
+                    .map(|name| item.has(name) && !self.suppressed.contains(name)),
+            )
+            .chain(
+                condition
+                    .is_numeric
+                    .iter()
+                    .map(|name| item.variable(name).is_some_and(is_numeric)),
+            )
+            .chain(std::iter::repeat_n(false, condition.unsupported))
+            .collect::<Vec<_>>();
+
+        match condition.matches {
+            Match::All => tests.iter().all(|test| *test),
+            Match::Any => tests.iter().any(|test| *test),
+            Match::None => !tests.iter().any(|test| *test),
+        }
+    }
+
+    fn call_macro(&mut self, name: &str) -> Rendered {
+        let style = self.style;
+        if let Some(elements) = style.macros.get(name) {
+            return self.elements(elements, "");
+        }
+        log::warn!("The CSL style has no macro named '{name}'");
+        Rendered::default()
+    }
+
+    /// Looks up a standard variable, recording that it was rendered.
+    fn variable(&mut self, name: &str) -> Option<&'a str> {
+        if self.suppressed.iter().any(|suppressed| suppressed == name) {
+            return None;
+        }
+        let value = self.item.variable(name)?;
+        self.rendered.push(name.to_owned());
+        Some(value)
+    }
+
+    fn text(&mut self, source: &TextSource, formatting: &Formatting) -> Rendered {
+        match source {
+            TextSource::Variable { name, short } => {
+                let value = if *short {
+                    self.variable(&format!("{name}-short"))
+                        .or_else(|| self.variable(name))
+                } else {
+                    self.variable(name)
+                };
+                match value {
+                    Some(value) => Rendered::variable(self.leaf(value, formatting)),
+                    None => Rendered::empty_variable(),
+                }
+            }
+            TextSource::Macro(name) => {
+                let rendered = self.call_macro(name);
+                if rendered.text.is_empty() {
+                    return rendered;
+                }
+                Rendered {
+                    text: self.affixes(
+                        formatting,
+                        self.quote(
+                            formatting,
+                            wrap_font(self.output, formatting, rendered.text),
+                        ),
+                    ),
+                    ..rendered
+                }
+            }
+            TextSource::Term { name, form, plural } => {
+                let term = self.locale.term(name, *form, *plural).unwrap_or_default();
+                Rendered::text(self.leaf(term, formatting))
+            }
+            TextSource::Value(value) => Rendered::text(self.leaf(value, formatting)),
+        }
+    }
+
+    fn number(&mut self, variable: &str, form: NumberForm, formatting: &Formatting) -> Rendered {
+        let Some(value) = self.variable(variable) else {
+            return Rendered::empty_variable();
+        };
+        let value = match (value.parse::<u32>(), form) {
+            (Ok(n), NumberForm::Ordinal) => format!("{n}{}", self.ordinal_suffix(n)),
+            (Ok(n), NumberForm::LongOrdinal) => self
+                .locale
+                .term(&format!("long-ordinal-{n:02}"), TermForm::Long, false)
+                .map_or_else(|| format!("{n}{}", self.ordinal_suffix(n)), str::to_owned),
+            (Ok(n), NumberForm::Roman) => roman(n),
+            _ => value.to_owned(),
+        };
+        Rendered::variable(self.leaf(&value, formatting))
+    }
+
+    fn ordinal_suffix(&self, n: u32) -> &str {
+        let term = |name: &str| self.locale.term(name, TermForm::Long, false);
+        let specific = if (11..=13).contains(&(n % 100)) {
+            term(&format!("ordinal-{:02}", n % 100))
+        } else {
+            None
+        };
+        specific
+            .or_else(|| match n % 10 {
+                1..=3 if !(11..=13).contains(&(n % 100)) => term(&format!("ordinal-{:02}", n % 10)),
+                _ => None,
+            })
+            .or_else(|| term("ordinal"))
+            .unwrap_or_default()
+    }
+
+    fn label(&mut self, label: &Label) -> Rendered {
+        let Some(value) = self.item.variable(&label.variable) else {
+            return Rendered::default();
+        };
+        let plural = match label.plural {
+            Plural::Always => true,
+            Plural::Never => false,
+            Plural::Contextual if label.variable == "number-of-pages" => {
+                value.parse::<u32>().is_ok_and(|n| n > 1)
+            }
+            Plural::Contextual => value.contains(['-', '–', ',', '&']),
+        };
+        match self.locale.term(&label.variable, label.form, plural) {
+            Some(term) => Rendered::text(self.leaf(term, &label.formatting)),
+            None => Rendered::default(),
+        }
+    }
+
+    fn date(&mut self, date: &Date) -> Rendered {
+        let Some(value) = self.item.date(&date.variable) else {
+            return Rendered::empty_variable();
+        };
+        self.rendered.push(date.variable.clone());
+        if let Some(literal) = &value.literal {
+            return Rendered::variable(self.leaf(literal, &date.formatting));
+        }
+
+        // localized dates use the date parts of the locale, which can be changed by the style
+        let (parts, delimiter) = match date.form.and_then(|form| self.locale.date(form)) {
+            Some(localized) => {
+                let parts = localized
+                    .parts
+                    .iter()
+                    .filter(|part| match part.name {
+                        DatePartName::Year => true,
+                        DatePartName::Month => date.shown >= 2,
+                        DatePartName::Day => date.shown >= 3,
+                    })
+                    .map(|part| {
+                        let mut part = part.clone();
+                        if let Some(custom) = date.parts.iter().find(|c| c.name == part.name) {
+                            if custom.form.is_some() {
+                                part.form.clone_from(&custom.form);
+                            }
+                            if custom.formatting.text_case.is_some() {
+                                part.formatting.text_case = custom.formatting.text_case;
+                            }
+                        }
+                        part
+                    })
+                    .collect::<Vec<_>>();
+                (parts, localized.delimiter.clone())
+            }
+            None => (date.parts.clone(), date.delimiter.clone()),
+        };
+
+        let text = parts
+            .iter()
+            .filter_map(|part| self.date_part(part, value))
+            .collect::<Vec<_>>()
+            .join(&self.escape(&delimiter));
+        let text = text.trim().to_owned();
+        if text.is_empty() {
+            return Rendered::empty_variable();
+        }
+        Rendered::variable(self.affixes(
+            &date.formatting,
+            wrap_font(self.output, &date.formatting, text),
+        ))
+    }
+
+    fn date_part(&self, part: &DatePart, date: &DateValue) -> Option<String> {
+        let form = part.form.as_deref();
+        let value = match part.name {
+            DatePartName::Year => {
+                let year = date.year?;
+                if form == Some("short") {
+                    format!("{:02}", year.rem_euclid(100))
+                } else {
+                    year.to_string()
+                }
+            }
+            DatePartName::Month => {
+                let month = date.month?;
+                match form {
+                    Some("numeric") => month.to_string(),
+                    Some("numeric-leading-zeros") => format!("{month:02}"),
+                    Some("short") => self
+                        .locale
+                        .term(&format!("month-{month:02}"), TermForm::Short, false)?
+                        .to_owned(),
+                    _ => self
+                        .locale
+                        .term(&format!("month-{month:02}"), TermForm::Long, false)?
+                        .to_owned(),
+                }
+            }
+            DatePartName::Day => {
+                let day = date.day?;
+                match form {
+                    Some("numeric-leading-zeros") => format!("{day:02}"),
+                    Some("ordinal") => format!("{day}{}", self.ordinal_suffix(day)),
+                    _ => day.to_string(),
+                }
+            }
+        };
+        Some(self.leaf(&value, &part.formatting))
+    }
+
+    /// Looks up a name option on the `name` element, or the inherited option of the section or
+    /// style.
+    fn option<'n>(&'n self, names: &'n Names, key: &str) -> Option<&'n str> {
+        names
+            .name
+            .options
+            .get(key)
+            .or_else(|| self.section.options.get(key))
+            .or_else(|| self.style.options.get(key))
+            .map(String::as_str)
+    }
+
+    fn names(&mut self, names: &Names) -> Rendered {
+        let mut lists = Vec::new();
+        for variable in &names.variables {
+            if self.suppressed.contains(variable) {
+                continue;
+            }
+            let Some(list) = self.item.names(variable) else {
+                continue;
+            };
+            self.rendered.push(variable.clone());
+
+            let mut text = self.name_list(names, list);
+            if let Some((label, before)) = &names.label {
+                let form_plural = list.len() > 1 && label.plural != Plural::Never
+                    || label.plural == Plural::Always;
+                if let Some(term) = self.locale.term(variable, label.form, form_plural) {
+                    let term = self.leaf(term, &label.formatting);
+                    text = if *before {
+                        format!("{term}{text}")
+                    } else {
+                        format!("{text}{term}")
+                    };
+                }
+            }
+            lists.push(text);
+        }
+
+        if lists.is_empty() {
+            return self.substitute(names);
+        }
+
+        let delimiter = names
+            .delimiter
+            .as_deref()
+            .or_else(|| {
+                self.section
+                    .options
+                    .get("names-delimiter")
+                    .map(String::as_str)
+            })
+            .or_else(|| {
+                self.style
+                    .options
+                    .get("names-delimiter")
+                    .map(String::as_str)
+            })
+            .unwrap_or_default();
+        let text = lists.join(&self.escape(delimiter));
+        Rendered::variable(self.affixes(
+            &names.formatting,
+            wrap_font(self.output, &names.formatting, text),
+        ))
+    }
+
+    /// Renders the first substitute that has a value, and suppresses the variables it used.
+    fn substitute(&mut self, names: &Names) -> Rendered {
+        for element in &names.substitute {
+            let start = self.rendered.len();
+            let rendered = self.element(element);
+            if !rendered.text.is_empty() {
+                let used = self.rendered.split_off(start);
+                self.suppressed.extend(used);
+                return Rendered::variable(self.affixes(
+                    &names.formatting,
+                    wrap_font(self.output, &names.formatting, rendered.text),
+                ));
+            }
+        }
+        Rendered::empty_variable()
+    }
+
+    fn name_list(&self, names: &Names, list: &[Name]) -> String {
+        let option = |key: &str| self.option(names, key);
+        let form = option("form").unwrap_or("long");
+        let delimiter = self.escape(option("delimiter").unwrap_or(", "));
+
+        let et_al_min = option("et-al-min").and_then(|n| n.parse::<usize>().ok());
+        let et_al_use_first = option("et-al-use-first").and_then(|n| n.parse::<usize>().ok());
+        let shown = match (et_al_min, et_al_use_first) {
+            (Some(min), Some(first)) if first > 0 && list.len() >= min => first.min(list.len()),
+            _ => list.len(),
+        };
+        if form == "count" {
+            return shown.to_string();
+        }
+
+        let sort_order = option("name-as-sort-order");
+        let inverted = |i: usize| match sort_order {
+            Some("all") => true,
+            Some("first") => i == 0,
+            _ => false,
+        };
+        let rendered = list[..shown]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| self.name(names, name, inverted(i)))
+            .collect::<Vec<_>>();
+
+        let text = if shown < list.len() {
+            let (term, formatting) = names
+                .et_al
+                .as_ref()
+                .map_or(("et-al", None), |(term, formatting)| {
+                    (term.as_str(), Some(formatting))
+                });
+            let et_al = self
+                .locale
+                .term(term, TermForm::Long, false)
+                .unwrap_or("et al.");
+            let et_al = self.leaf(et_al, formatting.unwrap_or(&Formatting::default()));
+            let precedes = match option("delimiter-precedes-et-al") {
+                Some("always") => true,
+                Some("never") => false,
+                Some("after-inverted-name") => inverted(shown - 1),
+                _ => shown > 1,
+            };
+            let separator = if precedes { &delimiter } else { " " };
+            format!("{}{separator}{et_al}", rendered.join(&delimiter))
+        } else {
+            match rendered.as_slice() {
+                [] => String::new(),
+                [name] => name.clone(),
+                [rest @ .., final_name] => {
+                    let and = match option("and") {
+                        Some("text") => self.locale.term("and", TermForm::Long, false),
+                        Some("symbol") => Some("&"),
+                        _ => None,
+                    };
+                    let precedes = match option("delimiter-precedes-last") {
+                        Some("always") => true,
+                        Some("never") => false,
+                        Some("after-inverted-name") => inverted(rest.len() - 1),
+                        _ => rest.len() > 1,
+                    };
+                    let separator = match (and, precedes) {
+                        (Some(and), true) => format!("{delimiter}{} ", self.escape(and)),
+                        (Some(and), false) => format!(" {} ", self.escape(and)),
+                        (None, _) => delimiter.clone(),
+                    };
+                    format!("{}{separator}{final_name}", rest.join(&delimiter))
+                }
+            }
+        };
+
+        let formatting = &names.name.formatting;
+        self.affixes(formatting, text)
+    }
+
+    fn name(&self, names: &Names, name: &Name, inverted: bool) -> String {
+        let family = self.leaf(&name.family, &names.name.family);
+        if self.option(names, "form") == Some("short") || name.given.is_empty() {
+            return wrap_font(self.output, &names.name.formatting, family);
+        }
+
+        let given = match self.option(names, "initialize-with") {
+            Some(initialize_with) if self.option(names, "initialize") != Some("false") => {
+                let hyphen = self.option(names, "initialize-with-hyphen") != Some("false");
+                initials(&name.given, initialize_with, hyphen)
+            }
+            _ => name.given.clone(),
+        };
+        let given = self.leaf(&given, &names.name.given);
+
+        let name = if inverted {
+            let separator = self.option(names, "sort-separator").unwrap_or(", ");
+            format!("{family}{}{given}", self.escape(separator))
+        } else {
+            format!("{given} {family}")
+        };
+        wrap_font(self.output, &names.name.formatting, name)
+    }
+
+    /// Escapes text for the output markup.
+    fn escape(&self, text: &str) -> String {
+        match self.output {
+            Output::Text => text.to_owned(),
+            Output::Html => escape_html(text),
+            Output::Markdown => escape_markdown(text),
+        }
+    }
+
+    /// Renders a value with the formatting of an element.
+    fn leaf(&self, value: &str, formatting: &Formatting) -> String {
+        if value.is_empty() {
+            return String::new();
+        }
+        let mut value = if formatting.strip_periods {
+            value.replace('.', "")
+        } else {
+            value.to_owned()
+        };
+        if let Some(text_case) = formatting.text_case {
+            value = apply_text_case(&value, text_case);
+        }
+        let value = self.quote(formatting, self.escape(&value));
+        self.affixes(formatting, wrap_font(self.output, formatting, value))
+    }
+
+    fn quote(&self, formatting: &Formatting, text: String) -> String {
+        if !formatting.quotes || text.is_empty() {
+            return text;
+        }
+        let open = self
+            .locale
+            .term("open-quote", TermForm::Long, false)
+            .unwrap_or("“");
+        let close = self
+            .locale
+            .term("close-quote", TermForm::Long, false)
+            .unwrap_or("”");
+        format!("{open}{text}{close}")
+    }
+
+    fn affixes(&self, formatting: &Formatting, text: String) -> String {
+        if text.is_empty() {
+            return text;
+        }
+        format!(
+            "{}{text}{}",
+            self.escape(&formatting.prefix),
+            self.escape(&formatting.suffix)
+        )
+    }
+}
+
+/// Wraps text in the markup for the font formatting.
+fn wrap_font(output: Output, formatting: &Formatting, text: String) -> String {
+    if text.is_empty() {
+        return text;
+    }
+    let mut text = text;
+    match output {
+        Output::Text => {}
+        Output::Html => {
+            if formatting.italic {
+                text = format!("<i>{text}</i>");
+            }
+            if formatting.bold {
+                text = format!("<b>{text}</b>");
+            }
+            if formatting.small_caps {
+                text = format!("<span style=\"font-variant:small-caps;\">{text}</span>");
+            }
+            if formatting.superscript {
+                text = format!("<sup>{text}</sup>");
+            }
+            if formatting.subscript {
+                text = format!("<sub>{text}</sub>");
+            }
+        }
+        Output::Markdown => {
+            if formatting.italic {
+                text = format!("*{text}*");
+            }
+            if formatting.bold {
+                text = format!("**{text}**");
+            }
+        }
+    }
+    text
+}
+
+/// The initials of the given names, where `initialize_with` follows each initial.
+fn initials(given: &str, initialize_with: &str, hyphen: bool) -> String {
+    let initial = initialize_with.trim_end();
+    let separator = if initialize_with.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+
+    given
+        .split_whitespace()
+        .map(|word| {
+            let parts = word
+                .split('-')
+                .filter_map(|part| part.chars().next())
+                .map(|c| format!("{c}{initial}"));
+            if hyphen {
+                parts.collect::<Vec<_>>().join("-")
+            } else {
+                parts.collect::<String>()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn apply_text_case(value: &str, text_case: TextCase) -> String {
+    fn capitalize(word: &str) -> String {
+        let mut chars = word.chars();
+        chars.next().map_or_else(String::new, |first| {
+            first.to_uppercase().chain(chars).collect()
+        })
+    }
+
+    /// Words that are not capitalized in title case unless they are the first word.
+    const STOP_WORDS: [&str; 16] = [
+        "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "nor", "of", "on", "or",
+        "the", "to",
+    ];
+
+    match text_case {
+        TextCase::Lowercase => value.to_lowercase(),
+        TextCase::Uppercase => value.to_uppercase(),
+        TextCase::CapitalizeFirst | TextCase::Sentence => capitalize(value),
+        TextCase::CapitalizeAll => value
+            .split(' ')
+            .map(capitalize)
+            .collect::<Vec<_>>()
+            .join(" "),
+        TextCase::Title => value
+            .split(' ')
+            .enumerate()
+            .map(|(i, word)| {
+                if i > 0 && STOP_WORDS.contains(&word) {
+                    word.to_owned()
+                } else {
+                    capitalize(word)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn is_numeric(value: &str) -> bool {
+    let mut tokens = value
+        .split(['-', '–', ',', '&', ' '])
+        .filter(|token| !token.is_empty())
+        .peekable();
+    tokens.peek().is_some() && tokens.all(|token| token.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+fn roman(n: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+
+    let mut n = n;
+    let mut numeral = String::new();
+    for (value, letters) in NUMERALS {
+        while n >= value {
+            numeral.push_str(letters);
+            n -= value;
+        }
+    }
+    numeral
+}
+
+/// Removes doubled periods left by affixes and moves commas and periods inside closing quotes
+/// when the locale expects it.
+pub(super) fn tidy_punctuation(text: &str, punctuation_in_quote: bool) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut tidy = String::with_capacity(text.len());
+    for (i, c) in chars.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let ellipsis = prev == Some('.') && next == Some(&'.')
+            || i >= 2 && chars[i - 2] == '.' && prev == Some('.');
+        if *c == '.' && matches!(prev, Some('.' | '?' | '!')) && !ellipsis {
+            continue;
+        }
+        tidy.push(*c);
+    }
+
+    if punctuation_in_quote {
+        tidy = tidy.replace("”.", ".”").replace("”,", ",”");
+    }
+    tidy
+}
+
+/// Compares the sort keys of two items, where empty keys are sorted last.
+pub(super) fn compare_keys(keys: &[SortKey], a: &[String], b: &[String]) -> Ordering {
+    for ((key, a), b) in keys.iter().zip(a).zip(b) {
+        let descending = match key {
+            SortKey::Variable { descending, .. } | SortKey::Macro { descending, .. } => *descending,
+        };
+        let ordering = match (a.is_empty(), b.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) if descending => b.cmp(a),
+            (false, false) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initials_with_the_initialize_with_value() {
+        assert_eq!("D. E.", initials("Donald Ervin", ". ", true));
+        assert_eq!("D.E.", initials("Donald Ervin", ".", true));
+        assert_eq!("J.-P.", initials("Jean-Paul", ". ", true));
+        assert_eq!("J.P.", initials("Jean-Paul", ". ", false));
+    }
+
+    #[test]
+    fn numbers_and_text_cases() {
+        assert_eq!("xiv", roman(14));
+        assert!(is_numeric("12-14"));
+        assert!(!is_numeric("Special issue"));
+        assert_eq!(
+            "The Art of Computer Programming",
+            apply_text_case("the art of computer programming", TextCase::Title)
+        );
+    }
+
+    #[test]
+    fn doubled_periods_are_removed() {
+        assert_eq!(
+            "Knuth, D. E. 1984.",
+            tidy_punctuation("Knuth, D. E.. 1984.", false)
+        );
+        assert_eq!("Wait...", tidy_punctuation("Wait...", false));
+        assert_eq!("“Title,” 1984", tidy_punctuation("“Title”, 1984", true));
+    }
+}
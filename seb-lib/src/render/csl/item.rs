@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{Entry, EntryKind, FieldQuery},
+    format::parse_month,
+    render::Name,
+};
+
+/// A date variable of an item, where the year can be a literal such as `in press`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct DateValue {
+    pub(super) year: Option<i32>,
+    pub(super) month: Option<u32>,
+    pub(super) day: Option<u32>,
+    pub(super) literal: Option<String>,
+}
+
+impl DateValue {
+    fn parse(year: &str, month: Option<&str>, day: Option<&str>) -> Self {
+        match year.parse() {
+            Ok(year) => Self {
+                year: Some(year),
+                month: month.and_then(parse_month),
+                day: day
+                    .and_then(|day| day.parse().ok())
+                    .filter(|day| (1..=31).contains(day)),
+                literal: None,
+            },
+            Err(_) => Self {
+                literal: Some(year.to_owned()),
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Parses an ISO 8601 date such as `2021-05-04`, `2021-05` or `2021`.
+    fn parse_iso(date: &str) -> Self {
+        let mut parts = date.splitn(3, '-');
+        let year = parts.next().unwrap_or_default();
+        Self::parse(year, parts.next(), parts.next())
+    }
+
+    /// The date as a `YYYYMMDD` key used for sorting.
+    pub(super) fn sort_key(&self) -> String {
+        match self.year {
+            Some(year) => format!(
+                "{year:04}{:02}{:02}",
+                self.month.unwrap_or_default(),
+                self.day.unwrap_or_default()
+            ),
+            None => self.literal.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// The CSL item type of an entry kind.
+const fn item_type(kind: &EntryKind<'_>) -> &'static str {
+    match kind {
+        EntryKind::Article => "article-journal",
+        EntryKind::Booklet => "pamphlet",
+        EntryKind::BookChapter | EntryKind::BookSection => "chapter",
+        EntryKind::InProceedings => "paper-conference",
+        EntryKind::MasterThesis | EntryKind::PhdThesis => "thesis",
+        EntryKind::TechReport => "report",
+        EntryKind::Unpublished => "manuscript",
+        EntryKind::Book | EntryKind::BookPages | EntryKind::Manual | EntryKind::Proceedings => {
+            "book"
+        }
+        EntryKind::Other(_) => "document",
+    }
+}
+
+/// An entry converted to the variables of a CSL item.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Item {
+    pub(super) cite: String,
+    pub(super) kind: &'static str,
+    variables: HashMap<&'static str, String>,
+    names: HashMap<&'static str, Vec<Name>>,
+    dates: HashMap<&'static str, DateValue>,
+}
+
+impl Item {
+    pub(super) fn new(entry: &Entry) -> Self {
+        let field = |name: &str| {
+            entry
+                .get_field(name)
+                .map(|value| value.trim().to_owned())
+                .filter(|value| !value.is_empty())
+        };
+        let kind = entry.kind();
+
+        let mut item = Self {
+            cite: entry.cite().to_owned(),
+            kind: item_type(&kind),
+            ..Self::default()
+        };
+
+        let (title, container) = match kind {
+            EntryKind::Article => (field("title"), field("journal")),
+            // the title of a book chapter is the title of the book
+            EntryKind::BookChapter => (field("chapter"), field("title")),
+            _ => (field("title"), field("book_title")),
+        };
+        let publisher = ["publisher", "school", "institution", "organization"]
+            .iter()
+            .find_map(|name| field(name));
+        let genre = match kind {
+            EntryKind::MasterThesis => Some("Master's thesis".to_owned()),
+            EntryKind::PhdThesis => Some("PhD thesis".to_owned()),
+            EntryKind::TechReport => field("type").or_else(|| Some("Technical report".to_owned())),
+            _ => field("type"),
+        };
+        // the number of a report is the report number rather than the issue
+        let (issue, number) = match kind {
+            EntryKind::TechReport => (None, field("number")),
+            _ => (field("number"), None),
+        };
+
+        let variables = [
+            ("title", title),
+            ("container-title", container),
+            ("collection-title", field("series")),
+            ("publisher", publisher),
+            ("publisher-place", field("address")),
+            ("genre", genre),
+            ("volume", field("volume")),
+            ("issue", issue),
+            ("number", number),
+            ("page", field("pages").map(|pages| pages.replace("--", "–"))),
+            ("edition", field("edition")),
+            ("DOI", field("doi")),
+            ("ISBN", field("isbn")),
+            ("ISSN", field("issn")),
+            ("URL", field("url")),
+            ("abstract", field("abstract")),
+            ("note", field("note")),
+            ("keyword", field("keywords")),
+            ("language", field("language")),
+            ("citation-key", Some(item.cite.clone())),
+        ];
+        item.variables = variables
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|value| (name, value)))
+            .collect();
+
+        for (variable, name) in [
+            ("author", "author"),
+            ("editor", "editor"),
+            ("translator", "translator"),
+        ] {
+            if let Some(names) = field(name) {
+                item.names.insert(variable, Name::parse_list(&names));
+            }
+        }
+
+        if let Some(year) = field("year") {
+            let month = field("month");
+            let day = field("day");
+            item.dates.insert(
+                "issued",
+                DateValue::parse(&year, month.as_deref(), day.as_deref()),
+            );
+        }
+        if let Some(date) = field("urldate") {
+            item.dates.insert("accessed", DateValue::parse_iso(&date));
+        }
+
+        item
+    }
+
+    pub(super) fn set_citation_number(&mut self, number: usize) {
+        self.variables.insert("citation-number", number.to_string());
+    }
+
+    /// The value of a standard or number variable.
+    pub(super) fn variable(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(String::as_str)
+    }
+
+    pub(super) fn names(&self, name: &str) -> Option<&[Name]> {
+        self.names.get(name).map(Vec::as_slice)
+    }
+
+    pub(super) fn date(&self, name: &str) -> Option<&DateValue> {
+        self.dates.get(name)
+    }
+
+    /// Whether any kind of variable has a value.
+    pub(super) fn has(&self, name: &str) -> bool {
+        self.variable(name).is_some() || self.names(name).is_some() || self.date(name).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_from_fields() {
+        assert_eq!(
+            DateValue {
+                year: Some(2019),
+                month: Some(3),
+                day: None,
+                literal: None
+            },
+            DateValue::parse("2019", Some("March"), None)
+        );
+        assert_eq!(Some(12), DateValue::parse_iso("2021-12-01").month);
+        assert_eq!(
+            Some("in press".to_owned()),
+            DateValue::parse("in press", None, None).literal
+        );
+        assert_eq!("20210500", DateValue::parse_iso("2021-05").sort_key());
+    }
+}
@@ -0,0 +1,380 @@
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use roxmltree::{Document, Node};
+
+use super::style::{elements, invalid, Date, DateForm};
+use crate::{Error, ErrorKind};
+
+/// The `xml` namespace used by the `xml:lang` attribute.
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// The terms and date formats used when no locale file is given, a subset of the `en-US` locale
+/// of the CSL locales repository.
+const EN_US: &str = r#"<locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="en-US">
+  <style-options punctuation-in-quote="true"/>
+  <date form="text">
+    <date-part name="month" suffix=" "/>
+    <date-part name="day" suffix=", "/>
+    <date-part name="year"/>
+  </date>
+  <date form="numeric">
+    <date-part name="month" form="numeric-leading-zeros" suffix="/"/>
+    <date-part name="day" form="numeric-leading-zeros" suffix="/"/>
+    <date-part name="year"/>
+  </date>
+  <terms>
+    <term name="accessed">accessed</term>
+    <term name="and">and</term>
+    <term name="and others">and others</term>
+    <term name="anonymous">anonymous</term>
+    <term name="anonymous" form="short">anon.</term>
+    <term name="at">at</term>
+    <term name="available at">available at</term>
+    <term name="by">by</term>
+    <term name="circa">circa</term>
+    <term name="circa" form="short">c.</term>
+    <term name="cited">cited</term>
+    <term name="edition">
+      <single>edition</single>
+      <multiple>editions</multiple>
+    </term>
+    <term name="edition" form="short">ed.</term>
+    <term name="et-al">et al.</term>
+    <term name="forthcoming">forthcoming</term>
+    <term name="from">from</term>
+    <term name="ibid">ibid.</term>
+    <term name="in">in</term>
+    <term name="in press">in press</term>
+    <term name="internet">internet</term>
+    <term name="no date">no date</term>
+    <term name="no date" form="short">n.d.</term>
+    <term name="online">online</term>
+    <term name="presented at">presented at the</term>
+    <term name="reference">
+      <single>reference</single>
+      <multiple>references</multiple>
+    </term>
+    <term name="reference" form="short">
+      <single>ref.</single>
+      <multiple>refs.</multiple>
+    </term>
+    <term name="retrieved">retrieved</term>
+    <term name="scale">scale</term>
+    <term name="version">version</term>
+    <term name="open-quote">“</term>
+    <term name="close-quote">”</term>
+    <term name="open-inner-quote">‘</term>
+    <term name="close-inner-quote">’</term>
+    <term name="page-range-delimiter">–</term>
+    <term name="ordinal">th</term>
+    <term name="ordinal-01">st</term>
+    <term name="ordinal-02">nd</term>
+    <term name="ordinal-03">rd</term>
+    <term name="ordinal-11">th</term>
+    <term name="ordinal-12">th</term>
+    <term name="ordinal-13">th</term>
+    <term name="long-ordinal-01">first</term>
+    <term name="long-ordinal-02">second</term>
+    <term name="long-ordinal-03">third</term>
+    <term name="long-ordinal-04">fourth</term>
+    <term name="long-ordinal-05">fifth</term>
+    <term name="long-ordinal-06">sixth</term>
+    <term name="long-ordinal-07">seventh</term>
+    <term name="long-ordinal-08">eighth</term>
+    <term name="long-ordinal-09">ninth</term>
+    <term name="long-ordinal-10">tenth</term>
+    <term name="chapter">
+      <single>chapter</single>
+      <multiple>chapters</multiple>
+    </term>
+    <term name="chapter" form="short">
+      <single>chap.</single>
+      <multiple>chaps.</multiple>
+    </term>
+    <term name="issue">
+      <single>issue</single>
+      <multiple>issues</multiple>
+    </term>
+    <term name="issue" form="short">
+      <single>no.</single>
+      <multiple>nos.</multiple>
+    </term>
+    <term name="page">
+      <single>page</single>
+      <multiple>pages</multiple>
+    </term>
+    <term name="page" form="short">
+      <single>p.</single>
+      <multiple>pp.</multiple>
+    </term>
+    <term name="section">
+      <single>section</single>
+      <multiple>sections</multiple>
+    </term>
+    <term name="section" form="short">
+      <single>sec.</single>
+      <multiple>secs.</multiple>
+    </term>
+    <term name="volume">
+      <single>volume</single>
+      <multiple>volumes</multiple>
+    </term>
+    <term name="volume" form="short">
+      <single>vol.</single>
+      <multiple>vols.</multiple>
+    </term>
+    <term name="number-of-pages">
+      <single>page</single>
+      <multiple>pages</multiple>
+    </term>
+    <term name="number-of-pages" form="short">
+      <single>p.</single>
+      <multiple>pp.</multiple>
+    </term>
+    <term name="editor">
+      <single>editor</single>
+      <multiple>editors</multiple>
+    </term>
+    <term name="editor" form="short">
+      <single>ed.</single>
+      <multiple>eds.</multiple>
+    </term>
+    <term name="translator">
+      <single>translator</single>
+      <multiple>translators</multiple>
+    </term>
+    <term name="translator" form="short">
+      <single>tran.</single>
+      <multiple>trans.</multiple>
+    </term>
+    <term name="editor" form="verb">edited by</term>
+    <term name="translator" form="verb">translated by</term>
+    <term name="editor" form="verb-short">ed. by</term>
+    <term name="translator" form="verb-short">trans. by</term>
+    <term name="month-01">January</term>
+    <term name="month-02">February</term>
+    <term name="month-03">March</term>
+    <term name="month-04">April</term>
+    <term name="month-05">May</term>
+    <term name="month-06">June</term>
+    <term name="month-07">July</term>
+    <term name="month-08">August</term>
+    <term name="month-09">September</term>
+    <term name="month-10">October</term>
+    <term name="month-11">November</term>
+    <term name="month-12">December</term>
+    <term name="month-01" form="short">Jan.</term>
+    <term name="month-02" form="short">Feb.</term>
+    <term name="month-03" form="short">Mar.</term>
+    <term name="month-04" form="short">Apr.</term>
+    <term name="month-05" form="short">May</term>
+    <term name="month-06" form="short">Jun.</term>
+    <term name="month-07" form="short">Jul.</term>
+    <term name="month-08" form="short">Aug.</term>
+    <term name="month-09" form="short">Sep.</term>
+    <term name="month-10" form="short">Oct.</term>
+    <term name="month-11" form="short">Nov.</term>
+    <term name="month-12" form="short">Dec.</term>
+  </terms>
+</locale>"#;
+
+/// The form of a locale term.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(super) enum TermForm {
+    Long,
+    Short,
+    Verb,
+    VerbShort,
+    Symbol,
+}
+
+impl TermForm {
+    /// The form used when a term does not have this form.
+    const fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Long => None,
+            Self::Short | Self::Verb => Some(Self::Long),
+            Self::VerbShort => Some(Self::Verb),
+            Self::Symbol => Some(Self::Short),
+        }
+    }
+}
+
+impl FromStr for TermForm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "long" => Ok(Self::Long),
+            "short" => Ok(Self::Short),
+            "verb" => Ok(Self::Verb),
+            "verb-short" => Ok(Self::VerbShort),
+            "symbol" => Ok(Self::Symbol),
+            _ => Err(invalid(format!("Unknown term form '{s}'"))),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Term {
+    single: String,
+    multiple: String,
+}
+
+/// A CSL locale with the terms and date formats used to render a [`CslStyle`][super::CslStyle].
+///
+/// The [`Default`] locale is a subset of `en-US`, a full locale such as `locales-en-GB.xml` can be
+/// loaded from the CSL locales repository.
+#[derive(Clone, Debug)]
+pub struct Locale {
+    lang: Option<String>,
+    terms: HashMap<(String, TermForm), Term>,
+    dates: HashMap<DateForm, Date>,
+    punctuation_in_quote: Option<bool>,
+}
+
+impl Locale {
+    /// Loads a locale from a CSL locale file.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when the file cannot be read or is not a valid CSL locale.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| {
+            Error::wrap_with(
+                ErrorKind::IO,
+                err,
+                format!("Cannot read the locale file '{}'", path.display()),
+            )
+        })?;
+        text.parse()
+    }
+
+    /// The language of the locale, such as `en-US`.
+    #[must_use]
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    pub(super) fn parse(node: Node<'_, '_>) -> Result<Self, Error> {
+        let mut locale = Self {
+            lang: node.attribute((XML_NAMESPACE, "lang")).map(str::to_owned),
+            terms: HashMap::new(),
+            dates: HashMap::new(),
+            punctuation_in_quote: None,
+        };
+
+        for child in elements(node) {
+            match child.tag_name().name() {
+                "style-options" => {
+                    locale.punctuation_in_quote = child
+                        .attribute("punctuation-in-quote")
+                        .map(|value| value == "true");
+                }
+                "date" => {
+                    let date = Date::parse(child)?;
+                    if let Some(form) = date.form {
+                        locale.dates.insert(form, date);
+                    }
+                }
+                "terms" => {
+                    for term in elements(child) {
+                        let name = term.attribute("name").ok_or_else(|| {
+                            invalid("A locale 'term' element is missing the 'name' attribute")
+                        })?;
+                        let form = term
+                            .attribute("form")
+                            .map_or(Ok(TermForm::Long), str::parse)?;
+                        let text = |name: &str| {
+                            elements(term)
+                                .find(|c| c.tag_name().name() == name)
+                                .and_then(|c| c.text())
+                                .map(str::to_owned)
+                        };
+                        let single = text("single")
+                            .or_else(|| term.text().map(str::to_owned))
+                            .unwrap_or_default()
+                            .trim()
+                            .to_owned();
+                        let multiple = text("multiple").unwrap_or_else(|| single.clone());
+                        locale
+                            .terms
+                            .insert((name.to_owned(), form), Term { single, multiple });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(locale)
+    }
+
+    /// Overrides the terms, date formats and options with those of `other`.
+    pub(super) fn merge(&mut self, other: &Self) {
+        self.terms
+            .extend(other.terms.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.dates
+            .extend(other.dates.iter().map(|(k, v)| (*k, v.clone())));
+        if other.punctuation_in_quote.is_some() {
+            self.punctuation_in_quote = other.punctuation_in_quote;
+        }
+    }
+
+    /// Whether a locale in a style applies to this locale, which is when it has no language or
+    /// the language or primary language matches.
+    pub(super) fn applies_to(&self, other: &Self) -> bool {
+        match (&self.lang, &other.lang) {
+            (None, _) => true,
+            (Some(lang), Some(other)) => {
+                lang == other || other.split('-').next() == Some(lang.as_str())
+            }
+            (Some(_), None) => false,
+        }
+    }
+
+    /// The term with the form, or the fallback forms when the term does not have that form.
+    pub(super) fn term(&self, name: &str, form: TermForm, plural: bool) -> Option<&str> {
+        let mut form = Some(form);
+        while let Some(current) = form {
+            if let Some(term) = self.terms.get(&(name.to_owned(), current)) {
+                return Some(if plural { &term.multiple } else { &term.single });
+            }
+            form = current.fallback();
+        }
+        None
+    }
+
+    pub(super) fn date(&self, form: DateForm) -> Option<&Date> {
+        self.dates.get(&form)
+    }
+
+    pub(super) fn punctuation_in_quote(&self) -> bool {
+        self.punctuation_in_quote.unwrap_or(false)
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        EN_US.parse().expect("The en-US locale is valid")
+    }
+}
+
+impl FromStr for Locale {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document = Document::parse(s).map_err(|err| {
+            Error::wrap_with(
+                ErrorKind::Deserialize,
+                err,
+                "The CSL locale is not valid XML",
+            )
+        })?;
+        let root = document.root_element();
+        if root.tag_name().name() != "locale" {
+            return Err(invalid("The root element of a CSL locale must be 'locale'"));
+        }
+        Self::parse(root)
+    }
+}
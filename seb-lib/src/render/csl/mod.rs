@@ -0,0 +1,549 @@
+//! Rendering of entries with CSL 1.0.2 styles.
+//!
+//! A [`CslStyle`] is loaded from a `.csl` file, such as one from the CSL styles repository, and
+//! combined with a [`Locale`] in a [`CslRenderer`] to render entries as a bibliography or as
+//! in-text citations. Each [`Entry`] is converted to a CSL item, so any format that can be read
+//! into a [`Biblio`] can be rendered with any style.
+//!
+//! The processor supports the rendering elements, conditions, sorting and name options of the
+//! specification. Disambiguation, cite grouping and collapsing, and the `subsequent-author-substitute`
+//! option are not supported, and the `position`, `locator` and `disambiguate` conditions are
+//! always false.
+//!
+//! # Examples
+//!
+//! ```
+//! use seb::{
+//!     ast::{Biblio, Entry, EntryKind},
+//!     render::{
+//!         csl::{CslRenderer, CslStyle, Locale},
+//!         Output,
+//!     },
+//! };
+//!
+//! let style: CslStyle = r#"
+//! <style xmlns="http://purl.org/net/xbiblio/csl" class="in-text" version="1.0">
+//!   <citation>
+//!     <layout prefix="(" suffix=")" delimiter="; ">
+//!       <names variable="author">
+//!         <name form="short" and="symbol"/>
+//!       </names>
+//!       <date variable="issued" prefix=", ">
+//!         <date-part name="year"/>
+//!       </date>
+//!     </layout>
+//!   </citation>
+//! </style>"#
+//!     .parse()
+//!     .unwrap();
+//!
+//! let mut resolver = Entry::resolver_with_cite(EntryKind::Book, "knuth1997");
+//! resolver.author("Knuth, Donald E.");
+//! resolver.title("The Art of Computer Programming");
+//! resolver.publisher("Addison-Wesley");
+//! resolver.year("1997");
+//! let biblio = Biblio::new(vec![resolver.resolve().unwrap()]);
+//!
+//! let renderer = CslRenderer::new(style, Locale::default(), Output::Text);
+//!
+//! assert_eq!("(Knuth, 1997)", renderer.render_citation(&biblio, &["knuth1997"]).unwrap());
+//! ```
+
+mod eval;
+mod item;
+mod locale;
+mod style;
+
+use std::{fs, path::Path};
+
+pub use locale::Locale;
+pub use style::CslStyle;
+
+use eval::{compare_keys, tidy_punctuation, Evaluator};
+use item::Item;
+use style::Section;
+
+use super::Output;
+use crate::{
+    ast::{Biblio, Entry},
+    Error, ErrorKind,
+};
+
+impl CslStyle {
+    /// Loads a style from a `.csl` file.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when the file cannot be read or is not a valid CSL style.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| {
+            Error::wrap_with(
+                ErrorKind::IO,
+                err,
+                format!("Cannot read the style file '{}'", path.display()),
+            )
+        })?;
+        text.parse()
+    }
+}
+
+/// Renders entries as a bibliography or as citations using a [`CslStyle`] and [`Locale`].
+#[derive(Clone, Debug)]
+pub struct CslRenderer {
+    style: CslStyle,
+    locale: Locale,
+    output: Output,
+}
+
+impl CslRenderer {
+    /// Creates a new [`CslRenderer`] for the style, locale and output markup.
+    ///
+    /// Any `locale` elements in the style that apply to the language of the locale override the
+    /// terms of the locale.
+    #[must_use]
+    pub fn new(style: CslStyle, locale: Locale, output: Output) -> Self {
+        let mut locale = locale;
+        // later locales override earlier ones, so the locales are applied in the order of the CSL
+        // fallback, which is a dialect such as `de-AT`, then a language such as `de` and last the
+        // locales without a language
+        let mut overrides = style
+            .locales
+            .iter()
+            .filter(|other| other.applies_to(&locale))
+            .collect::<Vec<_>>();
+        overrides.sort_by_key(|other| match other.lang() {
+            None => 0,
+            Some(lang) if !lang.contains('-') => 1,
+            Some(_) => 2,
+        });
+        let overrides = overrides.into_iter().cloned().collect::<Vec<_>>();
+        for other in &overrides {
+            locale.merge(other);
+        }
+
+        Self {
+            style,
+            locale,
+            output,
+        }
+    }
+
+    /// Renders the entries of a [`Biblio`] as a bibliography.
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when the style does not have a `bibliography` element.
+    pub fn render_biblio(&self, biblio: &Biblio) -> Result<String, Error> {
+        self.render_entries(biblio.entries())
+    }
+
+    /// Renders the entries as a bibliography, in the same way as [`CslRenderer::render_biblio`].
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when the style does not have a `bibliography` element.
+    pub fn render_entries<'e, I>(&self, entries: I) -> Result<String, Error>
+    where
+        I: IntoIterator<Item = &'e Entry>,
+    {
+        let section = self.style.bibliography.as_ref().ok_or_else(|| {
+            Error::new(
+                ErrorKind::NoValue,
+                "The CSL style does not have a bibliography",
+            )
+        })?;
+
+        let items = self.numbered(entries.into_iter().map(Item::new).collect());
+        let references = items
+            .iter()
+            .map(|item| self.evaluator(section, item).layout())
+            .map(|reference| self.affixes(section, &reference))
+            .collect::<Vec<_>>();
+
+        Ok(match self.output {
+            Output::Text => references.join("\n"),
+            Output::Markdown => references.join("\n\n"),
+            Output::Html => {
+                let entries = references
+                    .into_iter()
+                    .map(|reference| format!("  <div class=\"csl-entry\">{reference}</div>"))
+                    .collect::<Vec<_>>();
+                format!(
+                    "<div class=\"csl-bib-body\">\n{}\n</div>",
+                    entries.join("\n")
+                )
+            }
+        })
+    }
+
+    /// Renders an in-text citation of the entries with the cite keys.
+    ///
+    /// Citation numbers are taken from the order of the bibliography of all the entries of the
+    /// [`Biblio`].
+    ///
+    /// # Errors
+    ///
+    /// An `Err` is returned when no entry has one of the cite keys.
+    pub fn render_citation<S: AsRef<str>>(
+        &self,
+        biblio: &Biblio,
+        cites: &[S],
+    ) -> Result<String, Error> {
+        let items = self.numbered(biblio.entries().map(Item::new).collect());
+        let selected = cites
+            .iter()
+            .map(|cite| {
+                let cite = cite.as_ref();
+                items.iter().find(|item| item.cite == cite).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::NoValue,
+                        format!("No entry found with the cite key of '{cite}'"),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let section = &self.style.citation;
+        let selected = self.sorted(section, selected);
+        let citations = selected
+            .into_iter()
+            .map(|item| self.evaluator(section, item).layout())
+            .filter(|citation| !citation.is_empty())
+            .collect::<Vec<_>>();
+
+        let escaped = match self.output {
+            Output::Text => section.delimiter.clone(),
+            Output::Html => super::escape_html(&section.delimiter),
+            Output::Markdown => super::escape_markdown(&section.delimiter),
+        };
+        Ok(self.affixes(section, &citations.join(&escaped)))
+    }
+
+    const fn evaluator<'a>(&'a self, section: &'a Section, item: &'a Item) -> Evaluator<'a> {
+        Evaluator::new(&self.style, &self.locale, section, self.output, item)
+    }
+
+    /// Sorts the items by the bibliography and numbers them in that order.
+    ///
+    /// The items are first ordered by cite key, which is the order used when the bibliography is
+    /// not sorted as a [`Biblio`] does not keep the order of its entries.
+    fn numbered(&self, items: Vec<Item>) -> Vec<Item> {
+        let mut items = items;
+        items.sort_by(|a, b| a.cite.cmp(&b.cite));
+        for (i, item) in items.iter_mut().enumerate() {
+            item.set_citation_number(i + 1);
+        }
+
+        let Some(section) = &self.style.bibliography else {
+            return items;
+        };
+        let order = self
+            .sorted(section, items.iter().collect())
+            .into_iter()
+            .map(|item| item.cite.clone())
+            .collect::<Vec<_>>();
+        items.sort_by_key(|item| order.iter().position(|cite| *cite == item.cite));
+
+        for (i, item) in items.iter_mut().enumerate() {
+            item.set_citation_number(i + 1);
+        }
+        items
+    }
+
+    fn sorted<'i>(&self, section: &Section, items: Vec<&'i Item>) -> Vec<&'i Item> {
+        if section.sort.is_empty() {
+            return items;
+        }
+        let mut keyed = items
+            .into_iter()
+            .map(|item| (self.evaluator(section, item).sort_keys(), item))
+            .collect::<Vec<_>>();
+        keyed.sort_by(|(a, _), (b, _)| compare_keys(&section.sort, a, b));
+        keyed.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// Adds the affixes of the layout of the section and tidies the punctuation.
+    fn affixes(&self, section: &Section, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        let escape = |s: &str| match self.output {
+            Output::Text => s.to_owned(),
+            Output::Html => super::escape_html(s),
+            Output::Markdown => super::escape_markdown(s),
+        };
+        let text = format!(
+            "{}{text}{}",
+            escape(&section.formatting.prefix),
+            escape(&section.formatting.suffix)
+        );
+        tidy_punctuation(&text, self.locale.punctuation_in_quote())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::locale::TermForm;
+    use super::*;
+    use crate::ast::EntryKind;
+
+    /// A cut down version of an author-date style that is similar to APA.
+    const AUTHOR_DATE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<style xmlns="http://purl.org/net/xbiblio/csl" class="in-text" version="1.0"
+       demote-non-dropping-particle="never">
+  <info>
+    <title>Author-date test style</title>
+    <id>author-date</id>
+  </info>
+  <locale xml:lang="en">
+    <terms>
+      <term name="editor" form="short">
+        <single>Ed.</single>
+        <multiple>Eds.</multiple>
+      </term>
+    </terms>
+  </locale>
+  <macro name="author">
+    <names variable="author">
+      <name name-as-sort-order="all" and="symbol" sort-separator=", " initialize-with=". "
+            delimiter=", " delimiter-precedes-last="always"/>
+      <label form="short" prefix=" (" suffix=")" text-case="capitalize-first"/>
+      <substitute>
+        <names variable="editor">
+          <label form="short" prefix=" (" suffix=")" text-case="capitalize-first"/>
+        </names>
+        <text variable="title"/>
+      </substitute>
+    </names>
+  </macro>
+  <macro name="issued">
+    <choose>
+      <if variable="issued">
+        <date variable="issued">
+          <date-part name="year"/>
+        </date>
+      </if>
+      <else>
+        <text term="no date" form="short"/>
+      </else>
+    </choose>
+  </macro>
+  <macro name="title">
+    <choose>
+      <if type="book report thesis" match="any">
+        <text variable="title" font-style="italic"/>
+      </if>
+      <else>
+        <text variable="title"/>
+      </else>
+    </choose>
+  </macro>
+  <citation et-al-min="3" et-al-use-first="1">
+    <sort>
+      <key macro="author"/>
+      <key macro="issued"/>
+    </sort>
+    <layout prefix="(" suffix=")" delimiter="; ">
+      <group delimiter=", ">
+        <names variable="author">
+          <name form="short" and="symbol" delimiter=", "/>
+          <substitute>
+            <names variable="editor"/>
+            <text variable="title"/>
+          </substitute>
+        </names>
+        <text macro="issued"/>
+      </group>
+    </layout>
+  </citation>
+  <bibliography>
+    <sort>
+      <key macro="author"/>
+      <key macro="issued"/>
+    </sort>
+    <layout suffix=".">
+      <group delimiter=" ">
+        <text macro="author" suffix="."/>
+        <text macro="issued" prefix="(" suffix=")."/>
+        <text macro="title" suffix="."/>
+      </group>
+      <group prefix=" " delimiter=", ">
+        <text variable="container-title" font-style="italic"/>
+        <group>
+          <text variable="volume" font-style="italic"/>
+          <text variable="issue" prefix="(" suffix=")"/>
+        </group>
+        <text variable="page"/>
+      </group>
+      <text variable="publisher" prefix=" "/>
+    </layout>
+  </bibliography>
+</style>"#;
+
+    /// A cut down version of a numeric style that is similar to IEEE.
+    const NUMERIC: &str = r#"<style xmlns="http://purl.org/net/xbiblio/csl" class="in-text" version="1.0">
+  <citation>
+    <sort>
+      <key variable="citation-number"/>
+    </sort>
+    <layout prefix="[" suffix="]" delimiter=", ">
+      <text variable="citation-number"/>
+    </layout>
+  </citation>
+  <bibliography>
+    <layout>
+      <text variable="citation-number" prefix="[" suffix="] "/>
+      <names variable="author" suffix=", ">
+        <name initialize-with=". " and="text" delimiter=", "/>
+      </names>
+      <text variable="title" quotes="true" suffix=", "/>
+      <group delimiter=", " suffix=".">
+        <text variable="container-title" font-style="italic"/>
+        <group delimiter=" ">
+          <label variable="page" form="short"/>
+          <text variable="page"/>
+        </group>
+        <date variable="issued" form="text" date-parts="year-month"/>
+      </group>
+    </layout>
+  </bibliography>
+</style>"#;
+
+    fn biblio() -> Biblio {
+        let mut article = Entry::resolver_with_cite(EntryKind::Article, "knuth1984");
+        article.author("Knuth, Donald E.");
+        article.title("Literate Programming");
+        article.journal("The Computer Journal");
+        article.year("1984");
+        article.set_field("month", "may");
+        article.set_field("volume", "27");
+        article.set_field("number", "2");
+        article.set_field("pages", "97--111");
+
+        let mut book = Entry::resolver_with_cite(EntryKind::Book, "abelson1996");
+        book.author("Abelson, Harold and Sussman, Gerald Jay and Sussman, Julie");
+        book.title("Structure and Interpretation of Computer Programs");
+        book.publisher("MIT Press");
+        book.year("1996");
+
+        let mut edited = Entry::resolver_with_cite(EntryKind::Other("misc".into()), "edited");
+        edited.title("Collected Papers");
+        edited.set_field("editor", "Lamport, Leslie");
+
+        Biblio::new(vec![
+            article.resolve().expect("All article fields are set"),
+            book.resolve().expect("All book fields are set"),
+            edited.resolve().expect("The title is set"),
+        ])
+    }
+
+    fn renderer(style: &str, output: Output) -> CslRenderer {
+        CslRenderer::new(
+            style.parse().expect("The style is valid"),
+            Locale::default(),
+            output,
+        )
+    }
+
+    #[test]
+    fn author_date_bibliography() {
+        let renderer = renderer(AUTHOR_DATE, Output::Text);
+
+        assert_eq!(
+            "Abelson, H., Sussman, G. J., & Sussman, J. (1996). Structure and Interpretation of \
+             Computer Programs. MIT Press.\n\
+             Knuth, D. E. (1984). Literate Programming. The Computer Journal, 27(2), 97–111.\n\
+             Lamport, L. (Ed.). (n.d.). Collected Papers.",
+            renderer.render_biblio(&biblio()).unwrap()
+        );
+    }
+
+    #[test]
+    fn author_date_citations() {
+        let renderer = renderer(AUTHOR_DATE, Output::Text);
+
+        assert_eq!(
+            "(Abelson et al., 1996; Knuth, 1984)",
+            renderer
+                .render_citation(&biblio(), &["knuth1984", "abelson1996"])
+                .unwrap()
+        );
+        assert_eq!(
+            "(Lamport, n.d.)",
+            renderer.render_citation(&biblio(), &["edited"]).unwrap()
+        );
+        assert!(renderer.render_citation(&biblio(), &["missing"]).is_err());
+    }
+
+    #[test]
+    fn numeric_bibliography_and_citations() {
+        let renderer = renderer(NUMERIC, Output::Html);
+
+        assert_eq!(
+            "<div class=\"csl-bib-body\">\n  \
+             <div class=\"csl-entry\">[1] H. Abelson, G. J. Sussman, and J. Sussman, \
+             “Structure and Interpretation of Computer Programs,” 1996.</div>\n  \
+             <div class=\"csl-entry\">[2] “Collected Papers,”</div>\n  \
+             <div class=\"csl-entry\">[3] D. E. Knuth, “Literate Programming,” \
+             <i>The Computer Journal</i>, pp. 97–111, May 1984.</div>\n\
+             </div>",
+            renderer.render_biblio(&biblio()).unwrap()
+        );
+        assert_eq!(
+            "[1, 3]",
+            renderer
+                .render_citation(&biblio(), &["abelson1996", "knuth1984"])
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_styles() {
+        assert!("<style/>".parse::<CslStyle>().is_err());
+        assert!("<locale/>".parse::<CslStyle>().is_err());
+        assert!(
+            "<style><citation><layout><unknown/></layout></citation></style>"
+                .parse::<CslStyle>()
+                .is_err()
+        );
+
+        let style: CslStyle = "<style><citation><layout/></citation></style>"
+            .parse()
+            .unwrap();
+        assert!(!style.has_bibliography());
+        assert!(CslRenderer::new(style, Locale::default(), Output::Text)
+            .render_biblio(&biblio())
+            .is_err());
+    }
+
+    #[test]
+    fn style_locales_of_a_language_override_those_without_one() {
+        let style: CslStyle = r#"<style xmlns="http://purl.org/net/xbiblio/csl" version="1.0">
+  <locale xml:lang="en-US">
+    <terms><term name="editor">dialect editor</term></terms>
+  </locale>
+  <locale xml:lang="en">
+    <terms>
+      <term name="and">language and</term>
+      <term name="editor">language editor</term>
+    </terms>
+  </locale>
+  <locale>
+    <terms>
+      <term name="and">any and</term>
+      <term name="page">any page</term>
+    </terms>
+  </locale>
+  <citation><layout/></citation>
+</style>"#
+            .parse()
+            .unwrap();
+        let renderer = CslRenderer::new(style, Locale::default(), Output::Text);
+        let term = |name| renderer.locale.term(name, TermForm::Long, false);
+
+        assert_eq!(Some("dialect editor"), term("editor"));
+        assert_eq!(Some("language and"), term("and"));
+        assert_eq!(Some("any page"), term("page"));
+    }
+}
@@ -0,0 +1,613 @@
+use std::{collections::HashMap, str::FromStr};
+
+use roxmltree::{Document, Node};
+
+use super::{locale::TermForm, Locale};
+use crate::{Error, ErrorKind};
+
+/// The attributes of a `name` element, which can also be inherited from the `citation`,
+/// `bibliography` and `style` elements.
+const NAME_OPTIONS: [&str; 11] = [
+    "and",
+    "delimiter",
+    "delimiter-precedes-et-al",
+    "delimiter-precedes-last",
+    "et-al-min",
+    "et-al-use-first",
+    "form",
+    "initialize",
+    "initialize-with",
+    "name-as-sort-order",
+    "sort-separator",
+];
+
+pub(super) fn invalid<S: Into<String>>(message: S) -> Error {
+    Error::new(ErrorKind::Deserialize, message)
+}
+
+/// The child elements, ignoring text and comments.
+pub(super) fn elements<'a, 'i>(node: Node<'a, 'i>) -> impl Iterator<Item = Node<'a, 'i>> {
+    node.children().filter(Node::is_element)
+}
+
+fn child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    elements(node).find(|child| child.tag_name().name() == name)
+}
+
+fn attribute(node: Node<'_, '_>, name: &str) -> Option<String> {
+    node.attribute(name).map(str::to_owned)
+}
+
+fn required(node: Node<'_, '_>, name: &str) -> Result<String, Error> {
+    attribute(node, name).ok_or_else(|| {
+        invalid(format!(
+            "The '{}' element is missing the '{name}' attribute",
+            node.tag_name().name()
+        ))
+    })
+}
+
+/// The case a value is rendered in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum TextCase {
+    Lowercase,
+    Uppercase,
+    CapitalizeFirst,
+    CapitalizeAll,
+    Sentence,
+    Title,
+}
+
+/// The formatting attributes shared by the rendering elements.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Default)]
+pub(super) struct Formatting {
+    pub(super) prefix: String,
+    pub(super) suffix: String,
+    pub(super) italic: bool,
+    pub(super) bold: bool,
+    pub(super) small_caps: bool,
+    pub(super) superscript: bool,
+    pub(super) subscript: bool,
+    pub(super) text_case: Option<TextCase>,
+    pub(super) quotes: bool,
+    pub(super) strip_periods: bool,
+}
+
+impl Formatting {
+    fn parse(node: Node<'_, '_>) -> Self {
+        Self {
+            prefix: attribute(node, "prefix").unwrap_or_default(),
+            suffix: attribute(node, "suffix").unwrap_or_default(),
+            italic: node.attribute("font-style") == Some("italic")
+                || node.attribute("font-style") == Some("oblique"),
+            bold: node.attribute("font-weight") == Some("bold"),
+            small_caps: node.attribute("font-variant") == Some("small-caps"),
+            superscript: node.attribute("vertical-align") == Some("sup"),
+            subscript: node.attribute("vertical-align") == Some("sub"),
+            text_case: match node.attribute("text-case") {
+                Some("lowercase") => Some(TextCase::Lowercase),
+                Some("uppercase") => Some(TextCase::Uppercase),
+                Some("capitalize-first") => Some(TextCase::CapitalizeFirst),
+                Some("capitalize-all") => Some(TextCase::CapitalizeAll),
+                Some("sentence") => Some(TextCase::Sentence),
+                Some("title") => Some(TextCase::Title),
+                _ => None,
+            },
+            quotes: node.attribute("quotes") == Some("true"),
+            strip_periods: node.attribute("strip-periods") == Some("true"),
+        }
+    }
+}
+
+/// The source of the value of a `text` element.
+#[derive(Clone, Debug)]
+pub(super) enum TextSource {
+    Variable {
+        name: String,
+        short: bool,
+    },
+    Macro(String),
+    Term {
+        name: String,
+        form: TermForm,
+        plural: bool,
+    },
+    Value(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(super) enum DateForm {
+    Text,
+    Numeric,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum DatePartName {
+    Year,
+    Month,
+    Day,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct DatePart {
+    pub(super) name: DatePartName,
+    pub(super) form: Option<String>,
+    pub(super) formatting: Formatting,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Date {
+    pub(super) variable: String,
+    /// A localized date format from the locale.
+    pub(super) form: Option<DateForm>,
+    /// The number of date parts shown by a localized date, 3 for `year-month-day`.
+    pub(super) shown: usize,
+    pub(super) parts: Vec<DatePart>,
+    pub(super) delimiter: String,
+    pub(super) formatting: Formatting,
+}
+
+impl Date {
+    pub(super) fn parse(node: Node<'_, '_>) -> Result<Self, Error> {
+        let form = match node.attribute("form") {
+            Some("text") => Some(DateForm::Text),
+            Some("numeric") => Some(DateForm::Numeric),
+            Some(form) => return Err(invalid(format!("Unknown date form '{form}'"))),
+            None => None,
+        };
+
+        let parts = elements(node)
+            .filter(|child| child.tag_name().name() == "date-part")
+            .map(|child| {
+                let name = match required(child, "name")?.as_str() {
+                    "year" => DatePartName::Year,
+                    "month" => DatePartName::Month,
+                    "day" => DatePartName::Day,
+                    name => return Err(invalid(format!("Unknown date part '{name}'"))),
+                };
+                Ok(DatePart {
+                    name,
+                    form: attribute(child, "form"),
+                    formatting: Formatting::parse(child),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self {
+            variable: attribute(node, "variable").unwrap_or_default(),
+            form,
+            shown: match node.attribute("date-parts") {
+                Some("year") => 1,
+                Some("year-month") => 2,
+                _ => 3,
+            },
+            parts,
+            delimiter: attribute(node, "delimiter").unwrap_or_default(),
+            formatting: Formatting::parse(node),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum NumberForm {
+    Numeric,
+    Ordinal,
+    LongOrdinal,
+    Roman,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Plural {
+    Contextual,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Label {
+    /// The variable of the label, which is empty for a label of a `names` element.
+    pub(super) variable: String,
+    pub(super) form: TermForm,
+    pub(super) plural: Plural,
+    pub(super) formatting: Formatting,
+}
+
+impl Label {
+    fn parse(node: Node<'_, '_>) -> Result<Self, Error> {
+        Ok(Self {
+            variable: attribute(node, "variable").unwrap_or_default(),
+            form: term_form(node)?,
+            plural: match node.attribute("plural") {
+                Some("always") => Plural::Always,
+                Some("never") => Plural::Never,
+                _ => Plural::Contextual,
+            },
+            formatting: Formatting::parse(node),
+        })
+    }
+}
+
+/// The `name` element of a `names` element with the options that were set on it.
+#[derive(Clone, Debug, Default)]
+pub(super) struct NameFormat {
+    pub(super) options: HashMap<String, String>,
+    pub(super) family: Formatting,
+    pub(super) given: Formatting,
+    pub(super) formatting: Formatting,
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct Names {
+    pub(super) variables: Vec<String>,
+    pub(super) name: NameFormat,
+    pub(super) et_al: Option<(String, Formatting)>,
+    /// The label of the names and whether it comes before the names.
+    pub(super) label: Option<(Label, bool)>,
+    pub(super) substitute: Vec<Element>,
+    pub(super) delimiter: Option<String>,
+    pub(super) formatting: Formatting,
+}
+
+impl Names {
+    fn parse(node: Node<'_, '_>, parent: Option<&NameFormat>) -> Result<Self, Error> {
+        let name = match child(node, "name") {
+            Some(name) => {
+                let mut format = NameFormat {
+                    options: NAME_OPTIONS
+                        .iter()
+                        .filter_map(|option| {
+                            name.attribute(*option)
+                                .map(|value| ((*option).to_owned(), value.to_owned()))
+                        })
+                        .collect(),
+                    formatting: Formatting::parse(name),
+                    ..NameFormat::default()
+                };
+                for part in elements(name).filter(|c| c.tag_name().name() == "name-part") {
+                    match part.attribute("name") {
+                        Some("family") => format.family = Formatting::parse(part),
+                        Some("given") => format.given = Formatting::parse(part),
+                        _ => {}
+                    }
+                }
+                format
+            }
+            // names in a substitute without a name element use the name of the parent names
+            None => parent.cloned().unwrap_or_default(),
+        };
+
+        let et_al = child(node, "et-al").map(|et_al| {
+            (
+                attribute(et_al, "term").unwrap_or_else(|| "et-al".to_owned()),
+                Formatting::parse(et_al),
+            )
+        });
+
+        let name_index = elements(node).position(|c| c.tag_name().name() == "name");
+        let label = elements(node)
+            .enumerate()
+            .find(|(_, c)| c.tag_name().name() == "label")
+            .map(|(i, label)| {
+                Ok::<_, Error>((Label::parse(label)?, name_index.is_some_and(|n| i < n)))
+            })
+            .transpose()?;
+
+        let substitute = match child(node, "substitute") {
+            Some(substitute) => elements(substitute)
+                .map(|element| Element::parse_with(element, Some(&name)))
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            variables: required(node, "variable")?
+                .split_whitespace()
+                .map(str::to_owned)
+                .collect(),
+            name,
+            et_al,
+            label,
+            substitute,
+            delimiter: attribute(node, "delimiter"),
+            formatting: Formatting::parse(node),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Match {
+    All,
+    Any,
+    None,
+}
+
+/// A condition of an `if` or `else-if` element.
+#[derive(Clone, Debug)]
+pub(super) struct Condition {
+    pub(super) types: Vec<String>,
+    pub(super) variables: Vec<String>,
+    pub(super) is_numeric: Vec<String>,
+    /// The number of tests that are not supported, which are always false.
+    pub(super) unsupported: usize,
+    pub(super) matches: Match,
+}
+
+impl Condition {
+    fn parse(node: Node<'_, '_>) -> Self {
+        let list = |name: &str| {
+            node.attribute(name)
+                .map(|value| value.split_whitespace().map(str::to_owned).collect())
+                .unwrap_or_default()
+        };
+        let unsupported = ["disambiguate", "is-uncertain-date", "locator", "position"]
+            .iter()
+            .filter_map(|name| node.attribute(*name))
+            .map(|value| value.split_whitespace().count())
+            .sum();
+
+        Self {
+            types: list("type"),
+            variables: list("variable"),
+            is_numeric: list("is-numeric"),
+            unsupported,
+            matches: match node.attribute("match") {
+                Some("any") => Match::Any,
+                Some("none") => Match::None,
+                _ => Match::All,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(super) enum Element {
+    Text {
+        source: TextSource,
+        formatting: Formatting,
+    },
+    Date(Date),
+    Number {
+        variable: String,
+        form: NumberForm,
+        formatting: Formatting,
+    },
+    Names(Box<Names>),
+    Label(Label),
+    Group {
+        children: Vec<Element>,
+        delimiter: String,
+        formatting: Formatting,
+    },
+    Choose {
+        branches: Vec<(Condition, Vec<Element>)>,
+        otherwise: Vec<Element>,
+    },
+}
+
+fn term_form(node: Node<'_, '_>) -> Result<TermForm, Error> {
+    match node.attribute("form") {
+        Some(form) => TermForm::from_str(form),
+        None => Ok(TermForm::Long),
+    }
+}
+
+impl Element {
+    fn parse(node: Node<'_, '_>) -> Result<Self, Error> {
+        Self::parse_with(node, None)
+    }
+
+    fn parse_with(node: Node<'_, '_>, name: Option<&NameFormat>) -> Result<Self, Error> {
+        let formatting = Formatting::parse(node);
+        let children = |node: Node<'_, '_>| {
+            elements(node)
+                .map(|child| Self::parse_with(child, name))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let element = match node.tag_name().name() {
+            "text" => {
+                let source = if let Some(variable) = attribute(node, "variable") {
+                    TextSource::Variable {
+                        name: variable,
+                        short: node.attribute("form") == Some("short"),
+                    }
+                } else if let Some(name) = attribute(node, "macro") {
+                    TextSource::Macro(name)
+                } else if let Some(term) = attribute(node, "term") {
+                    TextSource::Term {
+                        name: term,
+                        form: term_form(node)?,
+                        plural: node.attribute("plural") == Some("true"),
+                    }
+                } else if let Some(value) = attribute(node, "value") {
+                    TextSource::Value(value)
+                } else {
+                    return Err(invalid(
+                        "A 'text' element must have a variable, macro, term or value attribute",
+                    ));
+                };
+                Self::Text { source, formatting }
+            }
+            "date" => Self::Date(Date::parse(node)?),
+            "number" => Self::Number {
+                variable: required(node, "variable")?,
+                form: match node.attribute("form") {
+                    Some("ordinal") => NumberForm::Ordinal,
+                    Some("long-ordinal") => NumberForm::LongOrdinal,
+                    Some("roman") => NumberForm::Roman,
+                    _ => NumberForm::Numeric,
+                },
+                formatting,
+            },
+            "names" => Self::Names(Box::new(Names::parse(node, name)?)),
+            "label" => Self::Label(Label::parse(node)?),
+            "group" => Self::Group {
+                children: children(node)?,
+                delimiter: attribute(node, "delimiter").unwrap_or_default(),
+                formatting,
+            },
+            "choose" => {
+                let mut branches = Vec::new();
+                let mut otherwise = Vec::new();
+                for branch in elements(node) {
+                    match branch.tag_name().name() {
+                        "if" | "else-if" => {
+                            branches.push((Condition::parse(branch), children(branch)?));
+                        }
+                        "else" => otherwise = children(branch)?,
+                        other => return Err(invalid(format!("Unknown 'choose' branch '{other}'"))),
+                    }
+                }
+                Self::Choose {
+                    branches,
+                    otherwise,
+                }
+            }
+            other => return Err(invalid(format!("Unknown rendering element '{other}'"))),
+        };
+
+        Ok(element)
+    }
+}
+
+/// A key used to sort the citations or the bibliography.
+#[derive(Clone, Debug)]
+pub(super) enum SortKey {
+    Variable { name: String, descending: bool },
+    Macro { name: String, descending: bool },
+}
+
+/// The `citation` or `bibliography` element of a style.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Section {
+    pub(super) layout: Vec<Element>,
+    pub(super) delimiter: String,
+    pub(super) formatting: Formatting,
+    pub(super) sort: Vec<SortKey>,
+    pub(super) options: HashMap<String, String>,
+}
+
+impl Section {
+    fn parse(node: Node<'_, '_>) -> Result<Self, Error> {
+        let layout = child(node, "layout").ok_or_else(|| {
+            invalid(format!(
+                "The '{}' element is missing a 'layout' element",
+                node.tag_name().name()
+            ))
+        })?;
+
+        let sort = match child(node, "sort") {
+            Some(sort) => elements(sort)
+                .map(|key| {
+                    let descending = key.attribute("sort") == Some("descending");
+                    if let Some(name) = attribute(key, "variable") {
+                        Ok(SortKey::Variable { name, descending })
+                    } else {
+                        Ok(SortKey::Macro {
+                            name: required(key, "macro")?,
+                            descending,
+                        })
+                    }
+                })
+                .collect::<Result<_, Error>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            layout: elements(layout)
+                .map(Element::parse)
+                .collect::<Result<_, _>>()?,
+            delimiter: attribute(layout, "delimiter").unwrap_or_default(),
+            formatting: Formatting::parse(layout),
+            sort,
+            options: options(node),
+        })
+    }
+}
+
+fn options(node: Node<'_, '_>) -> HashMap<String, String> {
+    node.attributes()
+        .map(|attribute| (attribute.name().to_owned(), attribute.value().to_owned()))
+        .collect()
+}
+
+/// A CSL 1.0.2 style loaded from a `.csl` file.
+///
+/// Only the parts of the style used to render citations and a bibliography are kept, the `info`
+/// element is ignored apart from the title.
+#[derive(Clone, Debug)]
+pub struct CslStyle {
+    title: String,
+    pub(super) macros: HashMap<String, Vec<Element>>,
+    pub(super) citation: Section,
+    pub(super) bibliography: Option<Section>,
+    pub(super) locales: Vec<Locale>,
+    pub(super) options: HashMap<String, String>,
+}
+
+impl CslStyle {
+    /// The title of the style from the `info` element.
+    #[must_use]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Whether the style has a `bibliography` element.
+    #[must_use]
+    pub const fn has_bibliography(&self) -> bool {
+        self.bibliography.is_some()
+    }
+}
+
+impl FromStr for CslStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document = Document::parse(s).map_err(|err| {
+            Error::wrap_with(
+                ErrorKind::Deserialize,
+                err,
+                "The CSL style is not valid XML",
+            )
+        })?;
+        let root = document.root_element();
+        if root.tag_name().name() != "style" {
+            return Err(invalid("The root element of a CSL style must be 'style'"));
+        }
+        if child(root, "citation").is_none() {
+            return Err(invalid("The CSL style is missing a 'citation' element"));
+        }
+
+        let mut style = Self {
+            title: child(root, "info")
+                .and_then(|info| child(info, "title"))
+                .and_then(|title| title.text())
+                .unwrap_or_default()
+                .trim()
+                .to_owned(),
+            macros: HashMap::new(),
+            citation: Section::default(),
+            bibliography: None,
+            locales: Vec::new(),
+            options: options(root),
+        };
+
+        for node in elements(root) {
+            match node.tag_name().name() {
+                "macro" => {
+                    let elements = elements(node)
+                        .map(Element::parse)
+                        .collect::<Result<_, _>>()?;
+                    style.macros.insert(required(node, "name")?, elements);
+                }
+                "citation" => style.citation = Section::parse(node)?,
+                "bibliography" => style.bibliography = Some(Section::parse(node)?),
+                "locale" => style.locales.push(Locale::parse(node)?),
+                _ => {}
+            }
+        }
+
+        Ok(style)
+    }
+}
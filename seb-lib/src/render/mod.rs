@@ -0,0 +1,349 @@
+//! Rendering of entries as formatted references in a citation style.
+//!
+//! A [`Renderer`] combines a built-in [`Style`] with an [`Output`] markup to render a single
+//! [`Entry`] or a whole [`Biblio`] as a reference list. The styles work off the entry kinds and
+//! fields of the [`ast`][crate::ast] module, so missing optional fields are left out of the
+//! reference.
+//!
+//! # Examples
+//!
+//! ```
+//! use seb::{
+//!     ast::{Entry, EntryKind},
+//!     render::{Output, Renderer, Style},
+//! };
+//!
+//! let mut resolver = Entry::resolver_with_cite(EntryKind::Book, "knuth1997");
+//! resolver.author("Knuth, Donald E.");
+//! resolver.title("The Art of Computer Programming");
+//! resolver.publisher("Addison-Wesley");
+//! resolver.year("1997");
+//! let entry = resolver.resolve().unwrap();
+//!
+//! let renderer = Renderer::new(Style::Apa, Output::Markdown);
+//!
+//! assert_eq!(
+//!     "Knuth, D. E. (1997). *The Art of Computer Programming*. Addison-Wesley.",
+//!     renderer.render_entry(&entry)
+//! );
+//! ```
+
+#[cfg(feature = "csl")]
+pub mod csl;
+mod name;
+mod style;
+
+use std::str::FromStr;
+
+pub use name::Name;
+
+use crate::{
+    ast::{Biblio, Entry},
+    Error, ErrorKind,
+};
+
+/// A built-in citation style.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    /// APA 7th edition.
+    Apa,
+    /// IEEE reference style, the references are numbered.
+    Ieee,
+    /// Chicago Manual of Style 17th edition author-date.
+    ChicagoAuthorDate,
+    /// Harvard (Cite Them Right).
+    Harvard,
+}
+
+impl Style {
+    /// All of the built-in styles.
+    pub const ALL: [Self; 4] = [
+        Self::Apa,
+        Self::Ieee,
+        Self::ChicagoAuthorDate,
+        Self::Harvard,
+    ];
+
+    /// The short name of the style used by [`Style::from_str`].
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Apa => "apa",
+            Self::Ieee => "ieee",
+            Self::ChicagoAuthorDate => "chicago",
+            Self::Harvard => "harvard",
+        }
+    }
+
+    /// Whether references in this style are numbered and kept in citation order instead of being
+    /// sorted by author.
+    #[must_use]
+    pub const fn is_numbered(self) -> bool {
+        matches!(self, Self::Ieee)
+    }
+}
+
+impl FromStr for Style {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "apa" | "apa7" => Ok(Self::Apa),
+            "ieee" => Ok(Self::Ieee),
+            "chicago" | "chicago-author-date" => Ok(Self::ChicagoAuthorDate),
+            "harvard" => Ok(Self::Harvard),
+            _ => Err(Error::new(
+                ErrorKind::Deserialize,
+                format!(
+                    "Unknown citation style '{s}' - expected one of apa, ieee, chicago or harvard"
+                ),
+            )),
+        }
+    }
+}
+
+/// The markup used for a rendered reference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Output {
+    /// Plain text without any emphasis.
+    Text,
+    /// HTML with `<i>` for emphasis and links for DOIs and URLs.
+    Html,
+    /// Markdown with `*` for emphasis.
+    Markdown,
+}
+
+impl FromStr for Output {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "txt" => Ok(Self::Text),
+            "html" => Ok(Self::Html),
+            "markdown" | "md" => Ok(Self::Markdown),
+            _ => Err(Error::new(
+                ErrorKind::Deserialize,
+                format!("Unknown output '{s}' - expected one of text, html or markdown"),
+            )),
+        }
+    }
+}
+
+/// A part of a rendered reference.
+#[derive(Clone, Debug, PartialEq)]
+enum Span {
+    Text(String),
+    Italic(String),
+    Link(String),
+}
+
+/// A reference built up from [`Span`]s before being written in an [`Output`] markup.
+#[derive(Debug, Default)]
+struct Reference {
+    spans: Vec<Span>,
+}
+
+impl Reference {
+    fn text<S: Into<String>>(&mut self, text: S) -> &mut Self {
+        let text = text.into();
+        if !text.is_empty() {
+            if let Some(Span::Text(last)) = self.spans.last_mut() {
+                last.push_str(&text);
+            } else {
+                self.spans.push(Span::Text(text));
+            }
+        }
+        self
+    }
+
+    fn italic<S: Into<String>>(&mut self, text: S) -> &mut Self {
+        let text = text.into();
+        if !text.is_empty() {
+            self.spans.push(Span::Italic(text));
+        }
+        self
+    }
+
+    fn link<S: Into<String>>(&mut self, url: S) -> &mut Self {
+        self.spans.push(Span::Link(url.into()));
+        self
+    }
+
+    fn write(&self, output: Output) -> String {
+        self.spans
+            .iter()
+            .map(|span| match (output, span) {
+                (Output::Text, Span::Text(s) | Span::Italic(s) | Span::Link(s)) => s.clone(),
+                (Output::Html, Span::Link(s)) if is_web_link(s) => {
+                    let s = escape_html(s);
+                    format!("<a href=\"{s}\">{s}</a>")
+                }
+                (Output::Html, Span::Text(s) | Span::Link(s)) => escape_html(s),
+                (Output::Html, Span::Italic(s)) => format!("<i>{}</i>", escape_html(s)),
+                (Output::Markdown, Span::Link(s)) if is_web_link(s) => format!("<{s}>"),
+                (Output::Markdown, Span::Text(s) | Span::Link(s)) => escape_markdown(s),
+                (Output::Markdown, Span::Italic(s)) => format!("*{}*", escape_markdown(s)),
+            })
+            .collect::<String>()
+            .trim()
+            .to_owned()
+    }
+}
+
+/// Whether the url is a web or DOI url, which are the only urls rendered as links so that a url
+/// such as `javascript:alert()` in a field is never rendered as a link that can be followed.
+fn is_web_link(url: &str) -> bool {
+    let url = url.trim_start().to_lowercase();
+    ["http://", "https://", "doi:"]
+        .iter()
+        .any(|scheme| url.starts_with(scheme))
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_markdown(s: &str) -> String {
+    s.chars()
+        .fold(String::with_capacity(s.len()), |mut acc, c| {
+            if matches!(c, '\\' | '*' | '_' | '[' | ']' | '`' | '<' | '>') {
+                acc.push('\\');
+            }
+            acc.push(c);
+            acc
+        })
+}
+
+/// Renders entries as references in a [`Style`] and [`Output`] markup.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Renderer {
+    style: Style,
+    output: Output,
+}
+
+impl Renderer {
+    /// Creates a new [`Renderer`] for the style and output markup.
+    #[must_use]
+    pub const fn new(style: Style, output: Output) -> Self {
+        Self { style, output }
+    }
+
+    /// Renders a single entry as a reference.
+    ///
+    /// Numbered styles are rendered without the number, use [`Renderer::render_biblio`] for a
+    /// numbered reference list.
+    #[must_use]
+    pub fn render_entry(&self, entry: &Entry) -> String {
+        style::reference(self.style, entry).write(self.output)
+    }
+
+    /// Renders the entries of a [`Biblio`] as a reference list.
+    ///
+    /// Numbered styles keep the entries in cite key order, otherwise entries are sorted by the
+    /// author, year and title as the style expects.
+    #[must_use]
+    pub fn render_biblio(&self, biblio: &Biblio) -> String {
+        let mut entries = biblio.entries().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.cite());
+        self.render_entries(entries)
+    }
+
+    /// Renders the entries as a reference list.
+    ///
+    /// Numbered styles keep the entries in the order they are given, such as the order they are
+    /// cited in, otherwise entries are sorted by the author, year and title as the style expects.
+    #[must_use]
+    pub fn render_entries<'e, I>(&self, entries: I) -> String
+    where
+        I: IntoIterator<Item = &'e Entry>,
+    {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        if !self.style.is_numbered() {
+            entries.sort_by_cached_key(|entry| style::sort_key(entry));
+        }
+
+        let references = entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let reference = self.render_entry(entry);
+                match (self.style.is_numbered(), self.output) {
+                    (false, _) => reference,
+                    (true, Output::Markdown) => format!("\\[{}\\] {reference}", i + 1),
+                    (true, _) => format!("[{}] {reference}", i + 1),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match self.output {
+            Output::Text => references.join("\n"),
+            Output::Markdown => references.join("\n\n"),
+            Output::Html => {
+                let items = references
+                    .into_iter()
+                    .map(|reference| format!("  <li>{reference}</li>"))
+                    .collect::<Vec<_>>();
+                format!("<ul class=\"references\">\n{}\n</ul>", items.join("\n"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_are_escaped_for_the_output() {
+        let mut reference = Reference::default();
+        reference.text("A & B <c> ").italic("snake_case").text(". ");
+
+        assert_eq!("A & B <c> snake_case.", reference.write(Output::Text));
+        assert_eq!(
+            "A &amp; B &lt;c&gt; <i>snake_case</i>.",
+            reference.write(Output::Html)
+        );
+        assert_eq!(
+            "A & B \\<c\\> *snake\\_case*.",
+            reference.write(Output::Markdown)
+        );
+    }
+
+    #[test]
+    fn only_web_urls_are_links() {
+        let mut reference = Reference::default();
+        reference
+            .link("https://example.com")
+            .text(" ")
+            .link("javascript:alert(1)");
+
+        assert_eq!(
+            "<a href=\"https://example.com\">https://example.com</a> javascript:alert(1)",
+            reference.write(Output::Html)
+        );
+        assert_eq!(
+            "<https://example.com> javascript:alert(1)",
+            reference.write(Output::Markdown)
+        );
+    }
+
+    #[test]
+    fn numbered_styles_keep_the_order_of_the_entries() {
+        let first = crate::ast::manual_entry("zeta", &[("title", "First")]);
+        let second = crate::ast::manual_entry("alpha", &[("title", "Second")]);
+
+        let references = Renderer::new(Style::Ieee, Output::Text).render_entries([&first, &second]);
+
+        assert!(references.starts_with("[1] First"), "{references}");
+    }
+
+    #[test]
+    fn parse_style_and_output_names() {
+        assert_eq!(Style::ChicagoAuthorDate, "Chicago".parse().unwrap());
+        assert_eq!(Output::Markdown, "md".parse().unwrap());
+        assert!("mla".parse::<Style>().is_err());
+    }
+}
@@ -0,0 +1,176 @@
+/// A personal name split into the family and given names.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Name {
+    /// The family name, or the whole name of an organisation.
+    pub family: String,
+    /// The given names, which can be empty.
+    pub given: String,
+    /// The suffix of the name, such as `Jr.`, which can be empty.
+    pub suffix: String,
+}
+
+impl Name {
+    /// Parses a single name in the form `Family, Given`, `Family, Suffix, Given` or
+    /// `Given Family`, where the family name starts at a lowercase particle such as the `van` of
+    /// `Guido van Rossum`.
+    ///
+    /// A name with a single word, such as an organisation, only has a family name.
+    #[must_use]
+    pub fn parse(name: &str) -> Self {
+        let name = name.trim();
+        let parts = name.split(',').map(str::trim).collect::<Vec<_>>();
+        let (family, suffix, given) = match parts.as_slice() {
+            [family, suffix, given, ..] => ((*family).to_owned(), *suffix, (*given).to_owned()),
+            [family, given] => ((*family).to_owned(), "", (*given).to_owned()),
+            _ => {
+                let words = name.split_whitespace().collect::<Vec<_>>();
+                let given = words.split_last().map_or(&[][..], |(_, given)| given);
+                // the family name is the last word unless it starts at a particle
+                let at = given
+                    .iter()
+                    .position(|word| word.starts_with(char::is_lowercase))
+                    .unwrap_or(given.len());
+                (words[at..].join(" "), "", words[..at].join(" "))
+            }
+        };
+
+        Self {
+            family,
+            given,
+            suffix: suffix.to_owned(),
+        }
+    }
+
+    /// Parses a list of names separated by ` and `, such as the value of an `author` field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use seb::render::Name;
+    ///
+    /// let names = Name::parse_list("Knuth, Donald E. and Leslie Lamport");
+    ///
+    /// assert_eq!("Knuth", names[0].family);
+    /// assert_eq!("Lamport", names[1].family);
+    /// assert_eq!("L.", names[1].initials());
+    /// ```
+    #[must_use]
+    pub fn parse_list(names: &str) -> Vec<Self> {
+        names
+            .split(" and ")
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+
+    /// The initials of the given names, such as `D. E.` for `Donald Ervin` or `J.-P.` for
+    /// `Jean-Paul`.
+    #[must_use]
+    pub fn initials(&self) -> String {
+        self.given
+            .split_whitespace()
+            .map(|given| {
+                given
+                    .split('-')
+                    .filter_map(|part| part.chars().next())
+                    .map(|c| format!("{c}."))
+                    .collect::<Vec<_>>()
+                    .join("-")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The name in the form `Family, G.`, or only the family name when there are no given names.
+    #[must_use]
+    pub fn family_initials(&self) -> String {
+        if self.given.is_empty() {
+            self.family.clone()
+        } else {
+            format!("{}, {}", self.family, self.initials())
+        }
+    }
+
+    /// The name in the form `G. Family`, or only the family name when there are no given names.
+    #[must_use]
+    pub fn initials_family(&self) -> String {
+        if self.given.is_empty() {
+            self.family.clone()
+        } else {
+            format!("{} {}", self.initials(), self.family)
+        }
+    }
+
+    /// The name in the form `Family, Given`, or only the family name when there are no given
+    /// names.
+    #[must_use]
+    pub fn family_given(&self) -> String {
+        if self.given.is_empty() {
+            self.family.clone()
+        } else {
+            format!("{}, {}", self.family, self.given)
+        }
+    }
+
+    /// The name in the form `Given Family`, or only the family name when there are no given names.
+    #[must_use]
+    pub fn given_family(&self) -> String {
+        if self.given.is_empty() {
+            self.family.clone()
+        } else {
+            format!("{} {}", self.given, self.family)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_names_in_both_orders() {
+        assert_eq!(
+            Name {
+                family: "Peyton Jones".to_owned(),
+                given: "Simon".to_owned(),
+                suffix: String::new()
+            },
+            Name::parse("Peyton Jones, Simon")
+        );
+        assert_eq!(
+            Name {
+                family: "Knuth".to_owned(),
+                given: "Donald E.".to_owned(),
+                suffix: String::new()
+            },
+            Name::parse("Donald E. Knuth")
+        );
+        assert_eq!(
+            Name {
+                family: "van Rossum".to_owned(),
+                given: "Guido".to_owned(),
+                suffix: String::new()
+            },
+            Name::parse("Guido van Rossum")
+        );
+        assert_eq!(
+            Name {
+                family: "King".to_owned(),
+                given: "Martin Luther".to_owned(),
+                suffix: "Jr.".to_owned()
+            },
+            Name::parse("King, Jr., Martin Luther")
+        );
+        assert_eq!("UNESCO", Name::parse("UNESCO").family_initials());
+    }
+
+    #[test]
+    fn initials_of_hyphenated_names() {
+        let name = Name::parse("Sartre, Jean-Paul Charles");
+
+        assert_eq!("J.-P. C.", name.initials());
+        assert_eq!("Sartre, J.-P. C.", name.family_initials());
+        assert_eq!("J.-P. C. Sartre", name.initials_family());
+    }
+}
@@ -0,0 +1,729 @@
+use super::{Name, Reference, Style};
+use crate::ast::{Entry, EntryKind, FieldQuery};
+
+/// The shape of a reference, which decides the elements used for the entry in each style.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Shape {
+    /// An article in a journal.
+    Journal,
+    /// A part of a book or proceedings with its own title.
+    Chapter,
+    /// A thesis for a degree.
+    Thesis { masters: bool },
+    /// A report of an institution.
+    Report,
+    /// A standalone work such as a book.
+    Book,
+}
+
+/// The values of an entry used by the styles.
+struct Parts<'e> {
+    shape: Shape,
+    authors: Vec<Name>,
+    editors: Vec<Name>,
+    year: Option<&'e str>,
+    /// The title of the work, which is the title of the part for a [`Shape::Chapter`].
+    title: &'e str,
+    /// The title of the journal or book that contains the work.
+    container: Option<&'e str>,
+    volume: Option<&'e str>,
+    number: Option<&'e str>,
+    pages: Option<String>,
+    edition: Option<String>,
+    publisher: Option<&'e str>,
+    address: Option<&'e str>,
+    doi: Option<&'e str>,
+    url: Option<&'e str>,
+}
+
+fn field<'e>(entry: &'e Entry, name: &str) -> Option<&'e str> {
+    entry
+        .get_field(name)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+impl<'e> Parts<'e> {
+    fn new(entry: &'e Entry) -> Self {
+        let kind = entry.kind();
+        let shape = match kind {
+            EntryKind::Article => Shape::Journal,
+            EntryKind::BookChapter | EntryKind::BookSection | EntryKind::InProceedings => {
+                Shape::Chapter
+            }
+            EntryKind::MasterThesis => Shape::Thesis { masters: true },
+            EntryKind::PhdThesis => Shape::Thesis { masters: false },
+            EntryKind::TechReport => Shape::Report,
+            _ => Shape::Book,
+        };
+
+        let (title, container) = match kind {
+            EntryKind::Article => (entry.title().trim(), field(entry, "journal")),
+            // the title of a book chapter is the title of the book
+            EntryKind::BookChapter => (
+                field(entry, "chapter").unwrap_or_else(|| entry.title().trim()),
+                Some(entry.title().trim()),
+            ),
+            _ => (entry.title().trim(), field(entry, "book_title")),
+        };
+
+        let publisher = ["publisher", "school", "institution", "organization"]
+            .iter()
+            .find_map(|name| field(entry, name));
+
+        Self {
+            shape,
+            authors: field(entry, "author")
+                .map(Name::parse_list)
+                .unwrap_or_default(),
+            editors: field(entry, "editor")
+                .map(Name::parse_list)
+                .unwrap_or_default(),
+            year: field(entry, "year"),
+            title,
+            container,
+            volume: field(entry, "volume"),
+            number: field(entry, "number"),
+            pages: field(entry, "pages").map(|pages| pages.replace("--", "–").replace('-', "–")),
+            edition: field(entry, "edition").map(ordinal),
+            publisher,
+            address: field(entry, "address"),
+            doi: field(entry, "doi"),
+            url: field(entry, "url"),
+        }
+    }
+
+    fn doi_url(&self) -> Option<String> {
+        self.doi.map(|doi| {
+            if doi.starts_with("http") {
+                doi.to_owned()
+            } else {
+                format!("https://doi.org/{doi}")
+            }
+        })
+    }
+
+    /// The DOI as a link, otherwise the URL as a link.
+    fn link(&self, r: &mut Reference) {
+        if let Some(url) = self.doi_url().or_else(|| self.url.map(str::to_owned)) {
+            r.link(url);
+        }
+    }
+
+    /// `Address: Publisher`, or whichever of the two the entry has.
+    fn place_publisher(&self) -> Option<String> {
+        match (self.address, self.publisher) {
+            (Some(address), Some(publisher)) => Some(format!("{address}: {publisher}")),
+            (address, publisher) => address.or(publisher).map(str::to_owned),
+        }
+    }
+}
+
+/// `2` becomes `2nd` and any value that isn't a number is used as it is.
+fn ordinal(edition: &str) -> String {
+    let Ok(n) = edition.parse::<u32>() else {
+        return edition.to_owned();
+    };
+    let suffix = match (n % 10, n % 100) {
+        (1, 11) | (2, 12) | (3, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+/// Adds a full stop unless the text already ends with punctuation.
+fn sentence(text: &str) -> String {
+    if text.ends_with(['.', '?', '!']) {
+        text.to_owned()
+    } else {
+        format!("{text}.")
+    }
+}
+
+/// Quotes the text with the punctuation inside the quotes, unless the text already ends with a
+/// question or exclamation mark.
+fn quoted(text: &str, punctuation: &str, (open, close): (char, char)) -> String {
+    if text.ends_with(['?', '!']) {
+        format!("{open}{text}{close}")
+    } else {
+        format!("{open}{text}{punctuation}{close}")
+    }
+}
+
+/// Joins the names with `, ` and uses `last` before the final name.
+fn join_names(names: &[String], last: &str) -> String {
+    match names {
+        [] => String::new(),
+        [name] => name.clone(),
+        [first, second] if !last.starts_with(',') => format!("{first}{last}{second}"),
+        [rest @ .., final_name] => format!("{}{last}{final_name}", rest.join(", ")),
+    }
+}
+
+/// Joins names in `Given Family` order, with a serial comma before `and` when there are more than
+/// two names.
+fn join_serial(names: &[String], and: &str) -> String {
+    if names.len() == 2 {
+        join_names(names, &format!(" {and} "))
+    } else {
+        join_names(names, &format!(", {and} "))
+    }
+}
+
+fn apa_names(names: &[Name]) -> String {
+    let names = names.iter().map(Name::family_initials).collect::<Vec<_>>();
+    if names.len() > 20 {
+        return format!(
+            "{}, . . . {}",
+            names[..19].join(", "),
+            names.last().expect("More than 20 names")
+        );
+    }
+    join_names(&names, ", & ")
+}
+
+fn ieee_names(names: &[Name]) -> String {
+    let names = names.iter().map(Name::initials_family).collect::<Vec<_>>();
+    if names.len() > 6 {
+        return format!("{} et al.", names[0]);
+    }
+    join_serial(&names, "and")
+}
+
+fn chicago_names(names: &[Name]) -> String {
+    let names = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            if i == 0 {
+                name.family_given()
+            } else {
+                name.given_family()
+            }
+        })
+        .collect::<Vec<_>>();
+    if names.len() > 10 {
+        return format!("{}, et al.", names[..7].join(", "));
+    }
+    join_names(&names, ", and ")
+}
+
+fn harvard_names(names: &[Name]) -> String {
+    let names = names.iter().map(Name::family_initials).collect::<Vec<_>>();
+    if names.len() > 3 {
+        return format!("{} et al.", names[0]);
+    }
+    join_names(&names, " and ")
+}
+
+/// The key used to sort references by author, year and title.
+pub(super) fn sort_key(entry: &Entry) -> (String, String, String) {
+    let parts = Parts::new(entry);
+    let author = parts
+        .authors
+        .first()
+        .or_else(|| parts.editors.first())
+        .map_or_else(
+            || parts.title.to_lowercase(),
+            |name| name.family.to_lowercase(),
+        );
+
+    (
+        author,
+        parts.year.unwrap_or_default().to_owned(),
+        parts.title.to_lowercase(),
+    )
+}
+
+pub(super) fn reference(style: Style, entry: &Entry) -> Reference {
+    let parts = Parts::new(entry);
+    let mut r = Reference::default();
+    match style {
+        Style::Apa => apa(&parts, &mut r),
+        Style::Ieee => ieee(&parts, &mut r),
+        Style::ChicagoAuthorDate => chicago(&parts, &mut r),
+        Style::Harvard => harvard(&parts, &mut r),
+    }
+    r
+}
+
+/// Writes the title of a standalone work in italics, or quoted when it is part of a larger work.
+const fn is_part(shape: Shape) -> bool {
+    matches!(shape, Shape::Journal | Shape::Chapter)
+}
+
+fn apa(p: &Parts<'_>, r: &mut Reference) {
+    let year = p.year.unwrap_or("n.d.");
+
+    // the title moves to the author position when there are no authors or editors
+    let title_first = p.authors.is_empty() && p.editors.is_empty();
+    if title_first {
+        if is_part(p.shape) {
+            r.text(sentence(p.title));
+        } else {
+            r.italic(sentence(p.title));
+        }
+        r.text(format!(" ({year}). "));
+    } else if p.authors.is_empty() {
+        let eds = if p.editors.len() == 1 { "Ed." } else { "Eds." };
+        r.text(format!("{} ({eds}). ({year}). ", apa_names(&p.editors)));
+    } else {
+        r.text(format!("{} ({year}). ", apa_names(&p.authors)));
+    }
+
+    match p.shape {
+        Shape::Journal => {
+            if !title_first {
+                r.text(format!("{} ", sentence(p.title)));
+            }
+            if let Some(journal) = p.container {
+                r.italic(journal);
+                if let Some(volume) = p.volume {
+                    r.text(", ").italic(volume);
+                }
+                if let Some(number) = p.number {
+                    r.text(format!("({number})"));
+                }
+                if let Some(pages) = &p.pages {
+                    r.text(format!(", {pages}"));
+                }
+                r.text(". ");
+            }
+        }
+        Shape::Chapter => {
+            if !title_first {
+                r.text(format!("{} ", sentence(p.title)));
+            }
+            if let Some(container) = p.container {
+                r.text("In ");
+                if !p.editors.is_empty() && !p.authors.is_empty() {
+                    let eds = if p.editors.len() == 1 { "Ed." } else { "Eds." };
+                    let editors = p
+                        .editors
+                        .iter()
+                        .map(Name::initials_family)
+                        .collect::<Vec<_>>();
+                    r.text(format!("{} ({eds}), ", join_serial(&editors, "&")));
+                }
+                r.italic(container);
+                if let Some(pages) = &p.pages {
+                    r.text(format!(" (pp. {pages})"));
+                }
+                r.text(". ");
+            }
+            if let Some(publisher) = p.publisher {
+                r.text(format!("{} ", sentence(publisher)));
+            }
+        }
+        Shape::Thesis { masters } => {
+            if !title_first {
+                r.italic(p.title);
+            }
+            let degree = if masters {
+                "Master's thesis"
+            } else {
+                "Doctoral dissertation"
+            };
+            match p.publisher {
+                Some(school) => r.text(format!(" [{degree}, {school}]. ")),
+                None => r.text(format!(" [{degree}]. ")),
+            };
+        }
+        Shape::Report | Shape::Book => {
+            if !title_first {
+                r.italic(p.title);
+            }
+            let mut details = Vec::new();
+            if let Some(edition) = &p.edition {
+                details.push(format!("{edition} ed."));
+            }
+            if let (Shape::Report, Some(number)) = (p.shape, p.number) {
+                details.push(format!("Report No. {number}"));
+            }
+            if let (Shape::Book, Some(pages)) = (p.shape, &p.pages) {
+                details.push(format!("pp. {pages}"));
+            }
+            if !details.is_empty() {
+                r.text(format!(" ({})", details.join(", ")));
+            }
+            if !title_first {
+                r.text(". ");
+            }
+            if let Some(publisher) = p.publisher {
+                r.text(format!("{} ", sentence(publisher)));
+            }
+        }
+    }
+
+    p.link(r);
+}
+
+fn ieee(p: &Parts<'_>, r: &mut Reference) {
+    let names = if p.authors.is_empty() {
+        &p.editors
+    } else {
+        &p.authors
+    };
+    if !names.is_empty() {
+        r.text(format!("{}, ", ieee_names(names)));
+    }
+
+    let mut tail = Vec::new();
+    match p.shape {
+        Shape::Journal => {
+            r.text(format!("{} ", quoted(p.title, ",", ('“', '”'))));
+            if let Some(journal) = p.container {
+                r.italic(journal).text(", ");
+            }
+            tail.extend(p.volume.map(|volume| format!("vol. {volume}")));
+            tail.extend(p.number.map(|number| format!("no. {number}")));
+            tail.extend(p.pages.as_ref().map(|pages| format!("pp. {pages}")));
+            tail.extend(p.year.map(str::to_owned));
+        }
+        Shape::Chapter => {
+            r.text(format!("{} ", quoted(p.title, ",", ('“', '”'))));
+            if let Some(container) = p.container {
+                r.text("in ").italic(container);
+                if !p.editors.is_empty() && !p.authors.is_empty() {
+                    let eds = if p.editors.len() == 1 { "Ed." } else { "Eds." };
+                    r.text(format!(", {}, {eds}", ieee_names(&p.editors)));
+                }
+                r.text(" ");
+            }
+            let published = [p.place_publisher(), p.year.map(str::to_owned)];
+            tail.push(
+                published
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+            tail.extend(p.pages.as_ref().map(|pages| format!("pp. {pages}")));
+        }
+        Shape::Thesis { masters } => {
+            r.text(format!("{} ", quoted(p.title, ",", ('“', '”'))));
+            tail.push(
+                if masters {
+                    "M.S. thesis"
+                } else {
+                    "Ph.D. dissertation"
+                }
+                .to_owned(),
+            );
+            tail.extend(p.publisher.map(str::to_owned));
+            tail.extend(p.address.map(str::to_owned));
+            tail.extend(p.year.map(str::to_owned));
+        }
+        Shape::Report => {
+            r.text(format!("{} ", quoted(p.title, ",", ('“', '”'))));
+            tail.extend(p.publisher.map(str::to_owned));
+            tail.extend(p.address.map(str::to_owned));
+            match p.number {
+                Some(number) => tail.push(format!("Tech. Rep. {number}")),
+                None => tail.push("Tech. Rep.".to_owned()),
+            }
+            tail.extend(p.year.map(str::to_owned));
+        }
+        Shape::Book => {
+            r.italic(p.title);
+            r.text(match &p.edition {
+                Some(edition) => format!(", {edition} ed. "),
+                None => ". ".to_owned(),
+            });
+            let published = [p.place_publisher(), p.year.map(str::to_owned)];
+            tail.push(
+                published
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+            tail.extend(p.pages.as_ref().map(|pages| format!("pp. {pages}")));
+        }
+    }
+
+    tail.retain(|part| !part.is_empty());
+    if !tail.is_empty() {
+        r.text(format!("{}. ", tail.join(", ")));
+    }
+
+    if let Some(doi) = p.doi {
+        r.text(format!(
+            "doi: {}.",
+            doi.trim_start_matches("https://doi.org/")
+        ));
+    } else if let Some(url) = p.url {
+        r.text("[Online]. Available: ").link(url);
+    }
+}
+
+fn chicago(p: &Parts<'_>, r: &mut Reference) {
+    let year = p.year.unwrap_or("n.d.");
+    if !p.authors.is_empty() {
+        r.text(format!("{} ", sentence(&chicago_names(&p.authors))));
+    } else if !p.editors.is_empty() {
+        let eds = if p.editors.len() == 1 { "ed." } else { "eds." };
+        r.text(format!("{}, {eds} ", chicago_names(&p.editors)));
+    }
+    r.text(format!("{year}. "));
+
+    match p.shape {
+        Shape::Journal => {
+            r.text(format!("{} ", quoted(p.title, ".", ('“', '”'))));
+            if let Some(journal) = p.container {
+                r.italic(journal);
+                if let Some(volume) = p.volume {
+                    r.text(format!(" {volume}"));
+                }
+                if let Some(number) = p.number {
+                    r.text(format!(" ({number})"));
+                }
+                if let Some(pages) = &p.pages {
+                    r.text(format!(": {pages}"));
+                }
+                r.text(". ");
+            }
+        }
+        Shape::Chapter => {
+            r.text(format!("{} ", quoted(p.title, ".", ('“', '”'))));
+            if let Some(container) = p.container {
+                r.text("In ").italic(container);
+                if !p.editors.is_empty() && !p.authors.is_empty() {
+                    let editors = p.editors.iter().map(Name::given_family).collect::<Vec<_>>();
+                    r.text(format!(", edited by {}", join_serial(&editors, "and")));
+                }
+                if let Some(pages) = &p.pages {
+                    r.text(format!(", {pages}"));
+                }
+                r.text(". ");
+            }
+            if let Some(published) = p.place_publisher() {
+                r.text(format!("{} ", sentence(&published)));
+            }
+        }
+        Shape::Thesis { masters } => {
+            r.text(format!("{} ", quoted(p.title, ".", ('“', '”'))));
+            let degree = if masters {
+                "Master's thesis"
+            } else {
+                "PhD diss."
+            };
+            match p.publisher {
+                Some(school) => r.text(format!(
+                    "{degree}, {}. ",
+                    sentence(school).trim_end_matches('.')
+                )),
+                None => r.text(format!("{} ", sentence(degree))),
+            };
+        }
+        Shape::Report | Shape::Book => {
+            r.italic(sentence(p.title)).text(" ");
+            if let Some(edition) = &p.edition {
+                r.text(format!("{edition} ed. "));
+            }
+            if let (Shape::Report, Some(number)) = (p.shape, p.number) {
+                r.text(format!("Report {number}. "));
+            }
+            if let Some(published) = p.place_publisher() {
+                r.text(format!("{} ", sentence(&published)));
+            }
+        }
+    }
+
+    if let Some(url) = p.doi_url().or_else(|| p.url.map(str::to_owned)) {
+        r.link(url).text(".");
+    }
+}
+
+fn harvard(p: &Parts<'_>, r: &mut Reference) {
+    let year = p.year.unwrap_or("no date");
+    if !p.authors.is_empty() {
+        r.text(format!("{} ", harvard_names(&p.authors)));
+    } else if !p.editors.is_empty() {
+        let eds = if p.editors.len() == 1 { "ed." } else { "eds." };
+        r.text(format!("{} ({eds}) ", harvard_names(&p.editors)));
+    }
+    r.text(format!("({year}) "));
+
+    match p.shape {
+        Shape::Journal => {
+            r.text(format!("‘{}’", p.title));
+            if let Some(journal) = p.container {
+                r.text(", ").italic(journal);
+                if let Some(volume) = p.volume {
+                    r.text(format!(", {volume}"));
+                }
+                if let Some(number) = p.number {
+                    r.text(format!("({number})"));
+                }
+                if let Some(pages) = &p.pages {
+                    r.text(format!(", pp. {pages}"));
+                }
+            }
+            r.text(". ");
+        }
+        Shape::Chapter => {
+            r.text(format!("‘{}’", p.title));
+            if let Some(container) = p.container {
+                r.text(", in ");
+                if !p.editors.is_empty() && !p.authors.is_empty() {
+                    let eds = if p.editors.len() == 1 { "ed." } else { "eds." };
+                    r.text(format!("{} ({eds}) ", harvard_names(&p.editors)));
+                }
+                r.italic(container);
+            }
+            r.text(". ");
+            let published = [
+                p.place_publisher(),
+                p.pages.as_ref().map(|pages| format!("pp. {pages}")),
+            ];
+            let published = published.into_iter().flatten().collect::<Vec<_>>();
+            if !published.is_empty() {
+                r.text(format!("{}. ", published.join(", ")));
+            }
+        }
+        Shape::Thesis { masters } => {
+            r.italic(p.title).text(". ");
+            r.text(if masters {
+                "Master's thesis. "
+            } else {
+                "PhD thesis. "
+            });
+            if let Some(school) = p.publisher {
+                r.text(format!("{} ", sentence(school)));
+            }
+        }
+        Shape::Report | Shape::Book => {
+            r.italic(p.title).text(". ");
+            if let Some(edition) = &p.edition {
+                r.text(format!("{edition} edn. "));
+            }
+            if let (Shape::Report, Some(number)) = (p.shape, p.number) {
+                r.text(format!("Report {number}. "));
+            }
+            if let Some(published) = p.place_publisher() {
+                r.text(format!("{} ", sentence(&published)));
+            }
+        }
+    }
+
+    if let Some(url) = p.doi_url().or_else(|| p.url.map(str::to_owned)) {
+        r.text("Available at: ").link(url).text(".");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{Output, Renderer};
+
+    fn article() -> Entry {
+        let mut resolver = Entry::resolver_with_cite(EntryKind::Article, "knuth1984");
+        resolver.author("Knuth, Donald E. and Levy, Silvio");
+        resolver.title("Literate Programming");
+        resolver.journal("The Computer Journal");
+        resolver.year("1984");
+        resolver.set_field("volume", "27");
+        resolver.set_field("number", "2");
+        resolver.set_field("pages", "97--111");
+        resolver.set_field("doi", "10.1093/comjnl/27.2.97");
+        resolver.resolve().expect("All article fields are set")
+    }
+
+    fn book_section() -> Entry {
+        let mut resolver = Entry::resolver_with_cite(EntryKind::BookSection, "section");
+        resolver.author("Wadler, Philip");
+        resolver.title("Monads for functional programming");
+        resolver.book_title("Advanced Functional Programming");
+        resolver.publisher("Springer");
+        resolver.year("1995");
+        resolver.set_field("editor", "Jeuring, Johan and Meijer, Erik");
+        resolver.set_field("pages", "24-52");
+        resolver.set_field("address", "Berlin");
+        resolver.resolve().expect("All book section fields are set")
+    }
+
+    fn render(style: Style, entry: &Entry) -> String {
+        Renderer::new(style, Output::Text).render_entry(entry)
+    }
+
+    #[test]
+    fn journal_article_in_each_style() {
+        let entry = article();
+
+        assert_eq!(
+            "Knuth, D. E., & Levy, S. (1984). Literate Programming. The Computer Journal, 27(2), \
+             97–111. https://doi.org/10.1093/comjnl/27.2.97",
+            render(Style::Apa, &entry)
+        );
+        assert_eq!(
+            "D. E. Knuth and S. Levy, “Literate Programming,” The Computer Journal, vol. 27, \
+             no. 2, pp. 97–111, 1984. doi: 10.1093/comjnl/27.2.97.",
+            render(Style::Ieee, &entry)
+        );
+        assert_eq!(
+            "Knuth, Donald E., and Silvio Levy. 1984. “Literate Programming.” The Computer \
+             Journal 27 (2): 97–111. https://doi.org/10.1093/comjnl/27.2.97.",
+            render(Style::ChicagoAuthorDate, &entry)
+        );
+        assert_eq!(
+            "Knuth, D. E. and Levy, S. (1984) ‘Literate Programming’, The Computer Journal, \
+             27(2), pp. 97–111. Available at: https://doi.org/10.1093/comjnl/27.2.97.",
+            render(Style::Harvard, &entry)
+        );
+    }
+
+    #[test]
+    fn book_section_in_each_style() {
+        let entry = book_section();
+
+        assert_eq!(
+            "Wadler, P. (1995). Monads for functional programming. In J. Jeuring & E. Meijer \
+             (Eds.), Advanced Functional Programming (pp. 24–52). Springer.",
+            render(Style::Apa, &entry)
+        );
+        assert_eq!(
+            "P. Wadler, “Monads for functional programming,” in Advanced Functional Programming, \
+             J. Jeuring and E. Meijer, Eds. Berlin: Springer, 1995, pp. 24–52.",
+            render(Style::Ieee, &entry)
+        );
+        assert_eq!(
+            "Wadler, Philip. 1995. “Monads for functional programming.” In Advanced Functional \
+             Programming, edited by Johan Jeuring and Erik Meijer, 24–52. Berlin: Springer.",
+            render(Style::ChicagoAuthorDate, &entry)
+        );
+        assert_eq!(
+            "Wadler, P. (1995) ‘Monads for functional programming’, in Jeuring, J. and Meijer, \
+             E. (eds.) Advanced Functional Programming. Berlin: Springer, pp. 24–52.",
+            render(Style::Harvard, &entry)
+        );
+    }
+
+    #[test]
+    fn name_lists_are_shortened() {
+        let names = Name::parse_list(
+            "A, A and B, B and C, C and D, D and E, E and F, F and G, G and H, H and I, I and \
+             J, J and K, K",
+        );
+
+        assert_eq!("A. A et al.", ieee_names(&names));
+        assert_eq!("A, A. et al.", harvard_names(&names));
+        assert_eq!(
+            "A, A, B B, C C, D D, E E, F F, G G, et al.",
+            chicago_names(&names)
+        );
+        assert_eq!("A, A., & B, B.", apa_names(&names[..2]));
+    }
+
+    #[test]
+    fn ordinal_editions() {
+        assert_eq!("1st", ordinal("1"));
+        assert_eq!("2nd", ordinal("2"));
+        assert_eq!("11th", ordinal("11"));
+        assert_eq!("23rd", ordinal("23"));
+        assert_eq!("Revised", ordinal("Revised"));
+    }
+}
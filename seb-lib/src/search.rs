@@ -0,0 +1,418 @@
+//! Local full-text search over the entries of a [`Biblio`].
+//!
+//! Text is tokenized and compared case-insensitively with diacritics removed, so that a query of
+//! `godel` matches a title containing `Gödel`. Matches are ranked by the field they are found in
+//! with the `title` field weighted the most followed by `author`, `keywords` and `abstract`.
+//!
+//! # Query syntax
+//!
+//! | Term                | Matches entries where                                     |
+//! |---------------------|-----------------------------------------------------------|
+//! | `borrow`            | any searched field contains a word starting with `borrow` |
+//! | `"borrow checking"` | any searched field contains the phrase                    |
+//! | `author:knuth`      | the `author` field contains a word starting with `knuth`  |
+//! | `year:>2010`        | the `year` field is a number greater than `2010`          |
+//!
+//! The comparison operators `>`, `>=`, `<`, `<=` and `=` are supported for field scoped terms
+//! and all the terms of a query must match for an entry to be found.
+//!
+//! # Examples
+//!
+//! ```
+//! use seb::{ast::{Biblio, Entry, EntryKind}, search::Query};
+//!
+//! let mut resolver = Entry::resolver_with_cite(EntryKind::Manual, "knuth");
+//! resolver.title("The Art of Computer Programming");
+//! resolver.author("Donald Knuth");
+//! resolver.year("1968");
+//! let biblio = Biblio::new(vec![resolver.resolve().unwrap()]);
+//!
+//! let query: Query = "art author:knuth year:<1970".parse().unwrap();
+//! let hits = biblio.search(&query);
+//!
+//! assert_eq!("knuth", hits[0].entry().cite());
+//! ```
+
+use std::{cmp::Ordering, str::FromStr};
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+use crate::{
+    ast::{Biblio, Entry, FieldQuery},
+    Error, ErrorKind,
+};
+
+/// The fields searched by unscoped terms and the weight of a match in each field.
+const WEIGHTED_FIELDS: [(&str, f64); 4] = [
+    ("title", 4.0),
+    ("author", 3.0),
+    ("keywords", 2.0),
+    ("abstract", 1.0),
+];
+
+/// Normalizes text for comparison by removing diacritics and converting it to lowercase.
+#[must_use]
+pub fn normalize(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Splits normalized text into word tokens.
+#[must_use]
+pub fn tokenize(text: &str) -> Vec<String> {
+    normalize(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// A comparison operator used in numeric field scoped terms.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+}
+
+impl Comparison {
+    fn matches(self, value: f64, target: f64) -> bool {
+        match value.partial_cmp(&target) {
+            Some(Ordering::Greater) => {
+                matches!(self, Self::Greater | Self::GreaterOrEqual)
+            }
+            Some(Ordering::Less) => matches!(self, Self::Less | Self::LessOrEqual),
+            Some(Ordering::Equal) => {
+                matches!(self, Self::GreaterOrEqual | Self::LessOrEqual | Self::Equal)
+            }
+            None => false,
+        }
+    }
+}
+
+/// A single term of a [`Query`].
+#[derive(Clone, Debug, PartialEq)]
+enum Term {
+    /// A sequence of tokens that must appear together, optionally in a specific field.
+    Text {
+        field: Option<String>,
+        tokens: Vec<String>,
+    },
+    /// A numeric comparison against the value of a field.
+    Compare {
+        field: String,
+        comparison: Comparison,
+        value: f64,
+    },
+}
+
+impl Term {
+    fn parse(field: Option<&str>, value: &str) -> Result<Option<Self>, Error> {
+        let field = field.map(str::to_lowercase);
+
+        if let Some(field) = &field {
+            let ops = [
+                (">=", Comparison::GreaterOrEqual),
+                ("<=", Comparison::LessOrEqual),
+                (">", Comparison::Greater),
+                ("<", Comparison::Less),
+                ("=", Comparison::Equal),
+            ];
+
+            if let Some((op, comparison)) = ops.iter().find(|(op, _)| value.starts_with(op)) {
+                let number = &value[op.len()..];
+                let value = number.trim().parse().map_err(|e| {
+                    Error::wrap_with(
+                        ErrorKind::Deserialize,
+                        e,
+                        format!("'{number}' in the search term '{field}:{value}' is not a number"),
+                    )
+                })?;
+
+                return Ok(Some(Self::Compare {
+                    field: field.clone(),
+                    comparison: *comparison,
+                    value,
+                }));
+            }
+        }
+
+        let tokens = tokenize(value);
+        Ok((!tokens.is_empty()).then_some(Self::Text { field, tokens }))
+    }
+
+    /// Returns the score of this term for the entry or `None` if the entry does not match.
+    fn score(&self, entry: &Entry) -> Option<f64> {
+        match self {
+            Self::Text {
+                field: Some(field),
+                tokens,
+            } => entry
+                .get_field(field)
+                .and_then(|value| text_score(&tokenize(value), tokens)),
+            Self::Text {
+                field: None,
+                tokens,
+            } => {
+                let score: f64 = WEIGHTED_FIELDS
+                    .iter()
+                    .filter_map(|(field, weight)| {
+                        let value = entry.get_field(field)?;
+                        text_score(&tokenize(value), tokens).map(|score| score * weight)
+                    })
+                    .sum();
+                (score > 0.0).then_some(score)
+            }
+            Self::Compare {
+                field,
+                comparison,
+                value,
+            } => entry
+                .get_field(field)
+                .and_then(|v| leading_number(v))
+                .filter(|v| comparison.matches(*v, *value))
+                .map(|_| 1.0),
+        }
+    }
+}
+
+/// Parses the leading number in a field value, such as the `2019` in `2019-05`.
+fn leading_number(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+/// Scores the `needle` tokens appearing in sequence in the `haystack` tokens.
+///
+/// The last token of the needle may match the start of a token so that partial words can be
+/// searched for, while an exact match scores higher than a partial match.
+fn text_score(haystack: &[String], needle: &[String]) -> Option<f64> {
+    let (last, init) = needle.split_last()?;
+
+    let score: f64 = haystack
+        .windows(needle.len())
+        .filter(|window| window[..init.len()] == *init)
+        .filter_map(|window| {
+            let candidate = &window[init.len()];
+            if candidate == last {
+                Some(1.0)
+            } else if candidate.starts_with(last.as_str()) {
+                Some(0.5)
+            } else {
+                None
+            }
+        })
+        .sum();
+
+    (score > 0.0).then_some(score)
+}
+
+/// A parsed search query, see the [module documentation](self) for the syntax.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+impl Query {
+    /// Returns `true` when the query has no terms and so will match no entries.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Returns the score of the entry for this query or `None` if it does not match.
+    #[must_use]
+    pub fn score(&self, entry: &Entry) -> Option<f64> {
+        if self.terms.is_empty() {
+            return None;
+        }
+
+        self.terms
+            .iter()
+            .map(|term| term.score(entry))
+            .sum::<Option<f64>>()
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut terms = Vec::new();
+        let mut chars = s.chars().peekable();
+
+        while chars.peek().is_some() {
+            // skip the whitespace between terms
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+            let mut field = None;
+            let mut value = String::new();
+
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                match c {
+                    '"' => {
+                        value.extend(chars.by_ref().take_while(|c| *c != '"'));
+                    }
+                    ':' if field.is_none() && !value.is_empty() => {
+                        field = Some(std::mem::take(&mut value));
+                    }
+                    c => value.push(c),
+                }
+            }
+
+            if let Some(term) = Term::parse(field.as_deref(), &value)? {
+                terms.push(term);
+            }
+        }
+
+        Ok(Self { terms })
+    }
+}
+
+/// An entry that matches a [`Query`] with the score of the match.
+#[derive(Debug, PartialEq)]
+pub struct Hit<'biblio> {
+    entry: &'biblio Entry,
+    score: f64,
+}
+
+impl<'biblio> Hit<'biblio> {
+    /// The entry that matched the query.
+    #[must_use]
+    pub const fn entry(&self) -> &'biblio Entry {
+        self.entry
+    }
+
+    /// The score of the match, a higher score is a better match.
+    #[must_use]
+    pub const fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+impl Biblio {
+    /// Searches the entries using the query and returns the entries that match from the best match
+    /// to the worst.
+    ///
+    /// Entries with the same score are ordered by their cite key.
+    #[must_use]
+    pub fn search(&self, query: &Query) -> Vec<Hit<'_>> {
+        let mut hits = self
+            .entries()
+            .filter_map(|entry| query.score(entry).map(|score| Hit { entry, score }))
+            .collect::<Vec<_>>();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.entry.cite().cmp(b.entry.cite()))
+        });
+
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::manual_entry;
+
+    fn search<'a>(biblio: &'a Biblio, query: &str) -> Vec<&'a str> {
+        let query = query.parse().expect("Valid query");
+        biblio
+            .search(&query)
+            .into_iter()
+            .map(|hit| hit.entry().cite())
+            .collect()
+    }
+
+    fn biblio() -> Biblio {
+        Biblio::new(vec![
+            manual_entry(
+                "rust",
+                &[
+                    ("title", "Borrow checking in Rust"),
+                    ("author", "Ferris Crab"),
+                    ("year", "2019"),
+                ],
+            ),
+            manual_entry(
+                "godel",
+                &[
+                    ("title", "Über formal unentscheidbare Sätze"),
+                    ("author", "Kurt Gödel"),
+                    ("year", "1931"),
+                ],
+            ),
+            manual_entry(
+                "abstract",
+                &[
+                    ("title", "Ownership types"),
+                    ("abstract", "A look at borrow checking"),
+                    ("year", "2012"),
+                ],
+            ),
+        ])
+    }
+
+    #[test]
+    fn normalize_removes_diacritics_and_case() {
+        assert_eq!("godel uber", normalize("Gödel ÜBER"));
+        assert_eq!(vec!["http", "1", "1"], tokenize("{HTTP/1.1}"));
+    }
+
+    #[test]
+    fn diacritic_and_case_insensitive_search() {
+        let biblio = biblio();
+
+        assert_eq!(vec!["godel"], search(&biblio, "GODEL"));
+        assert_eq!(vec!["godel"], search(&biblio, "satze"));
+    }
+
+    #[test]
+    fn title_matches_rank_above_abstract_matches() {
+        let biblio = biblio();
+
+        assert_eq!(
+            vec!["rust", "abstract"],
+            search(&biblio, "\"borrow checking\"")
+        );
+        assert_eq!(vec!["rust", "abstract"], search(&biblio, "borrow check"));
+    }
+
+    #[test]
+    fn field_scoped_and_comparison_terms() {
+        let biblio = biblio();
+
+        assert_eq!(vec!["godel"], search(&biblio, "author:godel"));
+        assert_eq!(vec!["abstract", "rust"], search(&biblio, "year:>2010"));
+        assert_eq!(vec!["rust"], search(&biblio, "borrow year:>=2019"));
+        assert_eq!(
+            Vec::<&str>::new(),
+            search(&biblio, "author:godel year:>2010")
+        );
+    }
+
+    #[test]
+    fn invalid_comparison_is_an_error() {
+        let err = "year:>recent".parse::<Query>().unwrap_err();
+
+        assert_eq!(ErrorKind::Deserialize, err.kind());
+    }
+
+    #[test]
+    fn empty_query_matches_nothing() {
+        let query: Query = "  ".parse().unwrap();
+
+        assert!(query.is_empty());
+        assert!(biblio().search(&query).is_empty());
+    }
+}